@@ -0,0 +1,99 @@
+//! Throughput of `Simulation::handle_event` for the events sent by the command line, measured
+//! against a simulation which already holds many nodes.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+use node_simulator::{node, simulation::Simulation};
+
+const NODE_COUNTS: [u32; 3] = [100, 1_000, 10_000];
+const EVENT_COUNT: u32 = 1_000;
+
+fn create_simulation(node_count: u32) -> Simulation {
+    let mut simulation = Simulation::new();
+    for id in 0..node_count {
+        let position = (id as node::Scalar, 0.0, 0.0);
+        simulation.add_node(node::Node::new(node::Id(id), position.into()));
+    }
+    simulation
+}
+
+fn set_node_event(id: u32) -> node::Event {
+    node::Event::SetNode(node::event::set_node::SetNodeEvent {
+        id: node::Id(id),
+        position: None,
+        velocity: Some((1.0, 0.0, 0.0).into()),
+        position_relative_to: None,
+        velocity_relative_to: None,
+        mass: Some(2.0),
+        radius: None,
+        gravitational_constant_override: None,
+        dampen_rate: None,
+        drag_coefficient: None,
+        freeze: None,
+        locked_axes: None,
+        constraint: None,
+        kinematic: None,
+    })
+}
+
+fn add_and_remove_events(first_id: u32) -> Vec<node::Event> {
+    (first_id..first_id + EVENT_COUNT)
+        .flat_map(|id| {
+            let node = node::Node::new(node::Id(id), (0.0, id as node::Scalar, 0.0).into());
+            [
+                node::Event::AddNode(node::AddNodeEvent {
+                    node,
+                    position_relative_to: None,
+                }),
+                node::Event::RemoveNode(node::RemoveNodeEvent {
+                    node_id: node::Id(id),
+                }),
+            ]
+        })
+        .collect()
+}
+
+fn event_handling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("handle_event");
+    group.throughput(Throughput::Elements(EVENT_COUNT as u64));
+    for node_count in NODE_COUNTS {
+        let simulation = create_simulation(node_count);
+        group.bench_with_input(
+            BenchmarkId::new("set_node", node_count),
+            &node_count,
+            |b, node_count| {
+                b.iter_batched(
+                    || {
+                        let events = (0..EVENT_COUNT).map(|i| set_node_event(i % node_count));
+                        (simulation.clone(), events.collect::<Vec<_>>())
+                    },
+                    |(mut simulation, events)| {
+                        events
+                            .into_iter()
+                            .for_each(|event| simulation.handle_event(event).unwrap())
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("add_and_remove_node", node_count),
+            &node_count,
+            |b, node_count| {
+                b.iter_batched(
+                    || (simulation.clone(), add_and_remove_events(*node_count)),
+                    |(mut simulation, events)| {
+                        events
+                            .into_iter()
+                            .for_each(|event| simulation.handle_event(event).unwrap())
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, event_handling);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+//! Time taken to build the instance buffer contents that are uploaded to the GPU every frame.
+
+use cgmath::Zero;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use node_simulator::graphics::instances::{instance, instance_collection::InstanceCollection};
+use node_simulator::graphics::ModelId;
+
+const INSTANCE_COUNTS: [u32; 4] = [1_000, 10_000, 100_000, 1_000_000];
+const MODEL_COUNT: u32 = 4;
+
+/// Instances shared evenly between a few models
+fn create_collections(instance_count: u32) -> Vec<InstanceCollection> {
+    (0..MODEL_COUNT)
+        .map(|model| {
+            let mut collection = InstanceCollection::new(ModelId(model));
+            for i in 0..instance_count / MODEL_COUNT {
+                collection.add(instance::Instance {
+                    position: cgmath::Point3::new(i as f32, model as f32, 0.0),
+                    rotation: cgmath::Quaternion::zero(),
+                    scale: 1.0,
+                });
+            }
+            collection
+        })
+        .collect()
+}
+
+fn instance_render_data(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_instance_render_data");
+    group.sample_size(20);
+    for instance_count in INSTANCE_COUNTS {
+        let collections = create_collections(instance_count);
+        group.throughput(Throughput::Elements(instance_count as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(instance_count),
+            &collections,
+            |b, collections| b.iter(|| InstanceCollection::get_instance_render_data(collections)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, instance_render_data);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+//! Time taken to step simulations of increasing size. Compare a serial and a parallel build with
+//! `cargo bench --bench step_scaling` and `cargo bench --bench step_scaling --features parallel`.
+//! The number of threads used can be limited with `RAYON_NUM_THREADS`.
+
+use std::time::Duration;
+
+use criterion::{
+    criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion, SamplingMode,
+    Throughput,
+};
+
+use node_simulator::{node, simulation::Simulation};
+
+const NODE_COUNTS: [u32; 4] = [300, 1_000, 3_000, 10_000];
+
+/// Every step is O(n²), so these take seconds each, and are sampled in their own group with fewer
+/// and longer measurements
+const LARGE_NODE_COUNTS: [u32; 2] = [30_000, 100_000];
+
+/// Nodes spread over a cube, one unit apart
+fn create_simulation(node_count: u32) -> Simulation {
+    let mut simulation = Simulation::new();
+    let side = (node_count as f64).cbrt().ceil() as u32;
+    for id in 0..node_count {
+        let (x, y, z) = (id % side, (id / side) % side, id / (side * side));
+        let position = (x as node::Scalar, y as node::Scalar, z as node::Scalar);
+        simulation.add_node(node::Node::new(node::Id(id), position.into()));
+    }
+    simulation
+}
+
+fn bench_node_counts<M: criterion::measurement::Measurement>(
+    group: &mut BenchmarkGroup<M>,
+    node_counts: &[u32],
+) {
+    for &node_count in node_counts {
+        let mut simulation = create_simulation(node_count);
+        group.throughput(Throughput::Elements(node_count as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(node_count),
+            &node_count,
+            |b, _| b.iter(|| simulation.step()),
+        );
+    }
+}
+
+fn step_scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step");
+    group.sample_size(10);
+    bench_node_counts(&mut group, &NODE_COUNTS);
+    group.finish();
+
+    let mut group = c.benchmark_group("step_large");
+    group
+        .sample_size(10)
+        .sampling_mode(SamplingMode::Flat)
+        .measurement_time(Duration::from_secs(120));
+    bench_node_counts(&mut group, &LARGE_NODE_COUNTS);
+    group.finish();
+}
+
+criterion_group!(benches, step_scaling);
+criterion_main!(benches);
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::{command, Parser};
 
 /// Program for running node-based simulations
@@ -9,4 +11,25 @@ pub struct CLIArgs {
     pub default_texture: Option<String>,
     #[arg(long, default_value_t = false)]
     pub no_display: bool,
+    /// Directory to look for scripts in, when they are not found relative to the working directory
+    /// or the script running them. May be given more than once
+    #[arg(long)]
+    pub script_path: Vec<PathBuf>,
+    /// File to keep the history of commands typed into the CLI in. Defaults to
+    /// .node_simulator_history in the home directory
+    #[arg(long)]
+    pub history_file: Option<PathBuf>,
+    /// Script to run on start up, before reading commands from the CLI
+    #[arg(long)]
+    pub script: Option<String>,
+    /// Number of steps to take on start up, after running the script
+    #[arg(long)]
+    pub steps: Option<u32>,
+    /// Command to run on start up, after taking the steps. May be given more than once
+    #[arg(long)]
+    pub exec: Vec<String>,
+    /// Exit once the start up script, steps and commands have run, rather than reading commands
+    /// from the CLI. The exit code is 1 if any of them gave an error
+    #[arg(long, default_value_t = false)]
+    pub exit_after: bool,
 }
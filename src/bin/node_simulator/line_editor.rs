@@ -0,0 +1,283 @@
+use std::env;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use clap::CommandFactory;
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+
+use node_simulator::simulation::Simulation;
+
+use crate::simulation_commands::SimulationCommand;
+
+const HISTORY_FILE_NAME: &str = ".node_simulator_history";
+
+/// Reads commands typed into a terminal, with history and completion of commands, flags and ids
+pub struct LineEditor {
+    editor: Editor<CommandHelper, FileHistory>,
+    history_file: Option<PathBuf>,
+}
+
+impl LineEditor {
+    /// `history_file` keeps commands between runs. If it is not given, a file in the home
+    /// directory is used
+    pub fn new(
+        simulation: Arc<Mutex<Simulation>>,
+        history_file: Option<PathBuf>,
+    ) -> rustyline::Result<Self> {
+        let config = Config::builder()
+            .completion_type(CompletionType::List)
+            .build();
+        let mut editor = Editor::with_config(config)?;
+        editor.set_helper(Some(CommandHelper {
+            simulation,
+            file_completer: FilenameCompleter::new(),
+        }));
+        let history_file = history_file.or_else(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(HISTORY_FILE_NAME))
+        });
+        if let Some(history_file) = &history_file {
+            // There is no history to load the first time the simulator is run
+            _ = editor.load_history(history_file);
+        }
+        Ok(LineEditor {
+            editor,
+            history_file,
+        })
+    }
+
+    /// Reads the next line, adding it to the history. Gives an error once the terminal is closed,
+    /// or the user presses Ctrl-C or Ctrl-D
+    pub fn read_line(&mut self) -> rustyline::Result<String> {
+        let line = self.editor.readline("> ")?;
+        if !line.trim().is_empty() {
+            _ = self.editor.add_history_entry(line.as_str());
+            if let Some(history_file) = &self.history_file {
+                if let Err(err) = self.editor.save_history(history_file) {
+                    println!(
+                        "Error saving history to {} - {}",
+                        history_file.display(),
+                        err
+                    );
+                }
+            }
+        }
+        Ok(line)
+    }
+}
+
+/// What the word being typed can be completed with
+#[derive(PartialEq, Debug)]
+enum Completion {
+    /// Subcommands or flags of the command typed so far
+    Words(Vec<String>),
+    NodeId,
+    EmitterId,
+    SinkId,
+    File,
+}
+
+struct CommandHelper {
+    simulation: Arc<Mutex<Simulation>>,
+    file_completer: FilenameCompleter,
+}
+
+impl CommandHelper {
+    fn ids(&self, completion: &Completion) -> Vec<u32> {
+        let simulation = match self.simulation.lock() {
+            Ok(simulation) => simulation,
+            Err(_) => return vec![],
+        };
+        let mut ids: Vec<u32> = match completion {
+            Completion::NodeId => simulation.nodes.iter().map(|node| node.id.0).collect(),
+            Completion::EmitterId => simulation
+                .emitters
+                .iter()
+                .map(|emitter| emitter.id)
+                .collect(),
+            Completion::SinkId => simulation.sinks.iter().map(|sink| sink.id).collect(),
+            Completion::Words(_) | Completion::File => vec![],
+        };
+        ids.sort();
+        ids
+    }
+}
+
+impl Completer for CommandHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |index| index + 1);
+        let word = &line[start..];
+        let candidates = match complete(line) {
+            Completion::File => return self.file_completer.complete(line, pos, ctx),
+            Completion::Words(words) => words,
+            completion => self
+                .ids(&completion)
+                .into_iter()
+                .map(|id| id.to_string())
+                .collect(),
+        };
+        let pairs = candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .map(|candidate| Pair {
+                display: candidate.clone(),
+                replacement: candidate,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for CommandHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CommandHelper {}
+
+impl Validator for CommandHelper {}
+
+impl Helper for CommandHelper {}
+
+/// Finds what the last word of `line` can be completed with, by following the earlier words
+/// through the commands of [`SimulationCommand`]
+fn complete(line: &str) -> Completion {
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+    // The last word is the one being completed, unless a new word has been started
+    let word = match line.ends_with(char::is_whitespace) {
+        true => "",
+        false => words.pop().unwrap_or_default(),
+    };
+
+    let root = SimulationCommand::command();
+    let mut command = &root;
+    let mut path = vec![];
+    let mut positionals = 0;
+    for word in words.iter() {
+        if let Some(subcommand) = command.find_subcommand(word) {
+            command = subcommand;
+            path.push(subcommand.get_name());
+            positionals = 0;
+            continue;
+        }
+        if word.starts_with('-') {
+            continue;
+        }
+        // `at` and `every` take a command to run after their own values
+        positionals += 1;
+        let takes_command = command
+            .get_positionals()
+            .any(|arg| arg.is_trailing_var_arg_set());
+        if takes_command && positionals == command.get_positionals().count() - 1 {
+            command = &root;
+            path.clear();
+        }
+    }
+
+    if word.starts_with('-') {
+        return flags(command);
+    }
+    if words.last() == Some(&"--around") {
+        return Completion::NodeId;
+    }
+    if words.last() == Some(&"--id") {
+        match path.as_slice() {
+            ["add", _] => {}
+            [_, "node" | "orbit"] => return Completion::NodeId,
+            [_, "emitter"] => return Completion::EmitterId,
+            [_, "sink"] => return Completion::SinkId,
+            _ => {}
+        }
+    }
+    if path.as_slice() == ["script"] {
+        return Completion::File;
+    }
+
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    match subcommands.is_empty() {
+        true => flags(command),
+        false => Completion::Words(subcommands),
+    }
+}
+
+fn flags(command: &clap::Command) -> Completion {
+    Completion::Words(
+        command
+            .get_arguments()
+            .filter_map(|arg| arg.get_long())
+            .map(|long| format!("--{}", long))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod a_line_editor {
+    use super::*;
+
+    fn words(words: &[&str]) -> Completion {
+        Completion::Words(words.iter().map(|word| word.to_string()).collect())
+    }
+
+    fn contains(completion: Completion, word: &str) -> bool {
+        match completion {
+            Completion::Words(words) => words.iter().any(|other| other == word),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn completes_commands() {
+        assert!(contains(complete(""), "add"));
+        assert!(contains(complete("se"), "set"));
+        assert!(contains(complete("set "), "node"));
+    }
+
+    #[test]
+    fn completes_flags_of_the_command() {
+        assert!(contains(
+            complete("set node --id 1 --grav"),
+            "--gravitational-constant-override"
+        ));
+        assert!(!contains(complete("get node --"), "--dampen"));
+    }
+
+    #[test]
+    fn completes_ids_of_existing_things() {
+        assert_eq!(Completion::NodeId, complete("set node --id "));
+        assert_eq!(Completion::NodeId, complete("remove node --id 1"));
+        assert_eq!(Completion::SinkId, complete("remove sink --id "));
+        assert_eq!(Completion::NodeId, complete("get orbit --id "));
+        assert_eq!(Completion::NodeId, complete("add orbiter --id 2 --around "));
+        assert!(contains(complete("get node --id --pos"), "--position"));
+        assert!(contains(complete("add node --id "), "--position"));
+    }
+
+    #[test]
+    fn completes_the_command_run_by_at_and_every() {
+        assert!(contains(complete("at 100 "), "set"));
+        assert!(contains(complete("at --time 1.5 se"), "set"));
+        assert_eq!(Completion::NodeId, complete("every 10 get node --id "));
+        assert_eq!(words(&[]), complete("every "));
+    }
+
+    #[test]
+    fn completes_script_files() {
+        assert_eq!(Completion::File, complete("script example"));
+    }
+}
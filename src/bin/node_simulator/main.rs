@@ -1,23 +1,31 @@
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
 use std::sync::{mpsc, Arc, Mutex};
 use std::time::{self, Duration};
-use std::{io, thread};
+use std::{process, thread};
 
 use clap::Parser;
+use rustyline::error::ReadlineError;
 
 use node_simulator::graphics::scene_event::{CloseEvent, ToggleSceneEvent};
 use node_simulator::graphics::{self, scene_event, GraphicsInterface};
+use node_simulator::node::vector::ParseContext;
 use node_simulator::{node, simulation};
 
 use args::CLIArgs;
+use line_editor::LineEditor;
 use simulation_commands::script_command::ScriptCommand;
+use simulation_commands::step_command::StepCommand;
+use simulation_commands::tokenizer;
 use simulation_commands::SimulationCommand;
 
 mod args;
+mod line_editor;
 mod simulation_commands;
 
 fn main() {
     let args = CLIArgs::parse();
-    run(args.default_texture, !args.no_display);
+    run(args);
 }
 
 fn create_graphics_interface(
@@ -27,26 +35,138 @@ fn create_graphics_interface(
     graphics::GraphicsInterface::new(simulation_rx, create_display)
 }
 
-fn run(_default_texture_path: Option<String>, create_display: bool) {
-    let simulation = Arc::new(Mutex::new(simulation::Simulation::new()));
+fn run(args: CLIArgs) {
+    let mut simulation = simulation::Simulation::new();
     let (simulation_tx, simulation_rx) = mpsc::channel::<Arc<Mutex<simulation::Simulation>>>();
     let (scene_event_tx, scene_event_rx) = mpsc::channel::<scene_event::Event>();
     let (node_event_tx, node_event_rx) = mpsc::channel::<node::Event>();
 
-    let graphics_interface = create_graphics_interface(simulation_rx, create_display);
+    let script_path = args.script_path.clone();
+    let mut context = ParseContext::default();
+    let succeeded = run_startup_commands(&args, &mut simulation, &scene_event_tx, &mut context);
+    if args.exit_after {
+        process::exit(if succeeded { 0 } else { 1 });
+    }
+
+    let simulation = Arc::new(Mutex::new(simulation));
+    let graphics_interface = create_graphics_interface(simulation_rx, !args.no_display);
 
+    let cli_simulation = Arc::clone(&simulation);
     thread::spawn(move || {
         let simulation = Arc::clone(&simulation);
         run_simulation(simulation, simulation_tx, node_event_rx);
     });
 
-    thread::spawn(|| {
+    let history_file = args.history_file.clone();
+    thread::spawn(move || {
         println!("Running node_simulator...");
-        read_input_from_cli(scene_event_tx, node_event_tx);
+        // Only offer line editing to people typing commands, rather than to piped input
+        let line_editor = match io::stdin().is_terminal() {
+            true => match LineEditor::new(cli_simulation, history_file) {
+                Ok(line_editor) => Some(line_editor),
+                Err(err) => {
+                    println!("Error starting line editor - {}", err);
+                    None
+                }
+            },
+            false => None,
+        };
+        read_input_from_cli(
+            scene_event_tx,
+            node_event_tx,
+            line_editor,
+            context,
+            script_path,
+        );
     });
     graphics_interface.run(scene_event_rx);
 }
 
+/// Runs the script, steps and commands given on the command line, in that order. They are run
+/// straight against the simulation before it starts stepping on its own, so their results do not
+/// depend on timing. Returns false if any of them gave an error.
+///
+/// A failed `assert` stops the run straight away. Once the run ends, a summary of the `assert` and
+/// `expect` checks is printed if there were any.
+fn run_startup_commands(
+    args: &CLIArgs,
+    simulation: &mut simulation::Simulation,
+    scene_event_tx: &mpsc::Sender<scene_event::Event>,
+    context: &mut ParseContext,
+) -> bool {
+    let mut commands = vec![];
+    if let Some(file) = &args.script {
+        commands.push(Ok(SimulationCommand {
+            command: simulation_commands::Command::Script(ScriptCommand { file: file.clone() }),
+        }));
+    }
+    if let Some(number_of_steps) = args.steps {
+        commands.push(Ok(SimulationCommand {
+            command: simulation_commands::Command::Step(StepCommand { number_of_steps }),
+        }));
+    }
+    commands.extend(args.exec.iter().cloned().map(SimulationCommand::try_from));
+
+    let mut succeeded = true;
+    let mut checks = 0;
+    let mut failed_checks = 0;
+    'commands: for command in commands {
+        let command = match command {
+            Ok(command) => command,
+            Err(err) => {
+                println!("{}", err);
+                succeeded = false;
+                continue;
+            }
+        };
+        let (node_event_tx, node_event_rx) = mpsc::channel::<node::Event>();
+        if let Err(err) = execute_command(
+            command,
+            scene_event_tx,
+            &node_event_tx,
+            context,
+            &args.script_path,
+        ) {
+            println!("{}", err);
+            succeeded = false;
+        }
+        for event in node_event_rx.try_iter() {
+            let check = match &event {
+                node::Event::Assert(assert_event) => Some(assert_event.stop_on_failure),
+                _ => None,
+            };
+            if check.is_some() {
+                checks += 1;
+            }
+            let result = simulation.handle_event(event);
+            print_merges(simulation);
+            if let Err(err) = result {
+                println!("{}", err);
+                succeeded = false;
+                match check {
+                    Some(true) => {
+                        failed_checks += 1;
+                        break 'commands;
+                    }
+                    Some(false) => failed_checks += 1,
+                    None => {}
+                }
+            }
+        }
+    }
+    if checks > 0 {
+        println!("{} of {} checks passed", checks - failed_checks, checks);
+    }
+    succeeded
+}
+
+/// Prints the nodes that have merged since the merges were last printed
+fn print_merges(simulation: &mut simulation::Simulation) {
+    for merge in simulation.take_merges() {
+        println!("{}", merge);
+    }
+}
+
 pub fn run_simulation(
     simulation: Arc<Mutex<simulation::Simulation>>,
     simulation_tx: mpsc::Sender<Arc<Mutex<simulation::Simulation>>>,
@@ -69,12 +189,15 @@ pub fn run_simulation(
                 false => get_target_duration(target_tps),
             };
             if let Ok(event) = event {
-                sim.handle_event(event)
+                if let Err(err) = sim.handle_event(event) {
+                    println!("{}", err);
+                }
             }
 
             if !sim_is_paused {
                 sim.step();
             }
+            print_merges(&mut sim);
         }
 
         let _ = simulation_tx.send(simulation.clone());
@@ -94,23 +217,54 @@ pub fn run_simulation(
 fn read_input_from_cli(
     scene_event_tx: mpsc::Sender<scene_event::Event>,
     node_event_tx: mpsc::Sender<node::Event>,
+    mut line_editor: Option<LineEditor>,
+    mut context: ParseContext,
+    script_path: Vec<PathBuf>,
 ) {
+    // Vectors are parsed before their events reach the simulation, so track the dimensionality and
+    // units that have been sent to it
     loop {
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read input");
+        let input = match &mut line_editor {
+            Some(line_editor) => match line_editor.read_line() {
+                Ok(input) if input.trim().is_empty() => continue,
+                Ok(input) => input,
+                // Closing the terminal, or pressing Ctrl-C or Ctrl-D, closes the simulator
+                Err(ReadlineError::Interrupted | ReadlineError::Eof) => {
+                    _ = scene_event_tx.send(scene_event::Event::Close(CloseEvent {}));
+                    return;
+                }
+                Err(err) => {
+                    println!("Error reading input - {}", err);
+                    _ = scene_event_tx.send(scene_event::Event::Close(CloseEvent {}));
+                    return;
+                }
+            },
+            None => {
+                let mut input = String::new();
+                io::stdin()
+                    .read_line(&mut input)
+                    .expect("Failed to read input");
+                input
+            }
+        };
         if input.is_empty() {
             continue;
         }
 
         let command = SimulationCommand::try_from(input);
         match command {
-            Ok(command) => execute_command(command, &scene_event_tx, &node_event_tx),
-            Err(e) => println!(
-                "{}",
-                SimulationCommand::remove_dummy_char_from_usage_string(e.to_string())
-            ),
+            Ok(command) => {
+                if let Err(err) = execute_command(
+                    command,
+                    &scene_event_tx,
+                    &node_event_tx,
+                    &mut context,
+                    &script_path,
+                ) {
+                    println!("{}", err)
+                }
+            }
+            Err(e) => println!("{}", e),
         }
     }
 }
@@ -119,14 +273,41 @@ fn execute_command(
     simulation_command: SimulationCommand,
     scene_event_tx: &mpsc::Sender<scene_event::Event>,
     node_event_tx: &mpsc::Sender<node::Event>,
-) {
+    context: &mut ParseContext,
+    script_path: &[PathBuf],
+) -> Result<(), String> {
     match &simulation_command.command {
         simulation_commands::Command::Add(args) => match &args.command {
             simulation_commands::add_command::Commands::Node(node_args) => {
-                let add_event = node::AddNodeEvent::try_from(node_args);
+                let add_event = node_args.to_event(context);
                 match add_event {
                     Ok(add_event) => _ = node_event_tx.send(node::Event::AddNode(add_event)),
-                    Err(err) => println!("{}", err),
+                    Err(err) => return Err(err.to_string()),
+                }
+            }
+            simulation_commands::add_command::Commands::Orbiter(orbiter_args) => {
+                match orbiter_args.to_event(context) {
+                    Ok(event) => _ = node_event_tx.send(node::Event::AddOrbiter(event)),
+                    Err(err) => return Err(err),
+                }
+            }
+            simulation_commands::add_command::Commands::Emitter(emitter_args) => {
+                match emitter_args.to_event(context) {
+                    Ok(event) => _ = node_event_tx.send(node::Event::AddEmitter(event)),
+                    Err(err) => return Err(err),
+                }
+            }
+            simulation_commands::add_command::Commands::Sink(sink_args) => {
+                match sink_args.to_event(context) {
+                    Ok(event) => _ = node_event_tx.send(node::Event::AddSink(event)),
+                    Err(err) => return Err(err),
+                }
+            }
+            #[cfg(feature = "scripting")]
+            simulation_commands::add_command::Commands::Hook(hook_args) => {
+                match hook_args.to_event(script_path) {
+                    Ok(event) => _ = node_event_tx.send(node::Event::AddHook(event)),
+                    Err(err) => return Err(err),
                 }
             }
         },
@@ -136,6 +317,27 @@ fn execute_command(
                     node_args,
                 )))
             }
+            simulation_commands::remove_command::Commands::Emitter(emitter_args) => {
+                _ = node_event_tx.send(node::Event::RemoveEmitter(node::RemoveEmitterEvent::from(
+                    emitter_args,
+                )))
+            }
+            simulation_commands::remove_command::Commands::Sink(sink_args) => {
+                _ = node_event_tx.send(node::Event::RemoveSink(node::RemoveSinkEvent::from(
+                    sink_args,
+                )))
+            }
+            #[cfg(feature = "scripting")]
+            simulation_commands::remove_command::Commands::Hook(hook_args) => {
+                _ = node_event_tx.send(node::Event::RemoveHook(node::RemoveHookEvent::from(
+                    hook_args,
+                )))
+            }
+            simulation_commands::remove_command::Commands::Schedule(schedule_args) => {
+                _ = node_event_tx.send(node::Event::CancelScheduled(
+                    node::CancelScheduledEvent::from(schedule_args),
+                ))
+            }
         },
         simulation_commands::Command::ToggleScene => {
             _ = scene_event_tx.send(scene_event::Event::ToggleScene(ToggleSceneEvent {}))
@@ -145,13 +347,7 @@ fn execute_command(
         }
         simulation_commands::Command::Set(set_args) => match &set_args.command {
             simulation_commands::set_command::Commands::Node(node_args) => {
-                let event = match node::event::set_node::SetNodeEvent::try_from(node_args) {
-                    Ok(args) => args,
-                    Err(err) => {
-                        println!("{}", err);
-                        return;
-                    }
-                };
+                let event = node_args.to_event(context)?;
                 _ = node_event_tx.send(node::Event::SetNode(event))
             }
             simulation_commands::set_command::Commands::Fps(fps_args) => {
@@ -164,6 +360,48 @@ fn execute_command(
                     tps_args,
                 )))
             }
+            simulation_commands::set_command::Commands::Softening(softening_args) => {
+                _ = node_event_tx.send(node::Event::SetSoftening(node::SetSofteningEvent::from(
+                    softening_args,
+                )))
+            }
+            simulation_commands::set_command::Commands::PairForceMode(pair_force_mode_args) => {
+                _ = node_event_tx.send(node::Event::SetPairForceMode(
+                    node::SetPairForceModeEvent::from(pair_force_mode_args),
+                ))
+            }
+            simulation_commands::set_command::Commands::StatsLog(stats_log_args) => {
+                _ = node_event_tx.send(node::Event::SetStatsLog(node::SetStatsLogEvent::from(
+                    stats_log_args,
+                )))
+            }
+            simulation_commands::set_command::Commands::Drag(drag_args) => {
+                _ = node_event_tx.send(node::Event::SetDrag(node::SetDragEvent::from(drag_args)))
+            }
+            simulation_commands::set_command::Commands::Dimensionality(dimensionality_args) => {
+                let event = node::SetDimensionalityEvent::from(dimensionality_args);
+                context.dimensionality = event.dimensionality;
+                _ = node_event_tx.send(node::Event::SetDimensionality(event))
+            }
+            simulation_commands::set_command::Commands::Units(units_args) => {
+                let event = units_args.to_event(context.units)?;
+                context.units = event.units;
+                _ = node_event_tx.send(node::Event::SetUnits(event))
+            }
+            simulation_commands::set_command::Commands::TimeStep(time_step_args) => {
+                let event = time_step_args.to_event(context)?;
+                _ = node_event_tx.send(node::Event::SetTimeStep(event))
+            }
+            simulation_commands::set_command::Commands::CollisionPolicy(collision_policy_args) => {
+                _ = node_event_tx.send(node::Event::SetCollisionPolicy(
+                    node::SetCollisionPolicyEvent::from(collision_policy_args),
+                ))
+            }
+            simulation_commands::set_command::Commands::AdaptiveStep(adaptive_step_args) => {
+                _ = node_event_tx.send(node::Event::SetAdaptiveStep(
+                    node::SetAdaptiveStepEvent::from(adaptive_step_args),
+                ))
+            }
         },
         simulation_commands::Command::Get(get_args) => match &get_args.command {
             simulation_commands::get_command::Commands::Node(get_node_event) => {
@@ -171,27 +409,139 @@ fn execute_command(
                     get_node_event.into(),
                 )))
             }
+            simulation_commands::get_command::Commands::Orbit(orbit_args) => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Orbit(
+                    orbit_args.into(),
+                )))
+            }
             simulation_commands::get_command::Commands::Tps => {
                 _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Tps))
             }
             simulation_commands::get_command::Commands::Fps => {
                 _ = scene_event_tx.send(scene_event::Event::GetFps)
             }
+            simulation_commands::get_command::Commands::Softening => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Softening))
+            }
+            simulation_commands::get_command::Commands::PairForceMode => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::PairForceMode))
+            }
+            simulation_commands::get_command::Commands::Stats => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Stats))
+            }
+            simulation_commands::get_command::Commands::Drag => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Drag))
+            }
+            simulation_commands::get_command::Commands::TimeStep => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::TimeStep))
+            }
+            simulation_commands::get_command::Commands::Dimensionality => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Dimensionality))
+            }
+            simulation_commands::get_command::Commands::Units => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Units))
+            }
+            simulation_commands::get_command::Commands::Emitters => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Emitters))
+            }
+            simulation_commands::get_command::Commands::Sinks => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Sinks))
+            }
+            simulation_commands::get_command::Commands::CollisionPolicy => {
+                _ = node_event_tx.send(node::Event::Get(
+                    node::event::get::GetEvent::CollisionPolicy,
+                ))
+            }
+            simulation_commands::get_command::Commands::Schedule => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Schedule))
+            }
+            #[cfg(feature = "scripting")]
+            simulation_commands::get_command::Commands::Hooks => {
+                _ = node_event_tx.send(node::Event::Get(node::event::get::GetEvent::Hooks))
+            }
         },
         simulation_commands::Command::Step(step_args) => {
             _ = node_event_tx.send(node::Event::Step(step_args.into()))
         }
         simulation_commands::Command::Script(script_args) => {
-            let commands = match ScriptCommand::load_script(script_args.file.clone()) {
-                Ok(commands) => commands,
-                Err(err) => {
-                    println!("Error running script - {}", err);
-                    return;
-                }
-            };
+            let commands = ScriptCommand::load_script(script_args.file.clone(), script_path)
+                .map_err(|err| format!("Error running script - {}", err))?;
             for command in commands.into_iter() {
-                execute_command(command, scene_event_tx, node_event_tx)
+                execute_command(command, scene_event_tx, node_event_tx, context, script_path)?
+            }
+        }
+        simulation_commands::Command::At(at_command) => match at_command.to_trigger(context) {
+            Ok(trigger) => {
+                return schedule_command(
+                    trigger,
+                    &at_command.command,
+                    node_event_tx,
+                    *context,
+                    script_path,
+                )
             }
+            Err(err) => return Err(err),
+        },
+        simulation_commands::Command::Assert(assert_command) => {
+            let event = assert_command.to_event(true, context)?;
+            _ = node_event_tx.send(node::Event::Assert(event))
+        }
+        simulation_commands::Command::Expect(assert_command) => {
+            let event = assert_command.to_event(false, context)?;
+            _ = node_event_tx.send(node::Event::Assert(event))
         }
+        simulation_commands::Command::Every(every_command) => {
+            return schedule_command(
+                every_command.into(),
+                &every_command.command,
+                node_event_tx,
+                *context,
+                script_path,
+            )
+        }
+    }
+    Ok(())
+}
+
+/// Sends the events of `command` to the simulation to be handled when `trigger` fires, rather
+/// than straight away
+fn schedule_command(
+    trigger: simulation::schedule::Trigger,
+    command: &[String],
+    node_event_tx: &mpsc::Sender<node::Event>,
+    mut context: ParseContext,
+    script_path: &[PathBuf],
+) -> Result<(), String> {
+    let description = command
+        .iter()
+        .map(|argument| tokenizer::quote(argument))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let simulation_command = SimulationCommand::try_from(description.clone())?;
+    // Collect the events the command would send, without changing the context used to parse the
+    // commands that follow
+    let (scheduled_scene_event_tx, scheduled_scene_event_rx) = mpsc::channel();
+    let (scheduled_node_event_tx, scheduled_node_event_rx) = mpsc::channel();
+    execute_command(
+        simulation_command,
+        &scheduled_scene_event_tx,
+        &scheduled_node_event_tx,
+        &mut context,
+        script_path,
+    )?;
+    if scheduled_scene_event_rx.try_iter().next().is_some() {
+        return Err(
+            "Error scheduling command - only simulation commands can be scheduled".to_string(),
+        );
+    }
+    let events: Vec<node::Event> = scheduled_node_event_rx.try_iter().collect();
+    if events.is_empty() {
+        return Ok(());
     }
+    _ = node_event_tx.send(node::Event::Schedule(node::ScheduleEvent {
+        trigger,
+        description,
+        events,
+    }));
+    Ok(())
 }
@@ -1,12 +1,20 @@
 pub mod add_command;
+pub mod assert_command;
+pub mod at_command;
+pub mod every_command;
 pub mod get_command;
 pub mod remove_command;
 pub mod script_command;
 pub mod set_command;
 pub mod step_command;
+pub mod tokenizer;
 
 #[derive(clap::Parser, Debug)]
-#[command(help_template = "Commands:\r\n{subcommands}")]
+#[command(
+    help_template = "Commands:\r\n{subcommands}",
+    no_binary_name = true,
+    override_usage = "<COMMAND>"
+)]
 pub struct SimulationCommand {
     #[command(subcommand)]
     pub command: Command,
@@ -22,38 +30,102 @@ pub enum Command {
     Close,
     Step(step_command::StepCommand),
     Script(script_command::ScriptCommand),
+    At(at_command::AtCommand),
+    Every(every_command::EveryCommand),
+    Assert(assert_command::AssertCommand),
+    Expect(assert_command::AssertCommand),
 }
 
 impl TryFrom<String> for SimulationCommand {
     type Error = String;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let mut value = value.to_lowercase().trim().to_string();
-        // Insert dummy char for command parsing
-        value.insert(0, ' ');
-        value.insert(0, '@');
+        let mut arguments = tokenizer::tokenize(&value)?;
+        Self::lowercase_names(&mut arguments);
 
         use clap::Parser;
-        let command: Result<SimulationCommand, clap::error::Error> =
-            Self::try_parse_from(value.split_whitespace());
-        match command {
-            Ok(command) => Ok(command),
-            Err(e) => Err(SimulationCommand::remove_dummy_char_from_usage_string(
-                e.to_string(),
-            )),
-        }
+        // The help templates use "\r\n", so line endings are made the same as the rest of the output
+        Self::try_parse_from(arguments).map_err(|err| err.to_string().replace("\r\n", "\n"))
     }
 }
 
 impl SimulationCommand {
-    pub fn remove_dummy_char_from_usage_string(message: String) -> String {
-        message
-            .lines()
-            .map(|line| {
-                let mut line = line.replace("@ ", "");
-                line.push('\n');
-                line
-            })
-            .collect()
+    /// Lowercases the names of subcommands and long options, so that they can be typed in any case
+    /// while values keep theirs
+    fn lowercase_names(arguments: &mut [String]) {
+        use clap::CommandFactory;
+        let mut command = Some(Self::command());
+        for argument in arguments.iter_mut() {
+            if let Some(option) = argument.strip_prefix("--") {
+                *argument = match option.split_once('=') {
+                    Some((name, value)) => format!("--{}={}", name.to_lowercase(), value),
+                    None => argument.to_lowercase(),
+                };
+                continue;
+            }
+            let name = argument.to_lowercase();
+            let Some(subcommand) = command
+                .as_ref()
+                .and_then(|command| command.find_subcommand(&name))
+                .cloned()
+            else {
+                continue;
+            };
+            *argument = name;
+            command = Some(subcommand);
+        }
+    }
+}
+
+#[cfg(test)]
+mod a_simulation_command {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn script_file(command: &str) -> String {
+        match SimulationCommand::try_from(command.to_string())
+            .unwrap()
+            .command
+        {
+            Command::Script(script_command) => script_command.file,
+            _ => panic!("Unexpected command"),
+        }
+    }
+
+    #[test]
+    fn keeps_the_case_of_values() {
+        assert_eq!(
+            "/Users/Me/Planets.txt",
+            script_file("script /Users/Me/Planets.txt")
+        );
+    }
+
+    #[test]
+    fn keeps_quoted_values_together() {
+        assert_eq!(
+            "My Scripts/a.txt",
+            script_file("script \"My Scripts/a.txt\"")
+        );
+        assert_eq!("My Scripts/a.txt", script_file("script My\\ Scripts/a.txt"));
+    }
+
+    #[rstest]
+    #[case("Add Node --ID 1")]
+    #[case("SET PAIR-FORCE-MODE Symmetric")]
+    #[case("set adaptive-step Error-Estimate --Tolerance=0.1")]
+    #[case("Set Node --id 1 --kinematic Circle")]
+    fn parses_names_and_values_in_any_case(#[case] command: &str) {
+        assert!(SimulationCommand::try_from(command.to_string()).is_ok());
+    }
+
+    #[test]
+    fn keeps_the_case_of_values_named_like_commands() {
+        assert_eq!("Add", script_file("Script Add"));
+    }
+
+    #[test]
+    fn gives_an_error_for_unclosed_quotes() {
+        assert!(SimulationCommand::try_from("script \"a.txt".to_string()).is_err());
     }
 }
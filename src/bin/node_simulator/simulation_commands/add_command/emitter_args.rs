@@ -0,0 +1,73 @@
+use node_simulator::{
+    node,
+    node::vector::ParseContext,
+    simulation::{
+        emitter::{Emitter, VelocityDistribution},
+        units::Quantity,
+    },
+};
+
+#[derive(clap::Args, Debug)]
+pub struct EmitterArgs {
+    #[arg(short, long)]
+    id: u32,
+    #[arg(short, long, allow_hyphen_values = true)]
+    position: Option<String>,
+    /// Nodes emitted per unit of simulated time. No more than 10000 are emitted in one step
+    #[arg(short, long)]
+    rate: node::Scalar,
+    /// Mean velocity of emitted nodes
+    #[arg(short, long, allow_hyphen_values = true)]
+    velocity: Option<String>,
+    #[arg(long, default_value = "fixed", ignore_case = true)]
+    velocity_distribution: Distribution,
+    /// Radius of the uniform distribution, or standard deviation of the normal distribution
+    #[arg(long, default_value_t = 0.0)]
+    velocity_spread: node::Scalar,
+    /// Mass of emitted nodes, which may be followed by a unit such as "kg" or "Mearth"
+    #[arg(short, long, default_value = "1")]
+    mass: String,
+    /// Seed for the random velocities of emitted nodes. Defaults to the id of the emitter
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Distribution {
+    /// Emit every node with the mean velocity
+    Fixed,
+    /// Spread velocities evenly within a sphere around the mean velocity
+    Uniform,
+    /// Spread each component of the velocity normally around the mean velocity
+    Normal,
+}
+
+impl EmitterArgs {
+    /// Creates the event to add the emitter, parsing its vectors against `context`
+    pub fn to_event(&self, context: &ParseContext) -> Result<node::AddEmitterEvent, String> {
+        let position = match &self.position {
+            Some(position) => node::Position::parse(position.to_string(), context)?,
+            None => node::Position::default(),
+        };
+        let velocity = match &self.velocity {
+            Some(velocity) => node::Force::parse(velocity.to_string(), context)?,
+            None => node::Force::zero(),
+        };
+        let spread = self.velocity_spread;
+        let velocity_distribution = match self.velocity_distribution {
+            Distribution::Fixed => VelocityDistribution::Fixed,
+            Distribution::Uniform => VelocityDistribution::Uniform { spread },
+            Distribution::Normal => VelocityDistribution::Normal { spread },
+        };
+        let emitter = Emitter::new(
+            self.id,
+            position,
+            self.rate,
+            velocity,
+            velocity_distribution,
+            context.scalar(&self.mass, "Mass", Quantity::Mass)?,
+            self.seed.unwrap_or(self.id as u64),
+        )?;
+        Ok(node::AddEmitterEvent { emitter })
+    }
+}
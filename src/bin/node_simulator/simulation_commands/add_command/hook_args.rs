@@ -0,0 +1,45 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use node_simulator::node;
+
+use crate::simulation_commands::script_command::ScriptCommand;
+
+#[derive(clap::Args, Debug)]
+pub struct HookArgs {
+    #[arg(short, long)]
+    id: u32,
+    /// Rhai script to run. Its `on_tick(sim)` function is run every tick. Found like scripts are,
+    /// relative to the script adding the hook, then each directory of the script path
+    #[arg(short, long)]
+    pub file: String,
+    /// Milliseconds the script may run for each tick before it is stopped
+    #[arg(long, default_value_t = 5)]
+    budget_ms: u64,
+}
+
+impl HookArgs {
+    /// Creates the event to add the hook, reading its file from the working directory or a
+    /// directory in `search_path`
+    pub fn to_event(&self, search_path: &[PathBuf]) -> Result<node::AddHookEvent, String> {
+        let Some(path) = ScriptCommand::find_script(&self.file, Path::new(""), search_path) else {
+            return Err(format!(
+                "Error adding hook {} - Could not find hook script '{}'",
+                self.id, self.file
+            ));
+        };
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) => return Err(format!("Error adding hook {} - {}", self.id, err)),
+        };
+        Ok(node::AddHookEvent {
+            id: self.id,
+            name: self.file.clone(),
+            source,
+            budget: Duration::from_millis(self.budget_ms),
+        })
+    }
+}
@@ -1,28 +1,34 @@
-use node_simulator::node::{self};
+use node_simulator::{
+    node::{self, vector::ParseContext},
+    simulation::units::Quantity,
+};
 
 #[derive(clap::Args, Debug)]
 pub struct NodeArgs {
     #[arg(short, long)]
     id: u32,
-    #[arg(short, long)]
+    #[arg(short, long, allow_hyphen_values = true)]
     position: Option<String>,
 }
 
-impl TryFrom<&NodeArgs> for node::AddNodeEvent {
-    type Error = NodeArgsError;
-
-    fn try_from(value: &NodeArgs) -> Result<Self, Self::Error> {
-        let id = node::Id(value.id);
-        let position = match &value.position {
-            Some(pos) => node::Position::try_from(pos.to_string()),
-            None => Ok(node::Position::default()),
+impl NodeArgs {
+    /// Creates the event to add the node, parsing its position against `context`. The position may
+    /// be relative to another node
+    pub fn to_event(&self, context: &ParseContext) -> Result<node::AddNodeEvent, NodeArgsError> {
+        let id = node::Id(self.id);
+        let position = match &self.position {
+            Some(pos) => node::Vector::parse(pos, "Position", Quantity::Length, context),
+            None => Ok(node::Vector::default()),
         };
         let position = match position {
             Ok(pos) => pos,
             Err(err) => return Err(NodeArgsError { message: err }),
         };
-        let node = node::Node::new(id, position);
-        Ok(node::AddNodeEvent { node })
+        let node = node::Node::new(id, node::Position::default() + position.offset);
+        Ok(node::AddNodeEvent {
+            node,
+            position_relative_to: position.relative_to,
+        })
     }
 }
 
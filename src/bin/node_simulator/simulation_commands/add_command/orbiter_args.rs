@@ -0,0 +1,61 @@
+use node_simulator::{
+    node::{self, orbit::Elements, vector::ParseContext},
+    simulation::units::Quantity,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct OrbiterArgs {
+    #[arg(short, long)]
+    id: u32,
+    /// Node to orbit. Its position, velocity and mass when the command runs decide the orbit
+    #[arg(long)]
+    around: u32,
+    /// Half of the longest diameter of the orbit
+    #[arg(long)]
+    semi_major_axis: String,
+    /// 0 for a circular orbit, up to but not including 1 for a long ellipse
+    #[arg(long, default_value_t = 0.0)]
+    eccentricity: node::Scalar,
+    /// Tilt of the orbit from the XZ plane, in degrees unless followed by "rad"
+    #[arg(long, default_value = "0", allow_hyphen_values = true)]
+    inclination: String,
+    /// Angle around the Y axis from the X axis at which the orbit rises through the XZ plane
+    #[arg(long, default_value = "0", allow_hyphen_values = true)]
+    longitude_of_ascending_node: String,
+    /// Angle from the ascending node to the closest point of the orbit
+    #[arg(long, default_value = "0", allow_hyphen_values = true)]
+    argument_of_periapsis: String,
+    /// Angle from the closest point of the orbit to where the node starts
+    #[arg(long, default_value = "0", allow_hyphen_values = true)]
+    true_anomaly: String,
+    /// Mass of the node, which may be followed by a unit such as "kg" or "Mearth"
+    #[arg(short, long, default_value = "1")]
+    mass: String,
+}
+
+impl OrbiterArgs {
+    /// Creates the event to add the node, parsing its elements against `context`. Orbiters are not
+    /// dampened, so that they keep to their orbit under the legacy drag model
+    pub fn to_event(&self, context: &ParseContext) -> Result<node::AddOrbiterEvent, String> {
+        let angle = |value: &str, name: &str| context.scalar(value, name, Quantity::Angle);
+        let elements = Elements::new(
+            context.scalar(&self.semi_major_axis, "Semi-major axis", Quantity::Length)?,
+            self.eccentricity,
+            angle(&self.inclination, "Inclination")?,
+            angle(
+                &self.longitude_of_ascending_node,
+                "Longitude of ascending node",
+            )?,
+            angle(&self.argument_of_periapsis, "Argument of periapsis")?,
+            angle(&self.true_anomaly, "True anomaly")?,
+        )?;
+        let mut node = node::Node::new(node::Id(self.id), node::Position::default());
+        node.mass = context.scalar(&self.mass, "Mass", Quantity::Mass)?;
+        node.dampen_rate = 0.0;
+        Ok(node::AddOrbiterEvent {
+            node,
+            around: node::Id(self.around),
+            elements,
+        })
+    }
+}
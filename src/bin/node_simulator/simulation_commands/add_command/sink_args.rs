@@ -0,0 +1,30 @@
+use node_simulator::{
+    node,
+    node::vector::ParseContext,
+    simulation::{sink::Sink, units::Quantity},
+};
+
+#[derive(clap::Args, Debug)]
+pub struct SinkArgs {
+    #[arg(short, long)]
+    id: u32,
+    #[arg(short, long, allow_hyphen_values = true)]
+    position: Option<String>,
+    /// Nodes closer than this to the position of the sink are absorbed, which may be followed by a
+    /// unit
+    #[arg(short, long)]
+    radius: String,
+}
+
+impl SinkArgs {
+    /// Creates the event to add the sink, parsing its position against `context`
+    pub fn to_event(&self, context: &ParseContext) -> Result<node::AddSinkEvent, String> {
+        let position = match &self.position {
+            Some(position) => node::Position::parse(position.to_string(), context)?,
+            None => node::Position::default(),
+        };
+        let radius = context.scalar(&self.radius, "Radius", Quantity::Length)?;
+        let sink = Sink::new(self.id, position, radius)?;
+        Ok(node::AddSinkEvent { sink })
+    }
+}
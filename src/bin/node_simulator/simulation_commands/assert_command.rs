@@ -0,0 +1,120 @@
+pub mod node_args;
+pub mod stats_args;
+
+use node_args::NodeArgs;
+use node_simulator::{
+    node::{self, event::assert, vector::ParseContext},
+    simulation::units::Quantity,
+};
+use stats_args::StatsArgs;
+
+// Checks values of the simulation, used by both the `assert` and `expect` commands. A failed
+// `assert` stops a run without the CLI, while a failed `expect` is reported and the run carries on
+#[derive(clap::Parser, Debug)]
+#[command(help_template = "Commands:\r\n{subcommands}")]
+pub struct AssertCommand {
+    #[command(subcommand)]
+    pub command: Commands,
+    /// Where the command was written, ie. "planets.txt:3", if it came from a script
+    #[arg(skip)]
+    pub source: Option<String>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum Commands {
+    Node(NodeArgs),
+    Stats(StatsArgs),
+}
+
+impl AssertCommand {
+    /// Creates the event to check the simulation, parsing vectors against `context`
+    pub fn to_event(
+        &self,
+        stop_on_failure: bool,
+        context: &ParseContext,
+    ) -> Result<node::AssertEvent, String> {
+        let (check, tolerance) = match &self.command {
+            Commands::Node(node_args) => {
+                (node_args.to_check(context)?, node_args.tolerance(context)?)
+            }
+            Commands::Stats(stats_args) => (
+                stats_args.to_check(context)?,
+                stats_args.tolerance(context)?,
+            ),
+        };
+        Ok(node::AssertEvent {
+            check,
+            tolerance,
+            stop_on_failure,
+            source: self.source.clone(),
+        })
+    }
+}
+
+/// Parses the tolerance of a check of values measuring `quantities`, converting it into the units
+/// of the simulation like the values are. A unit can only be given if the values all measure the
+/// same quantity
+fn parse_tolerance(
+    tolerance: &Option<String>,
+    quantities: &[Quantity],
+    context: &ParseContext,
+) -> Result<node::Scalar, String> {
+    let Some(tolerance) = tolerance else {
+        return Ok(assert::DEFAULT_TOLERANCE);
+    };
+    match quantities {
+        [quantity, others @ ..] if others.iter().all(|other| other == quantity) => {
+            context.scalar(tolerance, "Tolerance", *quantity)
+        }
+        _ => tolerance.trim().parse().map_err(|_| {
+            format!(
+                "Tolerance '{}' can not have a unit, as the values checked measure different quantities",
+                tolerance
+            )
+        }),
+    }
+}
+
+#[cfg(test)]
+mod an_assert_command {
+    use clap::Parser;
+    use node_simulator::simulation::{
+        dimensionality::Dimensionality,
+        units::{UnitSystem, Units},
+    };
+
+    use super::*;
+
+    fn tolerance(command: &str) -> Result<node::Scalar, String> {
+        let context = ParseContext {
+            dimensionality: Dimensionality::default(),
+            units: UnitSystem::Physical(Units::SI),
+        };
+        AssertCommand::try_parse_from(command.split_whitespace())
+            .unwrap()
+            .to_event(true, &context)
+            .map(|event| event.tolerance)
+    }
+
+    #[test]
+    fn converts_the_tolerance_into_the_units_of_the_simulation() {
+        assert_eq!(
+            Ok(2000.0),
+            tolerance("assert node --id 1 --position 1km,0,0 --tolerance 2km")
+        );
+        assert_eq!(
+            Ok(60.0),
+            tolerance("assert stats --time 2min --tolerance 1min")
+        );
+        assert_eq!(
+            Ok(0.5),
+            tolerance("assert node --id 1 --position 0,0,0 --mass 1 --tolerance 0.5")
+        );
+    }
+
+    #[test]
+    fn gives_an_error_for_a_tolerance_unit_that_does_not_match_the_values() {
+        assert!(tolerance("assert node --id 1 --mass 1 --tolerance 2km").is_err());
+        assert!(tolerance("assert node --id 1 --position 0,0,0 --mass 1 --tolerance 2km").is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use node_simulator::{
+    node::{
+        self,
+        event::assert::{Check, NodeCheck},
+        vector::ParseContext,
+    },
+    simulation::units::Quantity,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct NodeArgs {
+    #[arg(long)]
+    id: u32,
+    #[arg(long, allow_hyphen_values = true)]
+    position: Option<String>,
+    #[arg(long, allow_hyphen_values = true)]
+    velocity: Option<String>,
+    #[arg(long)]
+    mass: Option<String>,
+    #[arg(long)]
+    radius: Option<String>,
+    /// Largest difference from the expected values that is allowed, 0.000001 if not given. For
+    /// vectors, this is the distance between them. Values also match if they differ by a millionth
+    /// of their size, so that rounding does not fail checks. May have a unit if only one quantity is
+    /// checked, ie. "1km" for a position
+    #[arg(long)]
+    tolerance: Option<String>,
+}
+
+impl NodeArgs {
+    pub fn to_check(&self, context: &ParseContext) -> Result<Check, String> {
+        if self.position.is_none()
+            && self.velocity.is_none()
+            && self.mass.is_none()
+            && self.radius.is_none()
+        {
+            return Err("Nothing to check - give a value to compare the node against".to_string());
+        }
+        let position = match &self.position {
+            Some(position) => Some(node::Position::parse(position.clone(), context)?),
+            None => None,
+        };
+        let velocity = match &self.velocity {
+            Some(velocity) => Some(node::Force::parse(velocity.clone(), context)?),
+            None => None,
+        };
+        let scalar = |value: &Option<String>, name, quantity| match value {
+            Some(value) => context.scalar(value, name, quantity).map(Some),
+            None => Ok(None),
+        };
+        Ok(Check::Node(NodeCheck {
+            id: node::Id(self.id),
+            position,
+            velocity,
+            mass: scalar(&self.mass, "Mass", Quantity::Mass)?,
+            radius: scalar(&self.radius, "Radius", Quantity::Length)?,
+        }))
+    }
+
+    /// The tolerance, in the units of the simulation
+    pub fn tolerance(&self, context: &ParseContext) -> Result<node::Scalar, String> {
+        let quantities = [
+            (self.position.is_some(), Quantity::Length),
+            (self.velocity.is_some(), Quantity::Velocity),
+            (self.mass.is_some(), Quantity::Mass),
+            (self.radius.is_some(), Quantity::Length),
+        ];
+        let quantities: Vec<_> = quantities
+            .into_iter()
+            .filter_map(|(checked, quantity)| checked.then_some(quantity))
+            .collect();
+        super::parse_tolerance(&self.tolerance, &quantities, context)
+    }
+}
@@ -0,0 +1,92 @@
+use node_simulator::{
+    node::{
+        self,
+        event::assert::{Check, StatsCheck},
+        vector::ParseContext,
+    },
+    simulation::units::Quantity,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct StatsArgs {
+    #[arg(long)]
+    tick: Option<u64>,
+    /// Simulated time
+    #[arg(long)]
+    time: Option<String>,
+    #[arg(long, allow_hyphen_values = true)]
+    kinetic_energy: Option<node::Scalar>,
+    #[arg(long, allow_hyphen_values = true)]
+    potential_energy: Option<node::Scalar>,
+    #[arg(long, allow_hyphen_values = true)]
+    total_energy: Option<node::Scalar>,
+    #[arg(long, allow_hyphen_values = true)]
+    momentum: Option<String>,
+    #[arg(long, allow_hyphen_values = true)]
+    angular_momentum: Option<String>,
+    #[arg(long, allow_hyphen_values = true)]
+    centre_of_mass: Option<String>,
+    /// Largest difference from the expected values that is allowed, 0.000001 if not given. For
+    /// vectors, this is the distance between them. Values also match if they differ by a millionth
+    /// of their size, so that rounding does not fail checks. May have a unit if only one quantity is
+    /// checked, ie. "1min" for the time
+    #[arg(long)]
+    tolerance: Option<String>,
+}
+
+impl StatsArgs {
+    pub fn to_check(&self, context: &ParseContext) -> Result<Check, String> {
+        let parse_vector = |value: &Option<String>| match value {
+            Some(value) => node::Force::parse(value.clone(), context).map(Some),
+            None => Ok(None),
+        };
+        let check = StatsCheck {
+            tick: self.tick,
+            time: match &self.time {
+                Some(time) => Some(context.scalar(time, "Time", Quantity::Time)?),
+                None => None,
+            },
+            kinetic_energy: self.kinetic_energy,
+            potential_energy: self.potential_energy,
+            total_energy: self.total_energy,
+            momentum: parse_vector(&self.momentum)?,
+            angular_momentum: parse_vector(&self.angular_momentum)?,
+            centre_of_mass: match &self.centre_of_mass {
+                Some(centre_of_mass) => {
+                    Some(node::Position::parse(centre_of_mass.clone(), context)?)
+                }
+                None => None,
+            },
+        };
+        if check.tick.is_none()
+            && check.time.is_none()
+            && check.kinetic_energy.is_none()
+            && check.potential_energy.is_none()
+            && check.total_energy.is_none()
+            && check.momentum.is_none()
+            && check.angular_momentum.is_none()
+            && check.centre_of_mass.is_none()
+        {
+            return Err("Nothing to check - give a value to compare the stats against".to_string());
+        }
+        Ok(Check::Stats(check))
+    }
+
+    /// The tolerance, in the units of the simulation
+    pub fn tolerance(&self, context: &ParseContext) -> Result<node::Scalar, String> {
+        let quantities = [
+            (self.time.is_some(), Quantity::Time),
+            (self.kinetic_energy.is_some(), Quantity::Energy),
+            (self.potential_energy.is_some(), Quantity::Energy),
+            (self.total_energy.is_some(), Quantity::Energy),
+            (self.momentum.is_some(), Quantity::Momentum),
+            (self.angular_momentum.is_some(), Quantity::AngularMomentum),
+            (self.centre_of_mass.is_some(), Quantity::Length),
+        ];
+        let quantities: Vec<_> = quantities
+            .into_iter()
+            .filter_map(|(checked, quantity)| checked.then_some(quantity))
+            .collect();
+        super::parse_tolerance(&self.tolerance, &quantities, context)
+    }
+}
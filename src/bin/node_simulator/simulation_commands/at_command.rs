@@ -0,0 +1,67 @@
+use node_simulator::{
+    node::vector::ParseContext,
+    simulation::{schedule::Trigger, units::Quantity},
+};
+
+#[derive(clap::Args, Debug)]
+pub struct AtCommand {
+    /// Treat the value as a simulated time rather than a tick
+    #[arg(long)]
+    pub time: bool,
+    /// Tick, or simulated time with --time which may be followed by a unit such as "s" or "day", to
+    /// run the command at
+    pub value: String,
+    /// Command to run
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+    pub command: Vec<String>,
+}
+
+impl AtCommand {
+    /// Creates the trigger to run the command at, parsing a time against `context`
+    pub fn to_trigger(&self, context: &ParseContext) -> Result<Trigger, String> {
+        match self.time {
+            true => context
+                .scalar(&self.value, "Time", Quantity::Time)
+                .map(Trigger::AtTime),
+            false => match self.value.parse::<u64>() {
+                Ok(tick) => Ok(Trigger::AtTick(tick)),
+                Err(_) => Err(format!("Invalid tick: {}", self.value)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod an_at_command {
+    use node_simulator::simulation::{
+        dimensionality::Dimensionality,
+        units::{UnitSystem, Units},
+    };
+
+    use super::*;
+
+    fn trigger(time: bool, value: &str) -> Result<Trigger, String> {
+        let context = ParseContext {
+            dimensionality: Dimensionality::default(),
+            units: UnitSystem::Physical(Units::SI),
+        };
+        AtCommand {
+            time,
+            value: value.to_string(),
+            command: vec!["step".to_string()],
+        }
+        .to_trigger(&context)
+    }
+
+    #[test]
+    fn converts_the_time_into_the_units_of_the_simulation() {
+        assert_eq!(Ok(Trigger::AtTime(120.0)), trigger(true, "2min"));
+        assert_eq!(Ok(Trigger::AtTime(2.0)), trigger(true, "2"));
+    }
+
+    #[test]
+    fn gives_an_error_for_a_time_with_the_wrong_unit() {
+        assert!(trigger(true, "2km").is_err());
+        assert!(trigger(false, "2min").is_err());
+    }
+}
@@ -0,0 +1,16 @@
+use node_simulator::simulation::schedule::Trigger;
+
+#[derive(clap::Args, Debug)]
+pub struct EveryCommand {
+    /// Number of ticks between each run of the command
+    pub interval: u64,
+    /// Command to run
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+    pub command: Vec<String>,
+}
+
+impl From<&EveryCommand> for Trigger {
+    fn from(value: &EveryCommand) -> Self {
+        Trigger::Every(value.interval)
+    }
+}
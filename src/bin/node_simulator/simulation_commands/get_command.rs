@@ -1,6 +1,8 @@
 pub mod node_args;
+pub mod orbit_args;
 
 use node_args::NodeArgs;
+use orbit_args::OrbitArgs;
 
 #[derive(clap::Parser, Debug)]
 #[command(help_template = "Commands:\r\n{subcommands}")]
@@ -12,6 +14,20 @@ pub struct GetCommand {
 #[derive(clap::Subcommand, Debug)]
 pub enum Commands {
     Node(NodeArgs),
+    Orbit(OrbitArgs),
     Tps,
     Fps,
+    Softening,
+    PairForceMode,
+    Stats,
+    Drag,
+    TimeStep,
+    Dimensionality,
+    Units,
+    Emitters,
+    Sinks,
+    CollisionPolicy,
+    Schedule,
+    #[cfg(feature = "scripting")]
+    Hooks,
 }
@@ -9,11 +9,21 @@ pub struct NodeArgs {
     #[arg(long)]
     pub mass: bool,
     #[arg(long)]
+    pub radius: bool,
+    #[arg(long)]
     pub gravitational_constant_override: bool,
     #[arg(long)]
     pub dampen_rate: bool,
     #[arg(long)]
+    pub drag_coefficient: bool,
+    #[arg(long)]
     pub freeze: bool,
+    #[arg(long)]
+    pub locked_axes: bool,
+    #[arg(long)]
+    pub constraint: bool,
+    #[arg(long)]
+    pub kinematic: bool,
 }
 
 impl From<NodeArgs> for node_simulator::node::event::get::NodeArgs {
@@ -23,9 +33,14 @@ impl From<NodeArgs> for node_simulator::node::event::get::NodeArgs {
             position: value.position,
             velocity: value.velocity,
             mass: value.mass,
+            radius: value.radius,
             gravitational_constant_override: value.gravitational_constant_override,
             dampen_rate: value.dampen_rate,
+            drag_coefficient: value.drag_coefficient,
             freeze: value.freeze,
+            locked_axes: value.locked_axes,
+            constraint: value.constraint,
+            kinematic: value.kinematic,
         }
     }
 }
@@ -37,9 +52,14 @@ impl From<&NodeArgs> for node_simulator::node::event::get::NodeArgs {
             position: value.position,
             velocity: value.velocity,
             mass: value.mass,
+            radius: value.radius,
             gravitational_constant_override: value.gravitational_constant_override,
             dampen_rate: value.dampen_rate,
+            drag_coefficient: value.drag_coefficient,
             freeze: value.freeze,
+            locked_axes: value.locked_axes,
+            constraint: value.constraint,
+            kinematic: value.kinematic,
         }
     }
 }
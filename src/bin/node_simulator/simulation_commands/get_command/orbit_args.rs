@@ -0,0 +1,17 @@
+#[derive(clap::Args, Debug)]
+pub struct OrbitArgs {
+    #[arg(long)]
+    pub id: u32,
+    /// Node the orbit is around. Defaults to the node pulling hardest on the node
+    #[arg(long)]
+    pub around: Option<u32>,
+}
+
+impl From<&OrbitArgs> for node_simulator::node::event::get::OrbitArgs {
+    fn from(value: &OrbitArgs) -> Self {
+        Self {
+            id: value.id,
+            around: value.around,
+        }
+    }
+}
@@ -1,6 +1,16 @@
+pub mod emitter_args;
+#[cfg(feature = "scripting")]
+pub mod hook_args;
 pub mod node_args;
+pub mod schedule_args;
+pub mod sink_args;
 
+use emitter_args::EmitterArgs;
+#[cfg(feature = "scripting")]
+use hook_args::HookArgs;
 use node_args::NodeArgs;
+use schedule_args::ScheduleArgs;
+use sink_args::SinkArgs;
 
 #[derive(clap::Parser, Debug)]
 #[command(help_template = "Commands:\r\n{subcommands}")]
@@ -12,4 +22,9 @@ pub struct RemoveCommand {
 #[derive(clap::Subcommand, Debug)]
 pub enum Commands {
     Node(NodeArgs),
+    Emitter(EmitterArgs),
+    Sink(SinkArgs),
+    #[cfg(feature = "scripting")]
+    Hook(HookArgs),
+    Schedule(ScheduleArgs),
 }
@@ -0,0 +1,15 @@
+use node_simulator::node;
+
+#[derive(clap::Args, Debug)]
+pub struct EmitterArgs {
+    #[arg(short, long)]
+    id: u32,
+}
+
+impl From<&EmitterArgs> for node::RemoveEmitterEvent {
+    fn from(value: &EmitterArgs) -> Self {
+        node::RemoveEmitterEvent {
+            emitter_id: value.id,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use node_simulator::node;
+
+#[derive(clap::Args, Debug)]
+pub struct HookArgs {
+    #[arg(short, long)]
+    id: u32,
+}
+
+impl From<&HookArgs> for node::RemoveHookEvent {
+    fn from(value: &HookArgs) -> Self {
+        node::RemoveHookEvent { hook_id: value.id }
+    }
+}
@@ -0,0 +1,16 @@
+use node_simulator::node;
+
+#[derive(clap::Args, Debug)]
+pub struct ScheduleArgs {
+    /// Handle printed when the command was scheduled
+    #[arg(long)]
+    handle: u32,
+}
+
+impl From<&ScheduleArgs> for node::CancelScheduledEvent {
+    fn from(value: &ScheduleArgs) -> Self {
+        node::CancelScheduledEvent {
+            handle: value.handle,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use node_simulator::node;
+
+#[derive(clap::Args, Debug)]
+pub struct SinkArgs {
+    #[arg(short, long)]
+    id: u32,
+}
+
+impl From<&SinkArgs> for node::RemoveSinkEvent {
+    fn from(value: &SinkArgs) -> Self {
+        node::RemoveSinkEvent { sink_id: value.id }
+    }
+}
@@ -1,6 +1,13 @@
+mod expression;
+mod interpreter;
+
 use std::fs;
+use std::path::{Path, PathBuf};
 
-use super::SimulationCommand;
+#[cfg(feature = "scripting")]
+use super::add_command::{self, AddCommand};
+use super::{Command, SimulationCommand};
+use interpreter::{Include, ScriptError};
 
 #[derive(clap::Args, Debug)]
 pub struct ScriptCommand {
@@ -8,26 +15,211 @@ pub struct ScriptCommand {
 }
 
 impl ScriptCommand {
-    pub fn load_script(file: String) -> anyhow::Result<Vec<SimulationCommand>> {
-        let contents = fs::read_to_string(file)?;
-        let lines = contents.lines();
-        let commands = lines
-            .into_iter()
-            .map(|line| line.trim())
-            .map(Self::remove_comments)
-            .filter(|line| !line.is_empty())
-            .map(|line| SimulationCommand::try_from(line.to_string()))
-            .collect::<Result<Vec<_>, _>>();
-        match commands {
-            Ok(commands) => Ok(commands),
-            Err(err) => anyhow::bail!(err),
+    /// Runs the script in `file`, giving the commands it expands to. See docs/Scripting.md for the
+    /// statements scripts can use.
+    ///
+    /// Scripts run by the script are included in its commands. Their paths are relative to the
+    /// script that runs them, or to a directory in `search_path`. `file` itself is relative to the
+    /// working directory, or to a directory in `search_path`.
+    pub fn load_script(
+        file: String,
+        search_path: &[PathBuf],
+    ) -> anyhow::Result<Vec<SimulationCommand>> {
+        let Some(path) = Self::find_script(&file, Path::new(""), search_path) else {
+            anyhow::bail!("Could not find script '{}'", file);
+        };
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| anyhow::anyhow!("Could not read script '{}' - {}", file, err))?;
+        Ok(Self::load(&path, &contents, search_path, &mut vec![])?)
+    }
+
+    /// `loading` holds the scripts that are part way through loading, so that a script including
+    /// itself is caught
+    fn load(
+        path: &Path,
+        contents: &str,
+        search_path: &[PathBuf],
+        loading: &mut Vec<PathBuf>,
+    ) -> Result<Vec<SimulationCommand>, ScriptError> {
+        let file = path.display().to_string();
+        // Compare canonical paths, so that the same script reached by different paths is caught
+        loading.push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        let mut commands = vec![];
+        for expanded in interpreter::expand(&file, contents)? {
+            let mut command = SimulationCommand::try_from(expanded.command).map_err(|err| {
+                ScriptError::new(&file, expanded.line, err.trim_end().to_string())
+            })?;
+            if let Command::Assert(assert_command) | Command::Expect(assert_command) =
+                &mut command.command
+            {
+                assert_command.source = Some(format!("{}:{}", file, expanded.line));
+            }
+            // Hook files are found like included scripts, so scripts can share a directory with them
+            #[cfg(feature = "scripting")]
+            if let Command::Add(AddCommand {
+                command: add_command::Commands::Hook(hook_args),
+            }) = &mut command.command
+            {
+                let Some(path) = Self::find_script(&hook_args.file, directory, search_path) else {
+                    let message = format!("Could not find hook script '{}'", hook_args.file);
+                    return Err(ScriptError::new(&file, expanded.line, message));
+                };
+                hook_args.file = path.display().to_string();
+            }
+            match &command.command {
+                Command::Script(script_command) => {
+                    let including = Include {
+                        file: file.clone(),
+                        line: expanded.line,
+                    };
+                    commands.extend(Self::include(
+                        &script_command.file,
+                        directory,
+                        including,
+                        search_path,
+                        loading,
+                    )?)
+                }
+                _ => commands.push(command),
+            }
         }
+        loading.pop();
+        Ok(commands)
+    }
+
+    /// Loads the script `file`, run from the line `including` of a script in `directory`
+    fn include(
+        file: &str,
+        directory: &Path,
+        including: Include,
+        search_path: &[PathBuf],
+        loading: &mut Vec<PathBuf>,
+    ) -> Result<Vec<SimulationCommand>, ScriptError> {
+        let error_here =
+            |message: String| ScriptError::new(&including.file, including.line, message);
+        let Some(path) = Self::find_script(file, directory, search_path) else {
+            return Err(error_here(format!("Could not find script '{}'", file)));
+        };
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if let Some(start) = loading.iter().position(|loaded| *loaded == canonical) {
+            let cycle: Vec<String> = loading[start..]
+                .iter()
+                .chain([&canonical])
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(error_here(format!(
+                "Script includes itself - {}",
+                cycle.join(" -> ")
+            )));
+        }
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| error_here(format!("Could not read script '{}' - {}", file, err)))?;
+        Self::load(&path, &contents, search_path, loading).map_err(|mut err| {
+            err.included_from.push(including);
+            err
+        })
+    }
+
+    /// Finds `file` relative to `directory`, then each directory of `search_path` in turn
+    pub fn find_script(file: &str, directory: &Path, search_path: &[PathBuf]) -> Option<PathBuf> {
+        let path = Path::new(file);
+        if path.is_absolute() {
+            return path.is_file().then(|| path.to_path_buf());
+        }
+        std::iter::once(directory)
+            .chain(search_path.iter().map(PathBuf::as_path))
+            .map(|directory| directory.join(path))
+            .find(|path| path.is_file())
+    }
+}
+
+#[cfg(test)]
+mod a_script_command {
+    use super::*;
+
+    const SCRIPTS: &str = "tests/scripts/include";
+
+    fn steps_of(commands: Vec<SimulationCommand>) -> Vec<u32> {
+        commands
+            .into_iter()
+            .map(|command| match command.command {
+                Command::Step(step_command) => step_command.number_of_steps,
+                _ => panic!("Unexpected command"),
+            })
+            .collect()
     }
 
-    fn remove_comments(line: &str) -> &str {
-        if !line.contains("//") {
-            return line;
+    #[test]
+    fn includes_scripts_relative_to_the_including_script() {
+        let commands = ScriptCommand::load_script(format!("{SCRIPTS}/outer.txt"), &[]).unwrap();
+
+        assert_eq!(vec![1, 2, 3], steps_of(commands));
+    }
+
+    #[test]
+    fn finds_scripts_in_the_search_path() {
+        let search_path = [PathBuf::from(format!("{SCRIPTS}/nested"))];
+
+        let commands = ScriptCommand::load_script("inner.txt".to_string(), &search_path).unwrap();
+
+        assert_eq!(vec![2], steps_of(commands));
+    }
+
+    #[test]
+    fn gives_an_error_if_a_script_includes_itself() {
+        let err = ScriptCommand::load_script(format!("{SCRIPTS}/cycle_a.txt"), &[])
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("Script includes itself"), "{err}");
+        assert!(
+            err.ends_with(&format!("included from {SCRIPTS}/cycle_a.txt:1")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn shows_the_include_stack_in_errors() {
+        let err = ScriptCommand::load_script(format!("{SCRIPTS}/broken_outer.txt"), &[])
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(
+            format!(
+                "{SCRIPTS}/nested/broken.txt:2 - Unknown variable 'missing'\n\tincluded from {SCRIPTS}/broken_outer.txt:2"
+            ),
+            err
+        );
+    }
+
+    #[test]
+    fn names_the_script_it_could_not_read() {
+        let err = ScriptCommand::load_script(format!("{SCRIPTS}/not_utf8.txt"), &[])
+            .unwrap_err()
+            .to_string();
+
+        assert!(
+            err.starts_with(&format!("Could not read script '{SCRIPTS}/not_utf8.txt'")),
+            "{err}"
+        );
+    }
+
+    #[cfg(feature = "scripting")]
+    #[test]
+    fn finds_hook_files_relative_to_the_script() {
+        let commands =
+            ScriptCommand::load_script(format!("{SCRIPTS}/nested/add_hook.txt"), &[]).unwrap();
+
+        match &commands[0].command {
+            Command::Add(AddCommand {
+                command: add_command::Commands::Hook(hook_args),
+            }) => assert_eq!(
+                Path::new(SCRIPTS).join("nested").join("hook.rhai"),
+                Path::new(&hook_args.file)
+            ),
+            _ => panic!("Unexpected command"),
         }
-        return line.split("//").next().unwrap_or_default();
     }
 }
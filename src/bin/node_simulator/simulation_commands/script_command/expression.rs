@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+
+/// Values of the variables an expression can refer to
+pub type Variables = HashMap<String, f64>;
+
+/// Evaluates an arithmetic expression, such as `$r * cos($t) + 1`.
+///
+/// Variables may be written with or without a leading `$`. Comparisons and the logical operators
+/// `&&`, `||` and `!` give 1 for true and 0 for false, and treat any value other than 0 as true.
+pub fn evaluate(expression: &str, variables: &Variables) -> Result<f64, String> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        variables,
+    };
+    let value = parser.parse_or()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(token) => Err(format!("Unexpected '{}' in '{}'", token, expression)),
+    }
+}
+
+/// Evaluates a comma separated list of expressions, such as a vector, splitting only on the commas
+/// that are outside of brackets
+pub fn evaluate_list(expressions: &str, variables: &Variables) -> Result<Vec<f64>, String> {
+    let mut values = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, char) in expressions.char_indices() {
+        match char {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                values.push(evaluate(&expressions[start..index], variables)?);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+    values.push(evaluate(&expressions[start..], variables)?);
+    Ok(values)
+}
+
+/// Formats a value as a command argument. Whole numbers are written without a decimal point, so
+/// that they can be used as ids and counts.
+pub fn format_value(value: f64) -> String {
+    // Adding 0 turns -0 into 0
+    let value = value + 0.0;
+    match value.fract() == 0.0 && value.abs() < 1e15 {
+        true => format!("{}", value as i64),
+        false => format!("{}", value),
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(&'static str),
+    OpenBracket,
+    CloseBracket,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Identifier(identifier) => write!(f, "{}", identifier),
+            Token::Operator(operator) => write!(f, "{}", operator),
+            Token::OpenBracket => write!(f, "("),
+            Token::CloseBracket => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+const OPERATORS: [&str; 16] = [
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "^", "<", ">", "!", "=",
+];
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let chars: Vec<char> = expression.chars().collect();
+    let mut index = 0;
+    while index < chars.len() {
+        let char = chars[index];
+        if char.is_whitespace() {
+            index += 1;
+        } else if char.is_ascii_digit() || char == '.' {
+            let start = index;
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                index += 1;
+            }
+            // Exponents, such as 1e-5
+            if index < chars.len() && (chars[index] == 'e' || chars[index] == 'E') {
+                let mut end = index + 1;
+                if end < chars.len() && (chars[end] == '-' || chars[end] == '+') {
+                    end += 1;
+                }
+                if end < chars.len() && chars[end].is_ascii_digit() {
+                    index = end;
+                    while index < chars.len() && chars[index].is_ascii_digit() {
+                        index += 1;
+                    }
+                }
+            }
+            let number: String = chars[start..index].iter().collect();
+            match number.parse::<f64>() {
+                Ok(number) => tokens.push(Token::Number(number)),
+                Err(_) => return Err(format!("Invalid number '{}'", number)),
+            }
+        } else if char == '$' || char.is_alphabetic() || char == '_' {
+            let start = match char == '$' {
+                true => index + 1,
+                false => index,
+            };
+            index = start;
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            if index == start {
+                return Err("Expected a variable name after '$'".to_string());
+            }
+            tokens.push(Token::Identifier(chars[start..index].iter().collect()));
+        } else if char == '(' {
+            tokens.push(Token::OpenBracket);
+            index += 1;
+        } else if char == ')' {
+            tokens.push(Token::CloseBracket);
+            index += 1;
+        } else if char == ',' {
+            tokens.push(Token::Comma);
+            index += 1;
+        } else {
+            let rest: String = chars[index..].iter().collect();
+            match OPERATORS
+                .iter()
+                .find(|operator| rest.starts_with(*operator))
+            {
+                Some(&"=") | None => return Err(format!("Unexpected '{}'", char)),
+                Some(operator) => {
+                    tokens.push(Token::Operator(operator));
+                    index += operator.len();
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    variables: &'a Variables,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn next_operator_is(&self, operators: &[&str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Operator(operator)) if operators.contains(operator) => Some(operator),
+            _ => None,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<f64, String> {
+        let mut value = self.parse_and()?;
+        while self.next_operator_is(&["||"]).is_some() {
+            self.next();
+            let rhs = self.parse_and()?;
+            value = as_value(is_true(value) || is_true(rhs));
+        }
+        Ok(value)
+    }
+
+    fn parse_and(&mut self) -> Result<f64, String> {
+        let mut value = self.parse_comparison()?;
+        while self.next_operator_is(&["&&"]).is_some() {
+            self.next();
+            let rhs = self.parse_comparison()?;
+            value = as_value(is_true(value) && is_true(rhs));
+        }
+        Ok(value)
+    }
+
+    fn parse_comparison(&mut self) -> Result<f64, String> {
+        let lhs = self.parse_sum()?;
+        let Some(operator) = self.next_operator_is(&["==", "!=", "<=", ">=", "<", ">"]) else {
+            return Ok(lhs);
+        };
+        self.next();
+        let rhs = self.parse_sum()?;
+        Ok(as_value(match operator {
+            "==" => lhs == rhs,
+            "!=" => lhs != rhs,
+            "<=" => lhs <= rhs,
+            ">=" => lhs >= rhs,
+            "<" => lhs < rhs,
+            _ => lhs > rhs,
+        }))
+    }
+
+    fn parse_sum(&mut self) -> Result<f64, String> {
+        let mut value = self.parse_product()?;
+        while let Some(operator) = self.next_operator_is(&["+", "-"]) {
+            self.next();
+            let rhs = self.parse_product()?;
+            value = match operator {
+                "+" => value + rhs,
+                _ => value - rhs,
+            };
+        }
+        Ok(value)
+    }
+
+    fn parse_product(&mut self) -> Result<f64, String> {
+        let mut value = self.parse_unary()?;
+        while let Some(operator) = self.next_operator_is(&["*", "/", "%"]) {
+            self.next();
+            let rhs = self.parse_unary()?;
+            value = match operator {
+                "*" => value * rhs,
+                "/" => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Ok(value)
+    }
+
+    fn parse_unary(&mut self) -> Result<f64, String> {
+        match self.next_operator_is(&["-", "+", "!"]) {
+            Some("-") => {
+                self.next();
+                Ok(-self.parse_unary()?)
+            }
+            Some("+") => {
+                self.next();
+                self.parse_unary()
+            }
+            Some(_) => {
+                self.next();
+                Ok(as_value(!is_true(self.parse_unary()?)))
+            }
+            None => self.parse_power(),
+        }
+    }
+
+    fn parse_power(&mut self) -> Result<f64, String> {
+        let base = self.parse_primary()?;
+        match self.next_operator_is(&["^"]) {
+            Some(_) => {
+                self.next();
+                // Right associative, so 2^3^2 is 2^9
+                Ok(base.powf(self.parse_unary()?))
+            }
+            None => Ok(base),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<f64, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(number),
+            Some(Token::OpenBracket) => {
+                let value = self.parse_or()?;
+                match self.next() {
+                    Some(Token::CloseBracket) => Ok(value),
+                    _ => Err("Expected ')'".to_string()),
+                }
+            }
+            Some(Token::Identifier(name)) => match self.peek() {
+                Some(Token::OpenBracket) => {
+                    self.next();
+                    let arguments = self.parse_arguments()?;
+                    call(&name, &arguments)
+                }
+                _ => self.variable(&name),
+            },
+            Some(token) => Err(format!("Unexpected '{}'", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+
+    fn parse_arguments(&mut self) -> Result<Vec<f64>, String> {
+        let mut arguments = vec![];
+        if self.peek() == Some(&Token::CloseBracket) {
+            self.next();
+            return Ok(arguments);
+        }
+        loop {
+            arguments.push(self.parse_or()?);
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::CloseBracket) => return Ok(arguments),
+                _ => return Err("Expected ',' or ')'".to_string()),
+            }
+        }
+    }
+
+    fn variable(&self, name: &str) -> Result<f64, String> {
+        if let Some(value) = self.variables.get(name) {
+            return Ok(*value);
+        }
+        match name {
+            "pi" => Ok(std::f64::consts::PI),
+            "tau" => Ok(std::f64::consts::TAU),
+            "true" => Ok(1.0),
+            "false" => Ok(0.0),
+            _ => Err(format!("Unknown variable '{}'", name)),
+        }
+    }
+}
+
+fn call(function: &str, arguments: &[f64]) -> Result<f64, String> {
+    let expect_arguments = |count: usize| match arguments.len() == count {
+        true => Ok(()),
+        false => Err(format!(
+            "{} takes {} argument(s), but was given {}",
+            function,
+            count,
+            arguments.len()
+        )),
+    };
+    // Angles are in degrees, the same as the angles of vectors such as "sph(r,theta,phi)"
+    let unary: Option<fn(f64) -> f64> = match function {
+        "sin" => Some(|angle| angle.to_radians().sin()),
+        "cos" => Some(|angle| angle.to_radians().cos()),
+        "tan" => Some(|angle| angle.to_radians().tan()),
+        "asin" => Some(|value| value.asin().to_degrees()),
+        "acos" => Some(|value| value.acos().to_degrees()),
+        "atan" => Some(|value| value.atan().to_degrees()),
+        "sqrt" => Some(f64::sqrt),
+        "abs" => Some(f64::abs),
+        "floor" => Some(f64::floor),
+        "ceil" => Some(f64::ceil),
+        "round" => Some(f64::round),
+        "exp" => Some(f64::exp),
+        "ln" => Some(f64::ln),
+        "log10" => Some(f64::log10),
+        _ => None,
+    };
+    if let Some(unary) = unary {
+        expect_arguments(1)?;
+        return Ok(unary(arguments[0]));
+    }
+    let binary: Option<fn(f64, f64) -> f64> = match function {
+        "atan2" => Some(|y, x| y.atan2(x).to_degrees()),
+        "pow" => Some(f64::powf),
+        "min" => Some(f64::min),
+        "max" => Some(f64::max),
+        _ => None,
+    };
+    match binary {
+        Some(binary) => {
+            expect_arguments(2)?;
+            Ok(binary(arguments[0], arguments[1]))
+        }
+        None => Err(format!("Unknown function '{}'", function)),
+    }
+}
+
+fn is_true(value: f64) -> bool {
+    value != 0.0
+}
+
+fn as_value(condition: bool) -> f64 {
+    match condition {
+        true => 1.0,
+        false => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod an_expression {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("1 + 2 * 3", 7.0)]
+    #[case("(1 + 2) * 3", 9.0)]
+    #[case("-2 ^ 2", -4.0)]
+    #[case("2 ^ 3 ^ 2", 512.0)]
+    #[case("7 % 4", 3.0)]
+    #[case("1.5e1 / 3", 5.0)]
+    #[case("max(1, min(5, 3))", 3.0)]
+    #[case("3 > 2 && !(1 == 2)", 1.0)]
+    #[case("0 || 2 <= 1", 0.0)]
+    fn is_evaluated_with_precedence(#[case] expression: &str, #[case] expected: f64) {
+        assert_eq!(expected, evaluate(expression, &Variables::new()).unwrap());
+    }
+
+    #[rstest]
+    #[case("sin(90)", 1.0)]
+    #[case("cos(180)", -1.0)]
+    #[case("acos(0)", 90.0)]
+    #[case("atan2(1, 0)", 90.0)]
+    fn works_in_degrees(#[case] expression: &str, #[case] expected: f64) {
+        let value = evaluate(expression, &Variables::new()).unwrap();
+
+        assert!(
+            (expected - value).abs() < 1.0e-12,
+            "{} was {}",
+            expression,
+            value
+        );
+    }
+
+    #[test]
+    fn reads_variables_with_or_without_a_dollar_sign() {
+        let variables = Variables::from([("r".to_string(), 2.0), ("t".to_string(), 0.0)]);
+
+        assert_eq!(2.0, evaluate("$r*cos($t)", &variables).unwrap());
+        assert_eq!(4.0, evaluate("r * r", &variables).unwrap());
+    }
+
+    #[test]
+    fn splits_lists_outside_of_brackets() {
+        let variables = Variables::from([("r".to_string(), 2.0)]);
+
+        assert_eq!(
+            vec![3.0, 0.0, -2.0],
+            evaluate_list("max($r,3),0,-$r", &variables).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("1 +")]
+    #[case("$missing")]
+    #[case("nope(1)")]
+    #[case("sin(1, 2)")]
+    #[case("(1")]
+    #[case("1 = 1")]
+    fn gives_an_error_if_invalid(#[case] expression: &str) {
+        assert!(evaluate(expression, &Variables::new()).is_err());
+    }
+
+    #[test]
+    fn formats_whole_numbers_without_a_decimal_point() {
+        assert_eq!("3", format_value(3.0));
+        assert_eq!("-0.5", format_value(-0.5));
+        assert_eq!("0", format_value(-0.0));
+    }
+}
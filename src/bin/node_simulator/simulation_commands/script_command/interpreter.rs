@@ -0,0 +1,758 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::expression::{self, Variables};
+use crate::simulation_commands::tokenizer::{self, Argument, Quoting};
+
+/// How deeply macros may call other macros, so that a macro calling itself gives an error rather
+/// than overflowing the stack
+const MAX_MACRO_DEPTH: usize = 32;
+
+/// How many times a single loop may repeat, so that a loop over a huge range, or one starting at a
+/// number too large to count up from, gives an error rather than running forever
+const MAX_LOOP_ITERATIONS: usize = 100_000;
+
+/// How many statements and loop iterations a whole script may run, counting those inside macros,
+/// so that nested loops or macros calling each other many times give an error rather than
+/// expanding to more commands than fit in memory
+const MAX_STEPS: usize = 1_000_000;
+
+/// Error in a script, naming the script file and line it happened on
+#[derive(Debug)]
+pub struct ScriptError {
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+    /// Scripts that included the script with the error, starting with the innermost
+    pub included_from: Vec<Include>,
+}
+
+/// Line of a script that included another script
+#[derive(PartialEq, Debug)]
+pub struct Include {
+    pub file: String,
+    pub line: usize,
+}
+
+impl ScriptError {
+    pub fn new(file: &str, line: usize, message: String) -> Self {
+        ScriptError {
+            file: file.to_string(),
+            line,
+            message,
+            included_from: vec![],
+        }
+    }
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{} - {}", self.file, self.line, self.message)?;
+        for include in self.included_from.iter() {
+            write!(f, "\n\tincluded from {}:{}", include.file, include.line)?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for ScriptError {}
+
+/// A command produced by running a script, and the line of the script it came from
+#[derive(PartialEq, Debug)]
+pub struct ExpandedCommand {
+    pub line: usize,
+    pub command: String,
+}
+
+/// Runs the `let`, `for`, `if` and `macro` statements in a script, giving the simulation commands
+/// they expand to. `file` is only used to name the script in errors.
+pub fn expand(file: &str, contents: &str) -> Result<Vec<ExpandedCommand>, ScriptError> {
+    let lines: Vec<(usize, &str)> = contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, tokenizer::strip_comment(line).trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    let mut parser = Parser {
+        file,
+        lines,
+        position: 0,
+    };
+    let statements = match parser.parse_block()? {
+        (statements, BlockEnd::EndOfFile) => statements,
+        (_, BlockEnd::Close(line) | BlockEnd::Else(line) | BlockEnd::ElseIf(line, _)) => {
+            return Err(parser.error(line, "Unexpected '}'".to_string()))
+        }
+    };
+    let mut interpreter = Interpreter {
+        file,
+        variables: Variables::new(),
+        macros: HashMap::new(),
+        macro_depth: 0,
+        steps: 0,
+        commands: vec![],
+    };
+    interpreter.run(&statements)?;
+    Ok(interpreter.commands)
+}
+
+struct Line {
+    number: usize,
+    statement: Statement,
+}
+
+enum Statement {
+    Let {
+        name: String,
+        expression: String,
+    },
+    For {
+        variable: String,
+        start: String,
+        end: String,
+        inclusive: bool,
+        body: Vec<Line>,
+    },
+    If {
+        condition: String,
+        body: Vec<Line>,
+        else_body: Vec<Line>,
+    },
+    Macro {
+        name: String,
+        parameters: Vec<String>,
+        body: Rc<Vec<Line>>,
+    },
+    Command(String),
+}
+
+/// How a block of statements was ended
+enum BlockEnd {
+    EndOfFile,
+    /// `}`
+    Close(usize),
+    /// `} else {`
+    Else(usize),
+    /// `} else if <condition> {`
+    ElseIf(usize, String),
+}
+
+struct Parser<'a> {
+    file: &'a str,
+    lines: Vec<(usize, &'a str)>,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, line: usize, message: String) -> ScriptError {
+        ScriptError::new(self.file, line, message)
+    }
+
+    fn parse_block(&mut self) -> Result<(Vec<Line>, BlockEnd), ScriptError> {
+        let mut statements = vec![];
+        while let Some(&(number, line)) = self.lines.get(self.position) {
+            self.position += 1;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words == ["}"] {
+                return Ok((statements, BlockEnd::Close(number)));
+            }
+            if words == ["}", "else", "{"] {
+                return Ok((statements, BlockEnd::Else(number)));
+            }
+            if let Some(condition) = line
+                .strip_prefix('}')
+                .map(str::trim_start)
+                .and_then(|rest| rest.strip_prefix("else if "))
+                .and_then(|rest| rest.strip_suffix('{'))
+            {
+                return Ok((
+                    statements,
+                    BlockEnd::ElseIf(number, condition.trim().to_string()),
+                ));
+            }
+            let statement = match words[0] {
+                "let" => self.parse_let(number, line)?,
+                "for" => self.parse_for(number, line)?,
+                "if" => self.parse_if(number, line)?,
+                "macro" => self.parse_macro(number, line)?,
+                _ => Statement::Command(line.to_string()),
+            };
+            statements.push(Line { number, statement });
+        }
+        Ok((statements, BlockEnd::EndOfFile))
+    }
+
+    /// Parses the statements up to the `}` that closes a block opened on `opened_on`
+    fn parse_body(&mut self, opened_on: usize) -> Result<Vec<Line>, ScriptError> {
+        match self.parse_block()? {
+            (body, BlockEnd::Close(_)) => Ok(body),
+            (_, BlockEnd::Else(line) | BlockEnd::ElseIf(line, _)) => Err(self.error(
+                line,
+                "'else' can only follow the block of an 'if'".to_string(),
+            )),
+            (_, BlockEnd::EndOfFile) => {
+                Err(self.error(opened_on, "Block is missing its closing '}'".to_string()))
+            }
+        }
+    }
+
+    /// The text between `keyword` and the `{` that opens a block
+    fn block_header<'b>(
+        &self,
+        number: usize,
+        line: &'b str,
+        keyword: &str,
+    ) -> Result<&'b str, ScriptError> {
+        match line
+            .strip_prefix(keyword)
+            .and_then(|rest| rest.strip_suffix('{'))
+        {
+            Some(header) => Ok(header.trim()),
+            None => Err(self.error(
+                number,
+                format!(
+                    "'{}' must be followed by a block starting with '{{'",
+                    keyword
+                ),
+            )),
+        }
+    }
+
+    fn parse_let(&self, number: usize, line: &str) -> Result<Statement, ScriptError> {
+        let Some((name, expression)) = line["let".len()..].split_once('=') else {
+            return Err(self.error(number, "Expected 'let <name> = <value>'".to_string()));
+        };
+        Ok(Statement::Let {
+            name: self.parse_name(number, name)?,
+            expression: expression.trim().to_string(),
+        })
+    }
+
+    fn parse_for(&mut self, number: usize, line: &str) -> Result<Statement, ScriptError> {
+        let header = self.block_header(number, line, "for")?;
+        let Some((variable, range)) = header.split_once(" in ") else {
+            return Err(self.error(
+                number,
+                "Expected 'for <name> in <start>..<end> {'".to_string(),
+            ));
+        };
+        let (start, end, inclusive) = match range.split_once("..=") {
+            Some((start, end)) => (start, end, true),
+            None => match range.split_once("..") {
+                Some((start, end)) => (start, end, false),
+                None => {
+                    return Err(self.error(
+                        number,
+                        format!("Expected a range such as 0..10, but got '{}'", range.trim()),
+                    ))
+                }
+            },
+        };
+        Ok(Statement::For {
+            variable: self.parse_name(number, variable)?,
+            start: start.trim().to_string(),
+            end: end.trim().to_string(),
+            inclusive,
+            body: self.parse_body(number)?,
+        })
+    }
+
+    fn parse_if(&mut self, number: usize, line: &str) -> Result<Statement, ScriptError> {
+        let condition = self.block_header(number, line, "if")?.to_string();
+        self.parse_if_from(number, condition)
+    }
+
+    fn parse_if_from(
+        &mut self,
+        number: usize,
+        condition: String,
+    ) -> Result<Statement, ScriptError> {
+        let (body, else_body) = match self.parse_block()? {
+            (body, BlockEnd::Close(_)) => (body, vec![]),
+            (body, BlockEnd::Else(else_number)) => (body, self.parse_body(else_number)?),
+            (body, BlockEnd::ElseIf(else_number, else_condition)) => {
+                let statement = self.parse_if_from(else_number, else_condition)?;
+                (
+                    body,
+                    vec![Line {
+                        number: else_number,
+                        statement,
+                    }],
+                )
+            }
+            (_, BlockEnd::EndOfFile) => {
+                return Err(self.error(number, "Block is missing its closing '}'".to_string()))
+            }
+        };
+        Ok(Statement::If {
+            condition,
+            body,
+            else_body,
+        })
+    }
+
+    fn parse_macro(&mut self, number: usize, line: &str) -> Result<Statement, ScriptError> {
+        let header = self.block_header(number, line, "macro")?;
+        let mut words = header.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err(self.error(
+                number,
+                "Expected 'macro <name> <parameters...> {'".to_string(),
+            ));
+        };
+        let name = self.parse_name(number, name)?;
+        let parameters = words
+            .map(|parameter| self.parse_name(number, parameter))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Statement::Macro {
+            name,
+            parameters,
+            body: Rc::new(self.parse_body(number)?),
+        })
+    }
+
+    fn parse_name(&self, number: usize, name: &str) -> Result<String, ScriptError> {
+        let name = name.trim();
+        let name = name.strip_prefix('$').unwrap_or(name);
+        let is_valid = name.starts_with(|char: char| char.is_alphabetic() || char == '_')
+            && name
+                .chars()
+                .all(|char| char.is_alphanumeric() || char == '_');
+        match is_valid {
+            true => Ok(name.to_string()),
+            false => Err(self.error(number, format!("Invalid name '{}'", name))),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Macro {
+    parameters: Vec<String>,
+    body: Rc<Vec<Line>>,
+}
+
+struct Interpreter<'a> {
+    file: &'a str,
+    variables: Variables,
+    macros: HashMap<String, Macro>,
+    macro_depth: usize,
+    steps: usize,
+    commands: Vec<ExpandedCommand>,
+}
+
+impl Interpreter<'_> {
+    fn error(&self, line: usize, message: String) -> ScriptError {
+        ScriptError::new(self.file, line, message)
+    }
+
+    fn evaluate(&self, line: usize, expression: &str) -> Result<f64, ScriptError> {
+        expression::evaluate(expression, &self.variables).map_err(|err| self.error(line, err))
+    }
+
+    /// Counts a statement or loop iteration towards [`MAX_STEPS`]
+    fn count_step(&mut self, line: usize) -> Result<(), ScriptError> {
+        if self.steps == MAX_STEPS {
+            return Err(self.error(
+                line,
+                format!("Scripts can only run {} statements", MAX_STEPS),
+            ));
+        }
+        self.steps += 1;
+        Ok(())
+    }
+
+    fn run(&mut self, statements: &[Line]) -> Result<(), ScriptError> {
+        for Line { number, statement } in statements {
+            let number = *number;
+            self.count_step(number)?;
+            match statement {
+                Statement::Let { name, expression } => {
+                    let value = self.evaluate(number, expression)?;
+                    self.variables.insert(name.clone(), value);
+                }
+                Statement::For {
+                    variable,
+                    start,
+                    end,
+                    inclusive,
+                    body,
+                } => {
+                    let start = self.evaluate(number, start)?;
+                    let end = self.evaluate(number, end)?;
+                    let mut value = start;
+                    let mut iterations = 0;
+                    while value < end || (*inclusive && value == end) {
+                        if iterations == MAX_LOOP_ITERATIONS {
+                            return Err(self.error(
+                                number,
+                                format!("Loops can only repeat {} times", MAX_LOOP_ITERATIONS),
+                            ));
+                        }
+                        iterations += 1;
+                        self.count_step(number)?;
+                        self.variables.insert(variable.clone(), value);
+                        self.run(body)?;
+                        value += 1.0;
+                    }
+                }
+                Statement::If {
+                    condition,
+                    body,
+                    else_body,
+                } => match self.evaluate(number, condition)? != 0.0 {
+                    true => self.run(body)?,
+                    false => self.run(else_body)?,
+                },
+                Statement::Macro {
+                    name,
+                    parameters,
+                    body,
+                } => {
+                    let called = Macro {
+                        parameters: parameters.clone(),
+                        body: body.clone(),
+                    };
+                    self.macros.insert(name.clone(), called);
+                }
+                Statement::Command(command) => self.run_command(number, command)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn run_command(&mut self, number: usize, command: &str) -> Result<(), ScriptError> {
+        let arguments = tokenizer::split(command).map_err(|err| self.error(number, err))?;
+        if let Some(called) = self.macros.get(&arguments[0].text).cloned() {
+            return self.call_macro(number, &arguments[0].text, &called, &arguments[1..]);
+        }
+        let arguments = arguments
+            .iter()
+            .map(|argument| {
+                self.substitute(argument)
+                    .map(|text| tokenizer::quote(&text))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| self.error(number, err))?;
+        self.commands.push(ExpandedCommand {
+            line: number,
+            command: arguments.join(" "),
+        });
+        Ok(())
+    }
+
+    fn call_macro(
+        &mut self,
+        number: usize,
+        name: &str,
+        called: &Macro,
+        arguments: &[Argument],
+    ) -> Result<(), ScriptError> {
+        if arguments.len() != called.parameters.len() {
+            return Err(self.error(
+                number,
+                format!(
+                    "Macro '{}' takes {} argument(s), but was given {}",
+                    name,
+                    called.parameters.len(),
+                    arguments.len()
+                ),
+            ));
+        }
+        if self.macro_depth == MAX_MACRO_DEPTH {
+            return Err(self.error(
+                number,
+                format!("Macros can only be nested {} deep", MAX_MACRO_DEPTH),
+            ));
+        }
+        let values = arguments
+            .iter()
+            .map(|argument| self.evaluate(number, &argument.text))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Parameters and variables set by the macro are only visible inside it
+        let variables = self.variables.clone();
+        for (parameter, value) in called.parameters.iter().zip(values) {
+            self.variables.insert(parameter.clone(), value);
+        }
+        self.macro_depth += 1;
+        let result = self.run(&called.body);
+        self.macro_depth -= 1;
+        self.variables = variables;
+        result
+    }
+
+    /// Replaces an argument containing variables or quoted arithmetic with its value. Lists, such
+    /// as vectors, have each of their values calculated, as do the values of vectors written with
+    /// functions or units, such as "node(1) + sph($r km, 90, 0)".
+    fn substitute(&self, argument: &Argument) -> Result<String, String> {
+        let text = &argument.text;
+        let needs_substituting = match argument.quoting {
+            Quoting::Unquoted => text.contains('$'),
+            Quoting::Double => true,
+            // Single quoted and escaped arguments are kept as they are, like in a shell
+            Quoting::Literal => false,
+        };
+        if !needs_substituting {
+            return Ok(text.clone());
+        }
+        match expression::evaluate_list(text, &self.variables) {
+            Ok(values) => Ok(values
+                .into_iter()
+                .map(expression::format_value)
+                .collect::<Vec<_>>()
+                .join(",")),
+            // Quoted text that isn't arithmetic is passed on as it is
+            Err(_) if !text.contains('$') => Ok(text.clone()),
+            Err(_) => self.substitute_vector(text),
+        }
+    }
+
+    /// Calculates the values inside each bracket of a vector, keeping the functions and signs
+    /// between them for the vector to be parsed with
+    fn substitute_vector(&self, vector: &str) -> Result<String, String> {
+        let mut substituted = String::new();
+        let mut rest = vector;
+        while let Some(open) = rest.find(['(', '[']) {
+            let Some(close) = closing_bracket(rest, open) else {
+                break;
+            };
+            substituted.push_str(&rest[..=open]);
+            substituted.push_str(&self.substitute_values(&rest[open + 1..close])?);
+            substituted.push_str(&rest[close..=close]);
+            rest = &rest[close + 1..];
+        }
+        match substituted.is_empty() {
+            true => self.substitute_values(rest),
+            false => Ok(substituted + rest),
+        }
+    }
+
+    /// Calculates each of a comma separated list of values, which may be followed by a unit
+    fn substitute_values(&self, values: &str) -> Result<String, String> {
+        split_list(values)
+            .into_iter()
+            .map(|value| match expression::evaluate(value, &self.variables) {
+                Ok(value) => Ok(expression::format_value(value)),
+                Err(err) => {
+                    let value = value.trim();
+                    let number =
+                        value.trim_end_matches(|char: char| char.is_alphabetic() || char == '/');
+                    let unit = &value[number.len()..];
+                    // Names of variables and functions are not units
+                    let ends_value = number.ends_with(|char: char| {
+                        char.is_ascii_digit() || char == ')' || char.is_whitespace()
+                    });
+                    if unit.is_empty() || !ends_value {
+                        return Err(err);
+                    }
+                    let number = expression::evaluate(number, &self.variables)?;
+                    Ok(format!("{}{}", expression::format_value(number), unit))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|values| values.join(","))
+    }
+}
+
+/// Index of the bracket closing the one opened at `open`
+fn closing_bracket(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (index, char) in text.char_indices().skip_while(|(index, _)| *index < open) {
+        match char {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            _ => (),
+        }
+        if depth == 0 {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// Splits a comma separated list on the commas that are outside of brackets
+fn split_list(list: &str) -> Vec<&str> {
+    let mut values = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, char) in list.char_indices() {
+        match char {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                values.push(&list[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+    values.push(&list[start..]);
+    values
+}
+
+#[cfg(test)]
+mod a_script {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn commands_of(contents: &str) -> Vec<String> {
+        expand("test.txt", contents)
+            .unwrap()
+            .into_iter()
+            .map(|expanded| expanded.command)
+            .collect()
+    }
+
+    #[test]
+    fn passes_plain_commands_through() {
+        let contents = "add node --id 1 // Sun\n\nset node --id 1 --position \"4,0,0\"";
+
+        assert_eq!(
+            vec!["add node --id 1", "set node --id 1 --position 4,0,0"],
+            commands_of(contents)
+        );
+    }
+
+    #[test]
+    fn keeps_single_quoted_and_escaped_arguments_as_they_are() {
+        let contents = "let x = 1\nscript 'My Scripts/$x.txt'\nscript My\\ Scripts/a.txt";
+
+        assert_eq!(
+            vec!["script 'My Scripts/$x.txt'", "script 'My Scripts/a.txt'"],
+            commands_of(contents)
+        );
+    }
+
+    #[test]
+    fn substitutes_variables_and_arithmetic() {
+        let contents = "let r = 2\nlet t = 0\nadd node --id $r --position \"$r*cos($t), 0, -$r\"";
+
+        assert_eq!(
+            vec!["add node --id 2 --position 2,0,-2"],
+            commands_of(contents)
+        );
+    }
+
+    #[rstest]
+    #[case("\"sph($r,90,0)\"", "sph(2,90,0)")]
+    #[case("\"node(1)+($r,0,0)\"", "node(1)+(2,0,0)")]
+    #[case("\"($r km,0,0)\"", "(2km,0,0)")]
+    #[case("\"$r km,-$r km/s,0\"", "2km,-2km/s,0")]
+    #[case(
+        "\"node(1) - cyl(2*$r, cos(0)*90deg, -$r)\"",
+        "'node(1) - cyl(4,90deg,-2)'"
+    )]
+    fn substitutes_variables_inside_vector_functions_and_units(
+        #[case] vector: &str,
+        #[case] expected_vector: &str,
+    ) {
+        let contents = format!("let r = 2\nadd node --id 2 --position {}", vector);
+
+        assert_eq!(
+            vec![format!("add node --id 2 --position {}", expected_vector)],
+            commands_of(&contents)
+        );
+    }
+
+    #[test]
+    fn gives_an_error_for_unknown_variables_inside_vectors() {
+        let err = expand("test.txt", "add node --id 2 --position \"sph($r,90,0)\"").unwrap_err();
+
+        assert_eq!(1, err.line);
+        assert!(err.to_string().contains("Unknown variable 'r'"), "{}", err);
+    }
+
+    #[test]
+    fn repeats_commands_in_loops() {
+        let contents =
+            "for i in 0..2 {\n  add node --id $i\n}\nfor i in 5..=6 {\n  add node --id $i\n}";
+
+        assert_eq!(
+            vec![
+                "add node --id 0",
+                "add node --id 1",
+                "add node --id 5",
+                "add node --id 6"
+            ],
+            commands_of(contents)
+        );
+    }
+
+    #[test]
+    fn runs_the_branch_matching_the_condition() {
+        let contents = "for i in 0..3 {\n  if $i == 0 {\n    step\n  } else if $i == 1 {\n    close\n  } else {\n    add node --id $i\n  }\n}";
+
+        assert_eq!(
+            vec!["step", "close", "add node --id 2"],
+            commands_of(contents)
+        );
+    }
+
+    #[test]
+    fn expands_macros_with_their_own_variables() {
+        let contents = "let id = 9\nmacro planet id r {\n  add node --id $id --position \"$r,0,0\"\n}\nplanet 1 4\nplanet 2 \"2*4\"\nadd node --id $id";
+
+        assert_eq!(
+            vec![
+                "add node --id 1 --position 4,0,0",
+                "add node --id 2 --position 8,0,0",
+                "add node --id 9"
+            ],
+            commands_of(contents)
+        );
+    }
+
+    #[test]
+    fn names_the_file_and_line_of_errors() {
+        let err = expand("planets.txt", "add node --id 1\n\nadd node --id $missing").unwrap_err();
+
+        assert_eq!(3, err.line);
+        assert!(err.to_string().starts_with("planets.txt:3 - "));
+    }
+
+    #[test]
+    fn gives_an_error_for_unclosed_blocks() {
+        let err = expand("test.txt", "step\nfor i in 0..2 {\n  step").unwrap_err();
+
+        assert_eq!(2, err.line);
+    }
+
+    #[test]
+    fn stops_macros_that_call_themselves() {
+        let err = expand("test.txt", "macro forever {\n  forever\n}\nforever").unwrap_err();
+
+        assert_eq!(2, err.line);
+    }
+
+    #[rstest]
+    #[case("for i in 0..100000 {\n  for j in 0..100000 {\n    step\n  }\n}")]
+    #[case("for i in 0..100000 {\n  for j in 0..100000 {\n  }\n}")]
+    #[case(
+        "macro grow depth {\n  if $depth > 0 {\n    grow \"$depth - 1\"\n    grow \"$depth - 1\"\n  }\n  step\n}\ngrow 30"
+    )]
+    fn stops_scripts_that_run_too_many_statements(#[case] contents: &str) {
+        let err = expand("test.txt", contents).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("Scripts can only run 1000000 statements"));
+    }
+
+    #[test]
+    fn keeps_slashes_inside_quotes() {
+        let commands = commands_of("print \"http://example.com\" // comment");
+
+        assert_eq!(vec!["print http://example.com"], commands);
+    }
+
+    #[rstest]
+    #[case("for i in 0..1e12 {\n  step\n}")]
+    #[case("for i in 1e17..1e17+64 {\n  step\n}")]
+    fn stops_loops_that_repeat_too_many_times(#[case] contents: &str) {
+        let err = expand("test.txt", &format!("step\n{}", contents)).unwrap_err();
+
+        assert_eq!(2, err.line);
+        assert!(err
+            .to_string()
+            .starts_with("test.txt:2 - Loops can only repeat"));
+    }
+}
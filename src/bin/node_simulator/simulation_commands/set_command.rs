@@ -1,10 +1,28 @@
+pub mod adaptive_step_args;
+pub mod collision_policy_args;
+pub mod dimensionality_args;
+pub mod drag_args;
 pub mod fps_args;
 pub mod node_args;
+pub mod pair_force_mode_args;
+pub mod softening_args;
+pub mod stats_log_args;
+pub mod time_step_args;
 pub mod tps_args;
+pub mod units_args;
 
+use adaptive_step_args::AdaptiveStepArgs;
+use collision_policy_args::CollisionPolicyArgs;
+use dimensionality_args::DimensionalityArgs;
+use drag_args::DragArgs;
 use fps_args::FpsArgs;
 use node_args::NodeArgs;
+use pair_force_mode_args::PairForceModeArgs;
+use softening_args::SofteningArgs;
+use stats_log_args::StatsLogArgs;
+use time_step_args::TimeStepArgs;
 use tps_args::TpsArgs;
+use units_args::UnitsArgs;
 
 #[derive(clap::Parser, Debug)]
 #[command(help_template = "Commands:\r\n{subcommands}")]
@@ -15,7 +33,16 @@ pub struct SetCommand {
 
 #[derive(clap::Subcommand, Debug)]
 pub enum Commands {
-    Node(NodeArgs),
+    Node(Box<NodeArgs>),
     Tps(TpsArgs),
     Fps(FpsArgs),
+    Softening(SofteningArgs),
+    PairForceMode(PairForceModeArgs),
+    StatsLog(StatsLogArgs),
+    Drag(DragArgs),
+    TimeStep(TimeStepArgs),
+    AdaptiveStep(AdaptiveStepArgs),
+    CollisionPolicy(CollisionPolicyArgs),
+    Dimensionality(DimensionalityArgs),
+    Units(UnitsArgs),
 }
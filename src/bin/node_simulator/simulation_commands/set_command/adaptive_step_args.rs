@@ -0,0 +1,45 @@
+use node_simulator::{node, simulation::adaptive_step::AdaptiveStepMode};
+
+#[derive(clap::Args, Debug)]
+pub struct AdaptiveStepArgs {
+    #[arg(ignore_case = true)]
+    pub mode: Option<Mode>,
+    /// Distance nodes may move, or be in error by, over a single sub-step
+    #[arg(long)]
+    pub tolerance: Option<node::Scalar>,
+    #[arg(long)]
+    pub min_time_step: Option<node::Scalar>,
+    #[arg(long)]
+    pub max_time_step: Option<node::Scalar>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Mode {
+    /// Take each step as a single sub-step
+    Fixed,
+    /// Choose sub-steps from the greatest acceleration of any node
+    Acceleration,
+    /// Choose sub-steps from the difference between each sub-step and a trapezoidal step using the
+    /// forces at its end, retrying sub-steps with too large an error
+    ErrorEstimate,
+    /// Give each node its own power of two fraction of the time step, chosen from its acceleration.
+    /// Does not conserve momentum
+    Block,
+}
+
+impl From<&AdaptiveStepArgs> for node::SetAdaptiveStepEvent {
+    fn from(value: &AdaptiveStepArgs) -> Self {
+        let mode = value.mode.as_ref().map(|mode| match mode {
+            Mode::Fixed => AdaptiveStepMode::Fixed,
+            Mode::Acceleration => AdaptiveStepMode::Acceleration,
+            Mode::ErrorEstimate => AdaptiveStepMode::ErrorEstimate,
+            Mode::Block => AdaptiveStepMode::Block,
+        });
+        Self {
+            mode,
+            tolerance: value.tolerance,
+            min_time_step: value.min_time_step,
+            max_time_step: value.max_time_step,
+        }
+    }
+}
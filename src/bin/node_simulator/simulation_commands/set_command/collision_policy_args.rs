@@ -0,0 +1,25 @@
+use node_simulator::{node, simulation::collision::CollisionPolicy};
+
+#[derive(clap::Args, Debug)]
+pub struct CollisionPolicyArgs {
+    #[arg(ignore_case = true)]
+    pub policy: Policy,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Policy {
+    /// Let nodes pass through eachother
+    None,
+    /// Merge nodes that come within their combined radius into a single node
+    Merge,
+}
+
+impl From<&CollisionPolicyArgs> for node::SetCollisionPolicyEvent {
+    fn from(value: &CollisionPolicyArgs) -> Self {
+        let collision_policy = match value.policy {
+            Policy::None => CollisionPolicy::None,
+            Policy::Merge => CollisionPolicy::Merge,
+        };
+        Self { collision_policy }
+    }
+}
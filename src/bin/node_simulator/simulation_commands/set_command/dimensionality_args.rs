@@ -0,0 +1,27 @@
+use node_simulator::{node, simulation::dimensionality::Dimensionality};
+
+#[derive(clap::Args, Debug)]
+pub struct DimensionalityArgs {
+    #[arg(ignore_case = true)]
+    pub dimensionality: DimensionalityValue,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum DimensionalityValue {
+    /// Restrict nodes to the XZ plane, viewed top-down. Vectors may be given as "x,z"
+    #[value(name = "2d")]
+    TwoD,
+    /// Let nodes move along every axis
+    #[value(name = "3d")]
+    ThreeD,
+}
+
+impl From<&DimensionalityArgs> for node::SetDimensionalityEvent {
+    fn from(value: &DimensionalityArgs) -> Self {
+        let dimensionality = match value.dimensionality {
+            DimensionalityValue::TwoD => Dimensionality::TwoD,
+            DimensionalityValue::ThreeD => Dimensionality::ThreeD,
+        };
+        Self { dimensionality }
+    }
+}
@@ -0,0 +1,33 @@
+use node_simulator::node::{self, drag::DragModel};
+
+#[derive(clap::Args, Debug)]
+pub struct DragArgs {
+    #[arg(ignore_case = true)]
+    pub model: Option<Model>,
+    #[arg(long)]
+    pub medium_density: Option<node::Scalar>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Model {
+    /// Dampen velocity by each nodes dampen rate every tick, regardless of time step
+    Legacy,
+    /// Stokes drag, proportional to velocity
+    Linear,
+    /// Drag proportional to the square of velocity
+    Quadratic,
+}
+
+impl From<&DragArgs> for node::SetDragEvent {
+    fn from(value: &DragArgs) -> Self {
+        let model = value.model.as_ref().map(|model| match model {
+            Model::Legacy => DragModel::Legacy,
+            Model::Linear => DragModel::Linear,
+            Model::Quadratic => DragModel::Quadratic,
+        });
+        Self {
+            model,
+            medium_density: value.medium_density,
+        }
+    }
+}
@@ -1,55 +1,193 @@
-use node_simulator::node::{self, event::set_node};
+use node_simulator::{
+    node::{
+        self,
+        constraint::{Axes, Constraint},
+        event::set_node,
+        kinematic::Path,
+        vector::ParseContext,
+    },
+    simulation::units::Quantity,
+};
 
 #[derive(clap::Args, Debug)]
 pub struct NodeArgs {
     #[arg(long)]
     id: u32,
-    #[arg(long)]
+    #[arg(long, allow_hyphen_values = true)]
     position: Option<String>,
-    #[arg(long)]
+    #[arg(long, allow_hyphen_values = true)]
     velocity: Option<String>,
+    /// Mass of the node, which may be followed by a unit such as "kg" or "Msun"
     #[arg(long)]
-    mass: Option<f32>,
+    mass: Option<String>,
+    /// Size of the node when colliding with other nodes, which may be followed by a unit
+    #[arg(long)]
+    radius: Option<String>,
+    /// Gravitational constant used for the node. With the default symmetric pair force mode this
+    /// also changes how strongly other nodes pull on it
     #[arg(long, allow_hyphen_values = true)]
-    gravitational_constant_override: Option<f32>,
+    gravitational_constant_override: Option<node::Scalar>,
+    #[arg(long)]
+    dampen_rate: Option<node::Scalar>,
     #[arg(long)]
-    dampen_rate: Option<f32>,
+    drag_coefficient: Option<node::Scalar>,
     #[arg(long)]
     freeze: Option<bool>,
+    /// Axes the node cannot move along, ie. "xz", or "none"
+    #[arg(long)]
+    lock_axes: Option<String>,
+    #[arg(long, ignore_case = true)]
+    constraint: Option<ConstraintKind>,
+    #[arg(long, allow_hyphen_values = true, default_value = "0,0,0")]
+    constraint_origin: String,
+    /// Direction of a line constraint, or normal of a plane constraint
+    #[arg(long, allow_hyphen_values = true)]
+    constraint_direction: Option<String>,
+    #[arg(long, ignore_case = true)]
+    kinematic: Option<KinematicKind>,
+    #[arg(long, allow_hyphen_values = true, default_value = "0,0,0")]
+    circle_centre: String,
+    #[arg(long, default_value = "1")]
+    circle_radius: String,
+    #[arg(long, default_value = "1")]
+    circle_period: String,
+    #[arg(long, allow_hyphen_values = true, default_value = "0,1,0")]
+    circle_normal: String,
+    /// Positions to move between, separated by semicolons, ie. "0,0,0;1,0,0"
+    #[arg(long, allow_hyphen_values = true)]
+    keyframes: Option<String>,
+    #[arg(long, default_value = "1")]
+    keyframe_interval: String,
 }
 
-impl TryFrom<&NodeArgs> for set_node::SetNodeEvent {
-    type Error = String;
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum ConstraintKind {
+    /// Restrict the node to a line through the origin along the direction
+    Line,
+    /// Restrict the node to a plane through the origin with the direction as its normal
+    Plane,
+    /// Remove the nodes constraint
+    None,
+}
 
-    fn try_from(value: &NodeArgs) -> Result<Self, Self::Error> {
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum KinematicKind {
+    /// Circle the centre with the given radius, period and normal
+    Circle,
+    /// Move between keyframes, reaching the next every keyframe interval
+    Keyframes,
+    /// Stop following a path, and be moved by forces again
+    None,
+}
+
+impl NodeArgs {
+    fn constraint(&self, context: &ParseContext) -> Result<Option<Option<Constraint>>, String> {
+        let kind = match &self.constraint {
+            Some(kind) => kind,
+            None => return Ok(None),
+        };
+        let create_constraint = match kind {
+            ConstraintKind::Line => Constraint::line,
+            ConstraintKind::Plane => Constraint::plane,
+            ConstraintKind::None => return Ok(Some(None)),
+        };
+        let origin = node::Position::parse(self.constraint_origin.clone(), context)?;
+        let direction = match &self.constraint_direction {
+            Some(direction) => node::Force::parse(direction.clone(), context)?,
+            None => {
+                return Err("A line or plane constraint requires --constraint-direction".to_string())
+            }
+        };
+        create_constraint(origin, direction.0).map(|constraint| Some(Some(constraint)))
+    }
+
+    fn kinematic(&self, context: &ParseContext) -> Result<Option<Option<Path>>, String> {
+        let path = match &self.kinematic {
+            Some(KinematicKind::Circle) => Path::circle(
+                node::Position::parse(self.circle_centre.clone(), context)?,
+                context.scalar(&self.circle_radius, "Circle radius", Quantity::Length)?,
+                context.scalar(&self.circle_period, "Circle period", Quantity::Time)?,
+                node::Force::parse(self.circle_normal.clone(), context)?.0,
+            )?,
+            Some(KinematicKind::Keyframes) => {
+                let keyframes = match &self.keyframes {
+                    Some(keyframes) => keyframes,
+                    None => return Err("A keyframes path requires --keyframes".to_string()),
+                };
+                let positions = keyframes
+                    .trim_matches('"')
+                    .split(';')
+                    .map(|position| node::Position::parse(position.to_string(), context))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let interval =
+                    context.scalar(&self.keyframe_interval, "Keyframe interval", Quantity::Time)?;
+                Path::keyframes(positions, interval)?
+            }
+            Some(KinematicKind::None) => return Ok(Some(None)),
+            None => return Ok(None),
+        };
+        Ok(Some(Some(path)))
+    }
+}
+
+impl NodeArgs {
+    /// Creates the event to set the nodes values, parsing vectors against `context`. The position
+    /// and velocity may be relative to another node
+    pub fn to_event(&self, context: &ParseContext) -> Result<set_node::SetNodeEvent, String> {
+        let value = self;
         let id = node::Id(value.id);
         let position = match &value.position {
-            Some(position) => match node::Position::try_from(position.clone()) {
-                Ok(position) => Some(position),
-                Err(err) => return Err(err),
-            },
+            Some(position) => Some(node::Vector::parse(
+                position,
+                "Position",
+                Quantity::Length,
+                context,
+            )?),
             None => None,
         };
         let velocity = match &value.velocity {
-            Some(velocity) => match node::Force::try_from(velocity.clone()) {
-                Ok(velocity) => Some(velocity),
-                Err(err) => return Err(err),
-            },
+            Some(velocity) => Some(node::Vector::parse(
+                velocity,
+                "Force",
+                Quantity::Velocity,
+                context,
+            )?),
+            None => None,
+        };
+        let mass = match &value.mass {
+            Some(mass) => Some(context.scalar(mass, "Mass", Quantity::Mass)?),
+            None => None,
+        };
+        let radius = match &value.radius {
+            Some(radius) => Some(context.scalar(radius, "Radius", Quantity::Length)?),
             None => None,
         };
-        let mass = value.mass;
         let gravitational_constant_override = value.gravitational_constant_override;
         let dampen_rate = value.dampen_rate;
+        let drag_coefficient = value.drag_coefficient;
         let freeze = value.freeze;
+        let locked_axes = match &value.lock_axes {
+            Some(axes) => Some(Axes::try_from(axes.clone())?),
+            None => None,
+        };
+        let constraint = value.constraint(context)?;
+        let kinematic = value.kinematic(context)?;
 
-        Ok(Self {
+        Ok(set_node::SetNodeEvent {
             id,
-            position,
-            velocity,
+            position: position.map(|position| node::Position::default() + position.offset),
+            velocity: velocity.map(|velocity| node::Force::from(velocity.offset)),
+            position_relative_to: position.and_then(|position| position.relative_to),
+            velocity_relative_to: velocity.and_then(|velocity| velocity.relative_to),
             mass,
+            radius,
             gravitational_constant_override,
             dampen_rate,
+            drag_coefficient,
             freeze,
+            locked_axes,
+            constraint,
+            kinematic,
         })
     }
 }
@@ -0,0 +1,27 @@
+use node_simulator::node::{self, force::PairForceMode};
+
+#[derive(clap::Args, Debug)]
+pub struct PairForceModeArgs {
+    #[arg(ignore_case = true)]
+    pub mode: Mode,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum Mode {
+    /// Evaluate each pair once, applying equal and opposite forces. Conserves momentum. Gravitational
+    /// constant overrides are shared by the pair, so also change the force on the node they are set on
+    Symmetric,
+    /// Evaluate each node separately, using the gravitational constant override of the node
+    /// exerting the force. Matches the behaviour before pair force modes were added
+    Asymmetric,
+}
+
+impl From<&PairForceModeArgs> for node::SetPairForceModeEvent {
+    fn from(value: &PairForceModeArgs) -> Self {
+        let pair_force_mode = match value.mode {
+            Mode::Symmetric => PairForceMode::Symmetric,
+            Mode::Asymmetric => PairForceMode::Asymmetric,
+        };
+        Self { pair_force_mode }
+    }
+}
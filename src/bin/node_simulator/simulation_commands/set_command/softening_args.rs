@@ -0,0 +1,14 @@
+use node_simulator::node;
+
+#[derive(clap::Args, Debug)]
+pub struct SofteningArgs {
+    pub length: node::Scalar,
+}
+
+impl From<&SofteningArgs> for node::SetSofteningEvent {
+    fn from(value: &SofteningArgs) -> Self {
+        Self {
+            softening_length: value.length,
+        }
+    }
+}
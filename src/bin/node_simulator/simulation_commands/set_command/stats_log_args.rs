@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use node_simulator::node;
+
+#[derive(clap::Args, Debug)]
+pub struct StatsLogArgs {
+    /// CSV file to log stats to. Stops logging if not given
+    pub file: Option<String>,
+    /// Number of ticks between each logged row
+    #[arg(long, default_value = "1")]
+    pub every: u32,
+}
+
+impl From<&StatsLogArgs> for node::SetStatsLogEvent {
+    fn from(value: &StatsLogArgs) -> Self {
+        Self {
+            path: value.file.as_ref().map(PathBuf::from),
+            interval: value.every,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use node_simulator::{node, node::vector::ParseContext, simulation::units::Quantity};
+
+#[derive(clap::Args, Debug)]
+pub struct TimeStepArgs {
+    /// Time each step covers, which may be followed by a unit such as "s" or "day"
+    pub time_step: String,
+}
+
+impl TimeStepArgs {
+    /// Creates the event to set the time step, parsing it against `context`
+    pub fn to_event(&self, context: &ParseContext) -> Result<node::SetTimeStepEvent, String> {
+        Ok(node::SetTimeStepEvent {
+            time_step: context.scalar(&self.time_step, "Time step", Quantity::Time)?,
+        })
+    }
+}
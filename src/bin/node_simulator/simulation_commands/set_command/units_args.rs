@@ -0,0 +1,65 @@
+use node_simulator::{
+    node,
+    simulation::units::{Unit, UnitSystem, Units},
+};
+
+#[derive(clap::Args, Debug)]
+pub struct UnitsArgs {
+    /// Units to start from, before any given unit is changed. Keeps the current units if not given
+    #[arg(ignore_case = true)]
+    pub system: Option<System>,
+    /// Unit one length in the simulation is, ie. "m", "km" or "AU"
+    #[arg(short, long)]
+    pub length: Option<String>,
+    /// Unit one time in the simulation is, ie. "s", "h" or "day"
+    #[arg(short, long)]
+    pub time: Option<String>,
+    /// Unit one mass in the simulation is, ie. "kg", "Mearth" or "Msun"
+    #[arg(short, long)]
+    pub mass: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum System {
+    /// Values have no units, and gravity has a strength of -1, pushing nodes apart
+    Dimensionless,
+    /// Metres, seconds and kilograms, with real gravity
+    Si,
+    /// Astronomical units, days and solar masses, with real gravity
+    Astronomical,
+}
+
+impl UnitsArgs {
+    /// Creates the event to change `units`, keeping any unit that is not given. Changing a unit of
+    /// a dimensionless simulation starts from SI units
+    pub fn to_event(&self, units: UnitSystem) -> Result<node::SetUnitsEvent, String> {
+        let units = match self.system {
+            Some(System::Dimensionless) => UnitSystem::Dimensionless,
+            Some(System::Si) => UnitSystem::Physical(Units::SI),
+            Some(System::Astronomical) => UnitSystem::Physical(Units::ASTRONOMICAL),
+            None => units,
+        };
+        if self.length.is_none() && self.time.is_none() && self.mass.is_none() {
+            return Ok(node::SetUnitsEvent { units });
+        }
+        let mut units = match (&self.system, units) {
+            (Some(System::Dimensionless), _) => {
+                return Err("Units can not be given for a dimensionless simulation".to_string())
+            }
+            (_, UnitSystem::Dimensionless) => Units::SI,
+            (_, UnitSystem::Physical(units)) => units,
+        };
+        if let Some(length) = &self.length {
+            units.length = Unit::length(length)?;
+        }
+        if let Some(time) = &self.time {
+            units.time = Unit::time(time)?;
+        }
+        if let Some(mass) = &self.mass {
+            units.mass = Unit::mass(mass)?;
+        }
+        Ok(node::SetUnitsEvent {
+            units: UnitSystem::Physical(units),
+        })
+    }
+}
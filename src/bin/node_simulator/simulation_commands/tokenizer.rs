@@ -0,0 +1,189 @@
+/// How the characters of an argument were quoted, from least to most literal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Quoting {
+    /// No part of the argument was quoted or escaped
+    Unquoted,
+    /// Part of the argument was in double quotes
+    Double,
+    /// Part of the argument was in single quotes, or escaped with a backslash outside of quotes,
+    /// so should be kept exactly as it is
+    Literal,
+}
+
+/// An argument of a command, with its quotes and escapes removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Argument {
+    pub text: String,
+    pub quoting: Quoting,
+}
+
+/// Splits a command into its arguments the way a shell would. Arguments are separated by
+/// whitespace, unless it is quoted or escaped.
+///
+/// - Single quotes keep everything between them as it is
+/// - Double quotes keep everything between them, except that `\"` and `\\` are escapes
+/// - A backslash outside of quotes keeps the character after it as it is
+pub fn tokenize(command: &str) -> Result<Vec<String>, String> {
+    Ok(split(command)?
+        .into_iter()
+        .map(|argument| argument.text)
+        .collect())
+}
+
+/// Splits a command into its arguments like [`tokenize`], also giving how each was quoted
+pub fn split(command: &str) -> Result<Vec<Argument>, String> {
+    let mut arguments = vec![];
+    let mut argument = String::new();
+    // Quoted arguments are kept even if they are empty
+    let mut quoting = None;
+    let mut chars = command.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\'' => {
+                quoting = Some(Quoting::Literal);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(char) => argument.push(char),
+                        None => return Err("Missing closing \"'\"".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                quoting = quoting.max(Some(Quoting::Double));
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(char @ ('"' | '\\')) => argument.push(char),
+                            Some(char) => {
+                                argument.push('\\');
+                                argument.push(char);
+                            }
+                            None => return Err("Missing closing '\"'".to_string()),
+                        },
+                        Some(char) => argument.push(char),
+                        None => return Err("Missing closing '\"'".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(char) => {
+                    quoting = Some(Quoting::Literal);
+                    argument.push(char);
+                }
+                None => return Err("Nothing to escape after '\\'".to_string()),
+            },
+            char if char.is_whitespace() => {
+                if let Some(quoting) = quoting.take() {
+                    arguments.push(Argument {
+                        text: std::mem::take(&mut argument),
+                        quoting,
+                    });
+                }
+            }
+            char => {
+                quoting = quoting.max(Some(Quoting::Unquoted));
+                argument.push(char);
+            }
+        }
+    }
+    if let Some(quoting) = quoting {
+        arguments.push(Argument {
+            text: argument,
+            quoting,
+        });
+    }
+    Ok(arguments)
+}
+
+/// The part of `command` before a `//` comment. Slashes that are quoted or escaped, following the
+/// same rules as [`tokenize`], do not start a comment.
+pub fn strip_comment(command: &str) -> &str {
+    let mut quote = None;
+    let mut chars = command.char_indices();
+    while let Some((index, char)) = chars.next() {
+        match (quote, char) {
+            (None, '/') if command[index + 1..].starts_with('/') => return &command[..index],
+            (None, '\\') | (Some('"'), '\\') => {
+                chars.next();
+            }
+            (None, '\'' | '"') => quote = Some(char),
+            (Some(open), char) if char == open => quote = None,
+            _ => (),
+        }
+    }
+    command
+}
+
+/// Quotes an argument if needed, so that [`tokenize`] gives it back as it was
+pub fn quote(argument: &str) -> String {
+    let needs_quotes = argument.is_empty()
+        || argument
+            .chars()
+            .any(|char| char.is_whitespace() || matches!(char, '\'' | '"' | '\\'));
+    match needs_quotes {
+        true => format!("'{}'", argument.replace('\'', r"'\''")),
+        false => argument.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod a_tokenizer {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("add node --id 1", &["add", "node", "--id", "1"])]
+    #[case("  step   10 ", &["step", "10"])]
+    #[case("script /Users/Me/My\\ Scripts/Planets.txt", &["script", "/Users/Me/My Scripts/Planets.txt"])]
+    #[case("set node --position \"1, 2, 3\"", &["set", "node", "--position", "1, 2, 3"])]
+    #[case("script 'a \"b\" c'", &["script", "a \"b\" c"])]
+    #[case(r#"script "a \"b\" \\ \c""#, &["script", r#"a "b" \ \c"#])]
+    #[case("script ''", &["script", ""])]
+    #[case("--name=a' 'b", &["--name=a b"])]
+    fn splits_arguments_like_a_shell(#[case] command: &str, #[case] expected: &[&str]) {
+        assert_eq!(expected, tokenize(command).unwrap());
+    }
+
+    #[rstest]
+    #[case("step", Quoting::Unquoted)]
+    #[case("\"step\"", Quoting::Double)]
+    #[case("--position=\"1, 2\"", Quoting::Double)]
+    #[case("'step'", Quoting::Literal)]
+    #[case("\\$step", Quoting::Literal)]
+    #[case("\"a\"'b'", Quoting::Literal)]
+    fn gives_how_arguments_were_quoted(#[case] command: &str, #[case] expected: Quoting) {
+        assert_eq!(expected, split(command).unwrap()[0].quoting);
+    }
+
+    #[rstest]
+    #[case("script 'a")]
+    #[case("script \"a")]
+    #[case("script a\\")]
+    fn gives_an_error_if_a_quote_is_not_closed(#[case] command: &str) {
+        assert!(tokenize(command).is_err());
+    }
+
+    #[rstest]
+    #[case("step // comment", "step ")]
+    #[case("print \"http://a\" // b", "print \"http://a\" ")]
+    #[case("print 'a // \"b' // c", "print 'a // \"b' ")]
+    #[case("print \"a \\\" // b\"", "print \"a \\\" // b\"")]
+    #[case("print a\\//b", "print a\\//b")]
+    #[case("print \"a", "print \"a")]
+    fn strips_comments_outside_of_quotes(#[case] command: &str, #[case] expected: &str) {
+        assert_eq!(expected, strip_comment(command));
+    }
+
+    #[rstest]
+    #[case("plain")]
+    #[case("My Scripts/a.txt")]
+    #[case("it's")]
+    #[case("\"1, 2\" \\")]
+    #[case("")]
+    fn quotes_arguments_so_they_tokenize_back(#[case] argument: &str) {
+        assert_eq!(vec![argument], tokenize(&quote(argument)).unwrap());
+    }
+}
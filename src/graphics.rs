@@ -11,7 +11,7 @@ use std::time::{self, Duration};
 #[cfg(feature = "wgpu")]
 mod camera;
 #[cfg(feature = "wgpu")]
-mod instances;
+pub mod instances;
 #[cfg(feature = "wgpu")]
 mod models;
 pub mod scene_event;
@@ -21,6 +21,9 @@ mod texture;
 #[cfg(feature = "wgpu")]
 mod vertex;
 
+#[cfg(feature = "wgpu")]
+pub use models::model::ModelId;
+
 pub struct GraphicsInterface {
     pub simulation_rx: mpsc::Receiver<Arc<Mutex<simulation::Simulation>>>,
     pub context: sdl2::Sdl,
@@ -1,5 +1,13 @@
+use cgmath::{Angle, InnerSpace};
 use sdl2::keyboard::Keycode;
 
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Projection {
+    Perspective,
+    /// Parallel projection, used to view 2D simulations top-down
+    Orthographic,
+}
+
 pub struct Camera {
     pub eye: cgmath::Point3<f32>,
     pub target: cgmath::Point3<f32>,
@@ -8,6 +16,7 @@ pub struct Camera {
     pub fovy: f32,
     pub znear: f32,
     pub zfar: f32,
+    pub projection: Projection,
 }
 
 #[rustfmt::skip]
@@ -21,10 +30,46 @@ pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
 impl Camera {
     pub fn build_view_projection_matrix(&self) -> cgmath::Matrix4<f32> {
         let view = cgmath::Matrix4::look_at_rh(self.eye, self.target, self.up);
-        let proj = cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar);
+        let proj = match self.projection {
+            Projection::Perspective => {
+                cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar)
+            }
+            Projection::Orthographic => {
+                // Show the same area at the target as the perspective projection would, so moving
+                // the camera forward and backward still zooms
+                let half_height =
+                    (self.target - self.eye).magnitude() * cgmath::Deg(self.fovy / 2.0).tan();
+                let half_width = half_height * self.aspect;
+                cgmath::ortho(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.znear,
+                    self.zfar,
+                )
+            }
+        };
 
         return OPENGL_TO_WGPU_MATRIX * proj * view;
     }
+
+    /// Looks straight down at the XZ plane with an orthographic projection, keeping the distance
+    /// to the target
+    pub fn view_top_down(&mut self) {
+        let distance = (self.target - self.eye).magnitude();
+        self.eye = self.target + cgmath::Vector3::unit_y() * distance;
+        self.up = -cgmath::Vector3::unit_z();
+        self.projection = Projection::Orthographic;
+    }
+
+    /// Returns to the default perspective view, keeping the distance to the target
+    pub fn view_in_perspective(&mut self) {
+        let distance = (self.target - self.eye).magnitude();
+        self.eye = self.target + cgmath::Vector3::new(0.0, 1.0, 2.0).normalize() * distance;
+        self.up = cgmath::Vector3::unit_y();
+        self.projection = Projection::Perspective;
+    }
 }
 
 #[repr(C)]
@@ -117,7 +162,6 @@ impl CameraController {
     }
 
     pub fn update_camera(&self, camera: &mut Camera) {
-        use cgmath::InnerSpace;
         let forward = camera.target - camera.eye;
         let forward_norm = forward.normalize();
         let forward_mag = forward.magnitude();
@@ -133,6 +177,18 @@ impl CameraController {
 
         let right = forward_norm.cross(camera.up);
 
+        // Orbiting would tip a top-down camera off of the plane, so pan across it instead
+        if camera.projection == Projection::Orthographic {
+            let pan = match (self.is_right_pressed, self.is_left_pressed) {
+                (true, false) => right * self.speed,
+                (false, true) => -right * self.speed,
+                _ => return,
+            };
+            camera.eye += pan;
+            camera.target += pan;
+            return;
+        }
+
         // Redo radius calc in case the fowrard/backward is pressed.
         let forward = camera.target - camera.eye;
         let forward_mag = forward.magnitude();
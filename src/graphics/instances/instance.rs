@@ -1,9 +1,56 @@
-use cgmath::EuclideanSpace;
+use cgmath::{EuclideanSpace, Rotation3, Zero};
 
-#[derive(Copy, Clone, PartialEq)]
+use crate::{
+    node::{Node, Position},
+    simulation::{emitter::Emitter, sink::Sink},
+};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Instance {
     pub position: cgmath::Point3<f32>,
     pub rotation: cgmath::Quaternion<f32>,
+    /// Half the width of the drawn model, which is 1 for a node
+    pub scale: f32,
+}
+
+/// Rendering is always done in f32, whatever precision the simulation uses
+fn render_position(position: &Position) -> cgmath::Point3<f32> {
+    position
+        .0
+        .cast()
+        .expect("Positions can be represented as f32")
+}
+
+impl From<&Node> for Instance {
+    fn from(value: &Node) -> Self {
+        Self {
+            position: render_position(&value.position),
+            rotation: cgmath::Quaternion::zero(),
+            scale: 1.0,
+        }
+    }
+}
+
+impl From<&Emitter> for Instance {
+    /// Emitters are drawn smaller than nodes and turned to face a corner, so they can be told apart
+    fn from(value: &Emitter) -> Self {
+        Self {
+            position: render_position(&value.position),
+            rotation: cgmath::Quaternion::from_angle_y(cgmath::Deg(45.0)),
+            scale: 0.5,
+        }
+    }
+}
+
+impl From<&Sink> for Instance {
+    /// Sinks are drawn around the region they absorb nodes from
+    fn from(value: &Sink) -> Self {
+        Self {
+            position: render_position(&value.position),
+            rotation: cgmath::Quaternion::zero(),
+            scale: cgmath::num_traits::cast(value.radius).expect("Radii can be represented as f32"),
+        }
+    }
 }
 
 #[repr(C)]
@@ -16,7 +63,8 @@ impl Instance {
     pub fn to_raw(&self) -> InstanceRaw {
         InstanceRaw {
             model: (cgmath::Matrix4::from_translation(EuclideanSpace::to_vec(self.position))
-                * cgmath::Matrix4::from(self.rotation))
+                * cgmath::Matrix4::from(self.rotation)
+                * cgmath::Matrix4::from_scale(self.scale))
             .into(),
         }
     }
@@ -1,5 +1,6 @@
 use super::instance;
 use crate::graphics::models::model;
+use crate::simulation::Simulation;
 use std::ops::Range;
 
 pub struct InstanceCollection {
@@ -18,6 +19,39 @@ impl InstanceCollection {
         InstanceCollection { model, instances }
     }
 
+    /// Collections of the nodes, emitters and sinks of `simulation`, in that order, each drawn
+    /// with `model`
+    pub fn from_simulation(
+        simulation: &Simulation,
+        model: model::ModelId,
+    ) -> Vec<InstanceCollection> {
+        let collection =
+            |instances: Vec<instance::Instance>| InstanceCollection { model, instances };
+        vec![
+            collection(
+                simulation
+                    .nodes
+                    .iter()
+                    .map(|node| instance::Instance::from(&node))
+                    .collect(),
+            ),
+            collection(
+                simulation
+                    .emitters
+                    .iter()
+                    .map(instance::Instance::from)
+                    .collect(),
+            ),
+            collection(
+                simulation
+                    .sinks
+                    .iter()
+                    .map(instance::Instance::from)
+                    .collect(),
+            ),
+        ]
+    }
+
     pub fn add(&mut self, instance: instance::Instance) {
         self.instances.push(instance);
     }
@@ -58,3 +92,60 @@ impl InstanceCollection {
         }
     }
 }
+
+#[cfg(test)]
+mod an_instance_collection {
+    use super::*;
+    use crate::{
+        node,
+        simulation::{
+            emitter::{Emitter, VelocityDistribution},
+            sink::Sink,
+        },
+    };
+
+    #[test]
+    fn includes_the_emitters_and_sinks_of_a_simulation() {
+        let mut simulation = Simulation::new();
+        simulation.add_node(node::Node::new(node::Id(1), node::Position::default()));
+        let emitter = Emitter::new(
+            1,
+            node::Position::from((2.0, 0.0, 0.0)),
+            1.0,
+            node::Force::from((0.0, 0.0, 0.0)),
+            VelocityDistribution::Fixed,
+            1.0,
+            1,
+        )
+        .unwrap();
+        simulation.add_emitter(emitter).unwrap();
+        let sink = Sink::new(1, node::Position::from((-3.0, 0.0, 0.0)), 2.0).unwrap();
+        simulation.add_sink(sink).unwrap();
+
+        let collections = InstanceCollection::from_simulation(&simulation, model::ModelId(0));
+        let instances: Vec<Vec<&instance::Instance>> = collections
+            .iter()
+            .map(|collection| collection.iter().collect())
+            .collect();
+
+        assert_eq!(3, instances.len());
+        assert_eq!(cgmath::Point3::new(0.0, 0.0, 0.0), instances[0][0].position);
+        assert_eq!(cgmath::Point3::new(2.0, 0.0, 0.0), instances[1][0].position);
+        assert_eq!(
+            cgmath::Point3::new(-3.0, 0.0, 0.0),
+            instances[2][0].position
+        );
+        assert_eq!(2.0, instances[2][0].scale);
+
+        let render_data = InstanceCollection::get_instance_render_data(&collections);
+        assert_eq!(3, render_data.data.len());
+        assert_eq!(
+            vec![0..1, 1..2, 2..3],
+            render_data
+                .indexes
+                .iter()
+                .map(|index| index.1.clone())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -9,8 +9,6 @@ use graphics::vertex::Vertex;
 use sdl2::keyboard::Keycode;
 use wgpu::util::DeviceExt;
 
-use cgmath::prelude::*;
-
 pub struct State {
     _window: sdl2::video::Window,
     surface: wgpu::Surface,
@@ -30,6 +28,8 @@ pub struct State {
     models: model_collection::ModelCollection,
     node_model_id: model::ModelId,
     depth_texture: texture::Texture,
+    /// Dimensionality of the last simulation rendered, used to switch to a top-down camera in 2D
+    dimensionality: simulation::dimensionality::Dimensionality,
 }
 
 impl super::Scene for State {
@@ -135,6 +135,7 @@ impl super::Scene for State {
             fovy: 45.0,
             znear: 0.1,
             zfar: 100.0,
+            projection: camera::Projection::Perspective,
         };
 
         let camera_controller = camera::CameraController::new(0.2);
@@ -260,6 +261,7 @@ impl super::Scene for State {
             models,
             depth_texture,
             node_model_id,
+            dimensionality: simulation::dimensionality::Dimensionality::ThreeD,
         }
     }
 
@@ -320,22 +322,22 @@ impl super::Scene for State {
                 label: Some("Render Encoder"),
             });
 
-        let mut node_instance_collection =
-            instance_collection::InstanceCollection::new(self.node_model_id);
-
+        let mut instance_collections = vec![];
         if let Some(simulation) = simulation {
-            for node in simulation.nodes.iter() {
-                node_instance_collection.add(instance::Instance {
-                    position: node.position.into(),
-                    rotation: cgmath::Quaternion::zero(),
-                })
+            if simulation.dimensionality() != self.dimensionality {
+                self.set_dimensionality(simulation.dimensionality());
             }
+            // Instances are rebuilt every frame, so nodes removed by merging or sinks stop being
+            // drawn straight away
+            instance_collections = instance_collection::InstanceCollection::from_simulation(
+                simulation,
+                self.node_model_id,
+            );
         }
 
-        let instance_data =
-            instance_collection::InstanceCollection::get_instance_render_data(&vec![
-                node_instance_collection,
-            ]);
+        let instance_data = instance_collection::InstanceCollection::get_instance_render_data(
+            &instance_collections,
+        );
         let instance_buffer = self
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -371,27 +373,25 @@ impl super::Scene for State {
             render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
 
             use model::DrawModel;
-            let model = self.models.find(self.node_model_id);
-            let model = model.unwrap();
-            let range = &instance_data
-                .indexes
-                .iter()
-                .find(|value| value.0 == self.node_model_id)
-                .unwrap()
-                .1;
-            let mesh = &model.meshes[0];
-
-            let material =
-                if self.use_default_material == false && model.materials.is_empty() == false {
+            for (model_id, range) in &instance_data.indexes {
+                let model = self.models.find(*model_id).unwrap();
+                let mesh = &model.meshes[0];
+
+                let material = if !self.use_default_material && !model.materials.is_empty() {
                     &model.materials[0]
                 } else {
-                    &self
-                        .default_material
+                    self.default_material
                         .as_ref()
                         .unwrap_or(&self.fallback_material)
                 };
 
-            render_pass.draw_mesh_instanced(mesh, material, range.clone(), &self.camera_bind_group);
+                render_pass.draw_mesh_instanced(
+                    mesh,
+                    material,
+                    range.clone(),
+                    &self.camera_bind_group,
+                );
+            }
         }
 
         // submit will accept anything that implements IntoIter
@@ -405,3 +405,13 @@ impl super::Scene for State {
         self
     }
 }
+
+impl State {
+    fn set_dimensionality(&mut self, dimensionality: simulation::dimensionality::Dimensionality) {
+        self.dimensionality = dimensionality;
+        match dimensionality {
+            simulation::dimensionality::Dimensionality::TwoD => self.camera.view_top_down(),
+            simulation::dimensionality::Dimensionality::ThreeD => self.camera.view_in_perspective(),
+        }
+    }
+}
@@ -1,41 +1,91 @@
+pub mod constraint;
+pub mod drag;
 pub mod event;
 pub mod force;
 pub mod id;
+pub mod kinematic;
+pub mod orbit;
 pub mod position;
+pub mod vector;
 
 pub use event::{
-    add_node::AddNodeEvent, remove_node::RemoveNodeEvent, set_target_tps::SetTargetTpsEvent, Event,
+    add_emitter::AddEmitterEvent, add_node::AddNodeEvent, add_orbiter::AddOrbiterEvent,
+    add_sink::AddSinkEvent, assert::AssertEvent, cancel_scheduled::CancelScheduledEvent,
+    remove_emitter::RemoveEmitterEvent, remove_node::RemoveNodeEvent, remove_sink::RemoveSinkEvent,
+    schedule::ScheduleEvent, set_adaptive_step::SetAdaptiveStepEvent,
+    set_collision_policy::SetCollisionPolicyEvent, set_dimensionality::SetDimensionalityEvent,
+    set_drag::SetDragEvent, set_pair_force_mode::SetPairForceModeEvent,
+    set_softening::SetSofteningEvent, set_stats_log::SetStatsLogEvent,
+    set_target_tps::SetTargetTpsEvent, set_time_step::SetTimeStepEvent, set_units::SetUnitsEvent,
+    Event,
 };
+#[cfg(feature = "scripting")]
+pub use event::{add_hook::AddHookEvent, remove_hook::RemoveHookEvent};
 pub use force::Force;
 pub use id::Id;
 pub use position::Position;
+pub use vector::Vector;
+
+use cgmath::InnerSpace;
+
+/// Floating point type used by the simulation. Enable the `f64` feature for double precision, which
+/// keeps large scenes from jittering as precision is lost
+#[cfg(not(feature = "f64"))]
+pub type Scalar = f32;
+/// Floating point type used by the simulation. Enable the `f64` feature for double precision, which
+/// keeps large scenes from jittering as precision is lost
+#[cfg(feature = "f64")]
+pub type Scalar = f64;
+
+/// Mathematical constants for [`Scalar`]
+#[cfg(not(feature = "f64"))]
+pub use std::f32::consts;
+/// Mathematical constants for [`Scalar`]
+#[cfg(feature = "f64")]
+pub use std::f64::consts;
 
 #[derive(PartialEq, Debug, Clone, Copy)]
 pub struct Node {
     pub id: Id,
     pub position: Position,
     pub velocity: force::Force,
-    pub mass: f32,
-    pub gravitational_constant_override: Option<f32>,
+    pub mass: Scalar,
+    /// Size of the node when colliding with other nodes. Nodes with no radius never collide
+    pub radius: Scalar,
+    /// Gravitational constant used for this node instead of the simulation's. In the symmetric pair
+    /// force mode it is shared with every node it interacts with, so it scales the force on this
+    /// node as well as the force it exerts. In the asymmetric mode it only scales the force it exerts.
+    pub gravitational_constant_override: Option<Scalar>,
     /// Rate at which to dampen a nodes velocity. 0 is no dampening, 1 is instant dampening.
-    pub dampen_rate: f32,
+    /// Only used by the legacy drag model.
+    pub dampen_rate: Scalar,
+    /// Scales the drag on a node from the simulation medium. Used by the linear and quadratic drag
+    /// models.
+    pub drag_coefficient: Scalar,
     /// Stops the node from moving when undergoing a force. This node will still exert force on other
-    /// nodes - this can be disabled via the gravitational_constant_override
+    /// nodes - this can be disabled via the gravitational_constant_override, which in the symmetric
+    /// pair force mode also stops other nodes pulling on it
     pub freeze: bool,
+    /// Stops the node from moving along individual axes
+    pub locked_axes: constraint::Axes,
+    /// Keeps the node on a line or plane
+    pub constraint: Option<constraint::Constraint>,
 }
 
 impl Node {
-    const MIN_VELOCITY: f32 = 0.0001;
-
     pub fn new(id: Id, position: Position) -> Self {
         Node {
             id,
             position,
-            velocity: force::Force(cgmath::Vector3::<f32>::new(0.0, 0.0, 0.0)),
+            velocity: force::Force(cgmath::Vector3::<Scalar>::new(0.0, 0.0, 0.0)),
             mass: 1.0,
+            radius: 0.0,
             gravitational_constant_override: None,
             dampen_rate: 0.1,
+            drag_coefficient: 0.1,
             freeze: false,
+            locked_axes: constraint::Axes::none(),
+            constraint: None,
         }
     }
 
@@ -44,21 +94,53 @@ impl Node {
             true => self.position,
             false => self.position + self.velocity,
         };
-        // Dampen
-        self.velocity = self.velocity * (1.0 - self.dampen_rate);
-        let velocity_magnitude = self.velocity.magnitude();
-        if 0.0 < velocity_magnitude && velocity_magnitude < Self::MIN_VELOCITY {
-            self.velocity = Force::zero();
-        }
     }
 
     pub fn step<F>(&mut self, mut node_force_function: F) -> ()
     where
         F: FnMut(&mut Self) -> Force,
     {
+        let previous_position = self.position;
         let internal_force = node_force_function(self);
         self.velocity += internal_force * (1.0 / self.mass);
         self.update_position();
+        // Dampen
+        self.velocity = drag::Drag::default().apply(self, 1.0);
+        self.apply_constraints(&previous_position);
+    }
+
+    /// Whether the node is close enough to `other` to collide with it
+    pub fn overlaps(&self, other: &Node) -> bool {
+        let distance = Position::displacement(&self.position, &other.position).magnitude();
+        distance < self.radius + other.radius
+    }
+
+    /// Combines the node with `other` into a single node, with their summed mass and momentum, at
+    /// their centre of mass. The merged node has the volume of both nodes. The more massive node
+    /// survives, keeping its id and other properties.
+    pub fn merge(&self, other: &Node) -> Node {
+        let (survivor, absorbed) = match other.mass > self.mass {
+            true => (other, self),
+            false => (self, other),
+        };
+        let mass = survivor.mass + absorbed.mass;
+        let displacement = Position::displacement(&survivor.position, &absorbed.position);
+        Node {
+            position: survivor.position + Force(displacement * (absorbed.mass / mass)),
+            velocity: survivor.velocity * (survivor.mass / mass)
+                + absorbed.velocity * (absorbed.mass / mass),
+            mass,
+            radius: (survivor.radius.powi(3) + absorbed.radius.powi(3)).cbrt(),
+            ..*survivor
+        }
+    }
+
+    fn apply_constraints(&mut self, previous_position: &Position) {
+        self.locked_axes
+            .apply(previous_position, &mut self.position, &mut self.velocity);
+        if let Some(constraint) = self.constraint {
+            constraint.apply(&mut self.position, &mut self.velocity);
+        }
     }
 }
 
@@ -66,6 +148,37 @@ impl Node {
 mod a_node {
     use super::*;
 
+    #[test]
+    fn conserves_mass_and_momentum_when_merged() {
+        let mut light = Node::new(Id(1), Position::from((0.0, 0.0, 0.0)));
+        light.velocity = Force::from((3.0, 0.0, 0.0));
+        light.radius = 1.0;
+        let mut heavy = Node::new(Id(2), Position::from((4.0, 0.0, 0.0)));
+        heavy.mass = 3.0;
+        heavy.velocity = Force::from((-1.0, 1.0, 0.0));
+        heavy.radius = 1.0;
+
+        let merged = light.merge(&heavy);
+
+        assert_eq!(Id(2), merged.id);
+        assert_eq!(4.0, merged.mass);
+        assert_eq!(Position::from((3.0, 0.0, 0.0)), merged.position);
+        assert_eq!(Force::from((0.0, 0.75, 0.0)), merged.velocity);
+        assert!((merged.radius - (2.0 as Scalar).cbrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn only_overlaps_nodes_within_the_combined_radius() {
+        let mut a = Node::new(Id(1), Position::from((0.0, 0.0, 0.0)));
+        let mut b = Node::new(Id(2), Position::from((1.0, 0.0, 0.0)));
+        assert!(!a.overlaps(&b));
+
+        a.radius = 0.5;
+        b.radius = 0.6;
+
+        assert!(a.overlaps(&b));
+    }
+
     #[test]
     fn is_created_from_id_and_position() {
         let node = Node::new(
@@ -127,6 +240,37 @@ mod a_node {
         assert_eq!(expected_velocity * 0.5, node.velocity);
     }
 
+    #[test]
+    fn does_not_move_along_locked_axes() {
+        let mut node = Node::new(Id(1), Position::from((1.0, 2.0, 3.0)));
+        node.locked_axes = constraint::Axes {
+            x: false,
+            y: true,
+            z: false,
+        };
+
+        node.step(|_| Force::from((1.0, 1.0, 1.0)));
+
+        assert_eq!(2.0, node.position.0.y);
+        assert_eq!(0.0, node.velocity.0.y);
+        assert_ne!(1.0, node.position.0.x);
+        assert_ne!(3.0, node.position.0.z);
+    }
+
+    #[test]
+    fn stays_on_its_constraint() {
+        let mut node = Node::new(Id(1), Position::from((0.0, 0.0, 0.0)));
+        node.constraint = Some(
+            constraint::Constraint::line(Position::default(), (1.0, 1.0, 0.0).into()).unwrap(),
+        );
+
+        node.step(|_| Force::from((1.0, 0.0, 0.0)));
+
+        assert!(node.position.0.x > 0.0);
+        assert!((node.position.0.x - node.position.0.y).abs() < 1e-6);
+        assert_eq!(0.0, node.position.0.z);
+    }
+
     #[test]
     fn does_not_move_if_frozen() {
         let mut node = Node::new(Id(1), Position::default());
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use cgmath::InnerSpace;
+
+use super::{Force, Position, Scalar};
+
+/// Set of axes a node is locked to, and cannot move along
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct Axes {
+    pub x: bool,
+    pub y: bool,
+    pub z: bool,
+}
+
+impl Axes {
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !(self.x || self.y || self.z)
+    }
+
+    /// Keeps the locked components of `position` at their `previous` values, and removes the
+    /// locked components of `velocity`
+    pub fn apply(&self, previous: &Position, position: &mut Position, velocity: &mut Force) {
+        let locks = [self.x, self.y, self.z];
+        for (axis, locked) in locks.into_iter().enumerate() {
+            if locked {
+                position.0[axis] = previous.0[axis];
+                velocity.0[axis] = 0.0;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Axes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "none");
+        }
+        let axes = [(self.x, 'x'), (self.y, 'y'), (self.z, 'z')];
+        let axes: String = axes
+            .into_iter()
+            .filter(|(locked, _)| *locked)
+            .map(|(_, axis)| axis)
+            .collect();
+        write!(f, "{axes}")
+    }
+}
+
+impl TryFrom<String> for Axes {
+    type Error = String;
+
+    /// Parses a set of axes from their names, ie. "xz". "none" is the empty set
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.trim_matches('"').trim().to_lowercase();
+        let mut axes = Axes::none();
+        if value == "none" {
+            return Ok(axes);
+        }
+        for axis in value.chars() {
+            match axis {
+                'x' => axes.x = true,
+                'y' => axes.y = true,
+                'z' => axes.z = true,
+                ',' | ' ' => {}
+                _ => return Err(format!("Unknown axis {axis} - axes must be x, y or z")),
+            }
+        }
+        Ok(axes)
+    }
+}
+
+/// Restricts a node to moving along a line or within a plane
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Constraint {
+    Line {
+        origin: Position,
+        direction: cgmath::Vector3<Scalar>,
+    },
+    Plane {
+        origin: Position,
+        normal: cgmath::Vector3<Scalar>,
+    },
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Constraint::Line { origin, direction } => {
+                write!(f, "line through ({}) along ({})", origin, Force(*direction))
+            }
+            Constraint::Plane { origin, normal } => write!(
+                f,
+                "plane through ({}) with normal ({})",
+                origin,
+                Force(*normal)
+            ),
+        }
+    }
+}
+
+impl Constraint {
+    /// Creates a line constraint. Fails if the direction has no length
+    pub fn line(origin: Position, direction: cgmath::Vector3<Scalar>) -> Result<Self, String> {
+        match direction.magnitude() > 0.0 {
+            true => Ok(Constraint::Line {
+                origin,
+                direction: direction.normalize(),
+            }),
+            false => Err("Line direction must not be zero".to_string()),
+        }
+    }
+
+    /// Creates a plane constraint. Fails if the normal has no length
+    pub fn plane(origin: Position, normal: cgmath::Vector3<Scalar>) -> Result<Self, String> {
+        match normal.magnitude() > 0.0 {
+            true => Ok(Constraint::Plane {
+                origin,
+                normal: normal.normalize(),
+            }),
+            false => Err("Plane normal must not be zero".to_string()),
+        }
+    }
+
+    /// Projects `position` onto the constraint, and removes any component of `velocity` that would
+    /// move the node off of it
+    pub fn apply(&self, position: &mut Position, velocity: &mut Force) {
+        match self {
+            Constraint::Line { origin, direction } => {
+                let offset = position.0 - origin.0;
+                *position = Position(origin.0 + direction * offset.dot(*direction));
+                *velocity = Force(direction * velocity.0.dot(*direction));
+            }
+            Constraint::Plane { origin, normal } => {
+                let offset = position.0 - origin.0;
+                *position = Position(position.0 - normal * offset.dot(*normal));
+                *velocity = Force(velocity.0 - normal * velocity.0.dot(*normal));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod an_axes {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("x", Axes { x: true, y: false, z: false })]
+    #[case("xz", Axes { x: true, y: false, z: true })]
+    #[case("\"y,z\"", Axes { x: false, y: true, z: true })]
+    #[case("none", Axes::none())]
+    fn can_be_created_from_a_valid_string(#[case] value: String, #[case] expected_axes: Axes) {
+        assert_eq!(Ok(expected_axes), Axes::try_from(value));
+    }
+
+    #[test]
+    fn cant_be_created_from_an_invalid_string() {
+        assert_eq!(
+            Err("Unknown axis w - axes must be x, y or z".to_string()),
+            Axes::try_from("xw".to_string())
+        );
+    }
+
+    #[rstest]
+    #[case(Axes { x: true, y: false, z: true }, "xz")]
+    #[case(Axes::none(), "none")]
+    fn can_be_displayed(#[case] axes: Axes, #[case] expected_display_string: String) {
+        assert_eq!(expected_display_string, axes.to_string());
+    }
+
+    #[test]
+    fn keep_locked_components_still() {
+        let axes = Axes {
+            x: false,
+            y: true,
+            z: false,
+        };
+        let previous = Position::from((0.0, 1.0, 0.0));
+        let mut position = Position::from((1.0, 2.0, 3.0));
+        let mut velocity = Force::from((1.0, 1.0, 1.0));
+
+        axes.apply(&previous, &mut position, &mut velocity);
+
+        assert_eq!(Position::from((1.0, 1.0, 3.0)), position);
+        assert_eq!(Force::from((1.0, 0.0, 1.0)), velocity);
+    }
+}
+
+#[cfg(test)]
+mod a_constraint {
+    use super::*;
+
+    #[test]
+    fn projects_onto_a_line() {
+        let constraint =
+            Constraint::line(Position::from((0.0, 1.0, 0.0)), (2.0, 0.0, 0.0).into()).unwrap();
+        let mut position = Position::from((3.0, 5.0, -2.0));
+        let mut velocity = Force::from((1.0, 1.0, 1.0));
+
+        constraint.apply(&mut position, &mut velocity);
+
+        assert_eq!(Position::from((3.0, 1.0, 0.0)), position);
+        assert_eq!(Force::from((1.0, 0.0, 0.0)), velocity);
+    }
+
+    #[test]
+    fn projects_onto_a_plane() {
+        let constraint =
+            Constraint::plane(Position::from((0.0, 1.0, 0.0)), (0.0, 3.0, 0.0).into()).unwrap();
+        let mut position = Position::from((3.0, 5.0, -2.0));
+        let mut velocity = Force::from((1.0, 1.0, 1.0));
+
+        constraint.apply(&mut position, &mut velocity);
+
+        assert_eq!(Position::from((3.0, 1.0, -2.0)), position);
+        assert_eq!(Force::from((1.0, 0.0, 1.0)), velocity);
+    }
+
+    #[test]
+    fn cant_be_created_without_a_direction() {
+        let origin = Position::default();
+        assert!(Constraint::line(origin, (0.0, 0.0, 0.0).into()).is_err());
+        assert!(Constraint::plane(origin, (0.0, 0.0, 0.0).into()).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt;
+
+use super::{Force, Node, Scalar};
+
+/// Model used to slow nodes moving through the simulation medium
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum DragModel {
+    /// Multiplies velocity by `1 - dampen_rate` every tick, regardless of the time step.
+    /// Kept for compatibility with existing scenes - prefer a physical model.
+    #[default]
+    Legacy,
+    /// Stokes drag, proportional to velocity
+    Linear,
+    /// Drag proportional to the square of velocity
+    Quadratic,
+}
+
+impl fmt::Display for DragModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DragModel::Legacy => write!(f, "legacy"),
+            DragModel::Linear => write!(f, "linear"),
+            DragModel::Quadratic => write!(f, "quadratic"),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Drag {
+    pub model: DragModel,
+    /// Density of the medium nodes move through. Scales the drag on every node
+    pub medium_density: Scalar,
+}
+
+impl Default for Drag {
+    fn default() -> Self {
+        Drag {
+            model: DragModel::default(),
+            medium_density: 1.0,
+        }
+    }
+}
+
+impl fmt::Display for Drag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "model: {}, medium density: {}",
+            self.model, self.medium_density
+        )
+    }
+}
+
+impl Drag {
+    const MIN_VELOCITY: Scalar = 0.0001;
+
+    /// Returns the velocity of the node after undergoing drag for `dt`.
+    ///
+    /// The physical models are solved exactly over the time step, so the result does not depend on
+    /// how a period of time is split into steps.
+    pub fn apply(&self, node: &Node, dt: Scalar) -> Force {
+        self.apply_to(
+            node.velocity,
+            node.mass,
+            node.dampen_rate,
+            node.drag_coefficient,
+            dt,
+        )
+    }
+
+    /// Returns `velocity` after undergoing drag for `dt`, for a node with the given mass, dampen rate
+    /// and drag coefficient. Lets callers storing these in separate arrays apply drag without
+    /// building nodes.
+    pub fn apply_to(
+        &self,
+        velocity: Force,
+        mass: Scalar,
+        dampen_rate: Scalar,
+        drag_coefficient: Scalar,
+        dt: Scalar,
+    ) -> Force {
+        match self.model {
+            // Nodes that are not dampened keep their velocity however slow it is, as slow
+            // velocities are common in real world units
+            DragModel::Legacy if dampen_rate == 0.0 => velocity,
+            DragModel::Legacy => {
+                let velocity = velocity * (1.0 - dampen_rate);
+                let velocity_magnitude = velocity.magnitude();
+                match 0.0 < velocity_magnitude && velocity_magnitude < Self::MIN_VELOCITY {
+                    true => Force::zero(),
+                    false => velocity,
+                }
+            }
+            DragModel::Linear => {
+                // dv/dt = -k * v
+                let k = self.medium_density * drag_coefficient / mass;
+                velocity * (-k * dt).exp()
+            }
+            DragModel::Quadratic => {
+                // dv/dt = -k * |v| * v
+                let k = 0.5 * self.medium_density * drag_coefficient / mass;
+                velocity * (1.0 / (1.0 + k * velocity.magnitude() * dt))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod a_drag {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::node::{Id, Position};
+
+    fn moving_node() -> Node {
+        let mut node = Node::new(Id(1), Position::default());
+        node.velocity = Force::from((2.0, 0.0, 0.0));
+        node.dampen_rate = 0.5;
+        node.drag_coefficient = 0.5;
+        node
+    }
+
+    #[test]
+    fn legacy_model_dampens_by_dampen_rate_regardless_of_time_step() {
+        let drag = Drag {
+            model: DragModel::Legacy,
+            medium_density: 1.0,
+        };
+        let node = moving_node();
+
+        assert_eq!(Force::from((1.0, 0.0, 0.0)), drag.apply(&node, 1.0));
+        assert_eq!(Force::from((1.0, 0.0, 0.0)), drag.apply(&node, 0.1));
+    }
+
+    #[test]
+    fn legacy_model_stops_slow_nodes_that_are_dampened() {
+        let drag = Drag {
+            model: DragModel::Legacy,
+            medium_density: 1.0,
+        };
+        let mut node = moving_node();
+        node.velocity = Force::from((0.0001, 0.0, 0.0));
+
+        assert_eq!(Force::zero(), drag.apply(&node, 1.0));
+        node.dampen_rate = 0.0;
+        assert_eq!(node.velocity, drag.apply(&node, 1.0));
+    }
+
+    #[rstest]
+    #[case(DragModel::Linear)]
+    #[case(DragModel::Quadratic)]
+    fn physical_models_do_not_depend_on_time_step_subdivision(#[case] model: DragModel) {
+        let drag = Drag {
+            model,
+            medium_density: 1.2,
+        };
+        let node = moving_node();
+
+        let velocity_after_one_step = drag.apply(&node, 1.0);
+        let mut subdivided_node = node;
+        for _ in 0..4 {
+            subdivided_node.velocity = drag.apply(&subdivided_node, 0.25);
+        }
+
+        assert!(velocity_after_one_step.magnitude() < node.velocity.magnitude());
+        let difference = (velocity_after_one_step.0 - subdivided_node.velocity.0).x;
+        assert!(difference.abs() < 1e-6, "difference was {difference}");
+    }
+
+    #[rstest]
+    #[case(DragModel::Linear)]
+    #[case(DragModel::Quadratic)]
+    fn physical_models_scale_with_medium_density(#[case] model: DragModel) {
+        let thin_medium = Drag {
+            model,
+            medium_density: 0.5,
+        };
+        let thick_medium = Drag {
+            model,
+            medium_density: 2.0,
+        };
+        let vacuum = Drag {
+            model,
+            medium_density: 0.0,
+        };
+        let node = moving_node();
+
+        assert!(thick_medium.apply(&node, 1.0) < thin_medium.apply(&node, 1.0));
+        assert_eq!(node.velocity, vacuum.apply(&node, 1.0));
+    }
+
+    #[test]
+    fn linear_model_decays_exponentially() {
+        let drag = Drag {
+            model: DragModel::Linear,
+            medium_density: 1.0,
+        };
+        let node = moving_node();
+
+        let velocity = drag.apply(&node, 2.0);
+
+        assert_eq!(Force::from((2.0 * Scalar::exp(-1.0), 0.0, 0.0)), velocity);
+    }
+
+    #[test]
+    fn quadratic_model_slows_fast_nodes_more() {
+        let drag = Drag {
+            model: DragModel::Quadratic,
+            medium_density: 1.0,
+        };
+        let slow_node = moving_node();
+        let mut fast_node = moving_node();
+        fast_node.velocity = slow_node.velocity * 10.0;
+
+        let slow_ratio = drag.apply(&slow_node, 1.0).magnitude() / slow_node.velocity.magnitude();
+        let fast_ratio = drag.apply(&fast_node, 1.0).magnitude() / fast_node.velocity.magnitude();
+
+        assert!(fast_ratio < slow_ratio);
+    }
+}
@@ -1,22 +1,86 @@
+pub mod add_emitter;
+#[cfg(feature = "scripting")]
+pub mod add_hook;
 pub mod add_node;
+pub mod add_orbiter;
+pub mod add_sink;
+pub mod assert;
+pub mod cancel_scheduled;
 pub mod get;
+pub mod remove_emitter;
+#[cfg(feature = "scripting")]
+pub mod remove_hook;
 pub mod remove_node;
+pub mod remove_sink;
+pub mod schedule;
+pub mod set_adaptive_step;
+pub mod set_collision_policy;
+pub mod set_dimensionality;
+pub mod set_drag;
 pub mod set_node;
+pub mod set_pair_force_mode;
+pub mod set_softening;
+pub mod set_stats_log;
 pub mod set_target_tps;
+pub mod set_time_step;
+pub mod set_units;
 pub mod step;
 
+use add_emitter::AddEmitterEvent;
+#[cfg(feature = "scripting")]
+use add_hook::AddHookEvent;
 use add_node::AddNodeEvent;
+use add_orbiter::AddOrbiterEvent;
+use add_sink::AddSinkEvent;
+use assert::AssertEvent;
+use cancel_scheduled::CancelScheduledEvent;
 use get::GetEvent;
+use remove_emitter::RemoveEmitterEvent;
+#[cfg(feature = "scripting")]
+use remove_hook::RemoveHookEvent;
 use remove_node::RemoveNodeEvent;
+use remove_sink::RemoveSinkEvent;
+use schedule::ScheduleEvent;
+use set_adaptive_step::SetAdaptiveStepEvent;
+use set_collision_policy::SetCollisionPolicyEvent;
+use set_dimensionality::SetDimensionalityEvent;
+use set_drag::SetDragEvent;
 use set_node::SetNodeEvent;
+use set_pair_force_mode::SetPairForceModeEvent;
+use set_softening::SetSofteningEvent;
+use set_stats_log::SetStatsLogEvent;
 use set_target_tps::SetTargetTpsEvent;
+use set_time_step::SetTimeStepEvent;
+use set_units::SetUnitsEvent;
 use step::StepEvent;
 
+#[derive(Clone)]
 pub enum Event {
     AddNode(AddNodeEvent),
+    AddOrbiter(AddOrbiterEvent),
     RemoveNode(RemoveNodeEvent),
+    AddEmitter(AddEmitterEvent),
+    RemoveEmitter(RemoveEmitterEvent),
+    AddSink(AddSinkEvent),
+    RemoveSink(RemoveSinkEvent),
     SetNode(SetNodeEvent),
     Get(GetEvent),
     SetTargetTps(SetTargetTpsEvent),
+    SetSoftening(SetSofteningEvent),
+    SetPairForceMode(SetPairForceModeEvent),
+    SetStatsLog(SetStatsLogEvent),
+    SetDrag(SetDragEvent),
+    SetTimeStep(SetTimeStepEvent),
+    SetAdaptiveStep(SetAdaptiveStepEvent),
+    SetCollisionPolicy(SetCollisionPolicyEvent),
+    SetDimensionality(SetDimensionalityEvent),
+    SetUnits(SetUnitsEvent),
     Step(StepEvent),
+    Schedule(ScheduleEvent),
+    CancelScheduled(CancelScheduledEvent),
+    Assert(AssertEvent),
+    #[cfg(feature = "scripting")]
+    AddHook(AddHookEvent),
+    #[cfg(feature = "scripting")]
+    RemoveHook(RemoveHookEvent),
 }
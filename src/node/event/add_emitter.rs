@@ -0,0 +1,6 @@
+use crate::simulation::emitter::Emitter;
+
+#[derive(Clone)]
+pub struct AddEmitterEvent {
+    pub emitter: Emitter,
+}
@@ -0,0 +1,12 @@
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct AddHookEvent {
+    pub id: u32,
+    /// Where the script came from, such as its file
+    pub name: String,
+    /// Rhai source of the script
+    pub source: String,
+    /// Time the hook may run for each tick before it is stopped
+    pub budget: Duration,
+}
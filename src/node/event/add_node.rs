@@ -1,5 +1,8 @@
-use crate::node::Node;
+use crate::node::{self, Node};
 
+#[derive(Clone)]
 pub struct AddNodeEvent {
     pub node: Node,
+    /// Node the position of `node` is from, if it was given relative to one
+    pub position_relative_to: Option<node::Id>,
 }
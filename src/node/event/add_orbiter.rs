@@ -0,0 +1,9 @@
+use crate::node::{self, orbit::Elements, Node};
+
+#[derive(Clone)]
+pub struct AddOrbiterEvent {
+    pub node: Node,
+    /// Node that `node` orbits. Its position, velocity and mass are used when the event is handled
+    pub around: node::Id,
+    pub elements: Elements,
+}
@@ -0,0 +1,6 @@
+use crate::simulation::sink::Sink;
+
+#[derive(Clone)]
+pub struct AddSinkEvent {
+    pub sink: Sink,
+}
@@ -0,0 +1,360 @@
+use std::fmt;
+
+use cgmath::{EuclideanSpace, InnerSpace};
+
+use crate::{
+    node::{self, Scalar},
+    simulation::Simulation,
+};
+
+/// Tolerance of checks that are not given one, so that values which only differ by rounding match
+pub const DEFAULT_TOLERANCE: Scalar = 1.0e-6;
+
+/// Values also match if they are within this fraction of the size of the expected value, so that
+/// rounding does not fail checks of large values
+const RELATIVE_TOLERANCE: Scalar = 1.0e-6;
+
+/// Checks the simulation against expected values, failing if any are more than `tolerance` away
+#[derive(Clone)]
+pub struct AssertEvent {
+    pub check: Check,
+    pub tolerance: Scalar,
+    /// Whether a failure should stop the run the check is part of. Expectations are reported, and
+    /// the run carries on
+    pub stop_on_failure: bool,
+    /// Where the check was written, ie. "planets.txt:3", if it came from a script
+    pub source: Option<String>,
+}
+
+#[derive(Clone)]
+pub enum Check {
+    Node(NodeCheck),
+    Stats(StatsCheck),
+}
+
+#[derive(Clone)]
+pub struct NodeCheck {
+    pub id: node::Id,
+    pub position: Option<node::Position>,
+    pub velocity: Option<node::Force>,
+    pub mass: Option<Scalar>,
+    pub radius: Option<Scalar>,
+}
+
+#[derive(Clone, Default)]
+pub struct StatsCheck {
+    pub tick: Option<u64>,
+    pub time: Option<Scalar>,
+    pub kinetic_energy: Option<Scalar>,
+    pub potential_energy: Option<Scalar>,
+    pub total_energy: Option<Scalar>,
+    pub momentum: Option<node::Force>,
+    pub angular_momentum: Option<node::Force>,
+    pub centre_of_mass: Option<node::Position>,
+}
+
+/// A value that was checked, and the value it was expected to be
+struct Mismatch {
+    name: &'static str,
+    actual: String,
+    expected: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} was {}, expected {}",
+            self.name, self.actual, self.expected
+        )
+    }
+}
+
+impl AssertEvent {
+    /// Gives an error describing every value that was not as expected
+    pub fn handle(&self, simulation: &Simulation) -> Result<(), String> {
+        let mismatches = match &self.check {
+            Check::Node(node_check) => node_check.mismatches(simulation, self.tolerance),
+            Check::Stats(stats_check) => Ok(stats_check.mismatches(simulation, self.tolerance)),
+        };
+        let failure = match mismatches {
+            Ok(mismatches) if mismatches.is_empty() => return Ok(()),
+            Ok(mismatches) => {
+                let mismatches: Vec<String> = mismatches.iter().map(Mismatch::to_string).collect();
+                format!("{} (tolerance {})", mismatches.join(", "), self.tolerance)
+            }
+            Err(err) => err,
+        };
+        let kind = match self.stop_on_failure {
+            true => "Assertion failed",
+            false => "Expectation failed",
+        };
+        match &self.source {
+            Some(source) => Err(format!("{} - {} - {}", source, kind, failure)),
+            None => Err(format!("{} - {}", kind, failure)),
+        }
+    }
+}
+
+/// A value that can be checked
+trait Checked: fmt::Display {
+    /// Distance between this and `other`. For vectors, this is the distance between their ends
+    fn distance(&self, other: &Self) -> Scalar;
+    fn size(&self) -> Scalar;
+}
+
+impl Checked for Scalar {
+    fn distance(&self, other: &Self) -> Scalar {
+        (self - other).abs()
+    }
+
+    fn size(&self) -> Scalar {
+        self.abs()
+    }
+}
+
+impl Checked for node::Force {
+    fn distance(&self, other: &Self) -> Scalar {
+        (self.0 - other.0).magnitude()
+    }
+
+    fn size(&self) -> Scalar {
+        self.0.magnitude()
+    }
+}
+
+impl Checked for node::Position {
+    fn distance(&self, other: &Self) -> Scalar {
+        (self.0 - other.0).magnitude()
+    }
+
+    fn size(&self) -> Scalar {
+        self.0.to_vec().magnitude()
+    }
+}
+
+/// Adds a mismatch if `expected` is given, and `actual` is not within the tolerance of it, or
+/// within the relative tolerance of its size
+fn check<T: Checked>(
+    mismatches: &mut Vec<Mismatch>,
+    name: &'static str,
+    actual: T,
+    expected: Option<T>,
+    tolerance: Scalar,
+) {
+    let Some(expected) = expected else {
+        return;
+    };
+    let tolerance = tolerance.max(RELATIVE_TOLERANCE * expected.size());
+    // Written so that a distance of NaN does not match
+    let matches = actual.distance(&expected) <= tolerance;
+    if !matches {
+        mismatches.push(Mismatch {
+            name,
+            actual: actual.to_string(),
+            expected: expected.to_string(),
+        });
+    }
+}
+
+impl NodeCheck {
+    fn mismatches(
+        &self,
+        simulation: &Simulation,
+        tolerance: Scalar,
+    ) -> Result<Vec<Mismatch>, String> {
+        let node = match simulation.nodes.find(self.id) {
+            Some(node) => node,
+            None => return Err(format!("no node with id {} exists", self.id)),
+        };
+        let mut mismatches = vec![];
+        check(
+            &mut mismatches,
+            "position",
+            node.position,
+            self.position,
+            tolerance,
+        );
+        check(
+            &mut mismatches,
+            "velocity",
+            node.velocity,
+            self.velocity,
+            tolerance,
+        );
+        check(&mut mismatches, "mass", node.mass, self.mass, tolerance);
+        check(
+            &mut mismatches,
+            "radius",
+            node.radius,
+            self.radius,
+            tolerance,
+        );
+        Ok(mismatches)
+    }
+}
+
+impl StatsCheck {
+    fn mismatches(&self, simulation: &Simulation, tolerance: Scalar) -> Vec<Mismatch> {
+        let stats = simulation.stats();
+        let mut mismatches = vec![];
+        if let Some(tick) = self.tick {
+            if stats.tick != tick {
+                mismatches.push(Mismatch {
+                    name: "tick",
+                    actual: stats.tick.to_string(),
+                    expected: tick.to_string(),
+                });
+            }
+        }
+        check(
+            &mut mismatches,
+            "time",
+            simulation.time(),
+            self.time,
+            tolerance,
+        );
+        check(
+            &mut mismatches,
+            "kinetic energy",
+            stats.kinetic_energy,
+            self.kinetic_energy,
+            tolerance,
+        );
+        check(
+            &mut mismatches,
+            "potential energy",
+            stats.potential_energy,
+            self.potential_energy,
+            tolerance,
+        );
+        check(
+            &mut mismatches,
+            "total energy",
+            stats.total_energy(),
+            self.total_energy,
+            tolerance,
+        );
+        check(
+            &mut mismatches,
+            "momentum",
+            node::Force(stats.momentum),
+            self.momentum,
+            tolerance,
+        );
+        check(
+            &mut mismatches,
+            "angular momentum",
+            node::Force(stats.angular_momentum),
+            self.angular_momentum,
+            tolerance,
+        );
+        check(
+            &mut mismatches,
+            "centre of mass",
+            stats.centre_of_mass,
+            self.centre_of_mass,
+            tolerance,
+        );
+        mismatches
+    }
+}
+
+#[cfg(test)]
+mod an_assert_event {
+    use super::*;
+
+    fn simulation() -> Simulation {
+        let mut simulation = Simulation::new();
+        let mut node = node::Node::new(node::Id(2), node::Position::from((4.0, 0.0, 0.0)));
+        node.mass = 2.0;
+        simulation.add_node(node);
+        simulation
+    }
+
+    fn node_assertion(position: (Scalar, Scalar, Scalar), tolerance: Scalar) -> AssertEvent {
+        AssertEvent {
+            check: Check::Node(NodeCheck {
+                id: node::Id(2),
+                position: Some(node::Position::from(position)),
+                velocity: None,
+                mass: None,
+                radius: None,
+            }),
+            tolerance,
+            stop_on_failure: true,
+            source: Some("test.txt:3".to_string()),
+        }
+    }
+
+    #[test]
+    fn passes_within_the_tolerance() {
+        let event = node_assertion((4.005, 0.0, 0.0), 0.01);
+
+        assert!(event.handle(&simulation()).is_ok());
+    }
+
+    #[test]
+    fn names_the_source_and_values_when_failing() {
+        let event = node_assertion((4.5, 0.0, 0.0), 0.01);
+
+        assert_eq!(
+            Err("test.txt:3 - Assertion failed - position was x: 4, y: 0, z: 0, expected x: 4.5, y: 0, z: 0 (tolerance 0.01)".to_string()),
+            event.handle(&simulation())
+        );
+    }
+
+    #[test]
+    fn allows_for_rounding_of_the_expected_values() {
+        let mut simulation = simulation();
+        let mut node = node::Node::new(node::Id(3), node::Position::from((1.0e8, 0.0, 0.0)));
+        node.mass = 0.1 + 0.2;
+        simulation.add_node(node);
+        let mut event = node_assertion((4.0, 0.0, 0.0), DEFAULT_TOLERANCE);
+        event.check = Check::Node(NodeCheck {
+            id: node::Id(3),
+            position: Some(node::Position::from((1.0e8 + 64.0, 0.0, 0.0))),
+            velocity: None,
+            mass: Some(0.3),
+            radius: None,
+        });
+
+        assert!(event.handle(&simulation).is_ok());
+        event.tolerance = 0.0;
+        assert!(event.handle(&simulation).is_ok());
+    }
+
+    #[test]
+    fn fails_if_the_node_does_not_exist() {
+        let mut event = node_assertion((4.0, 0.0, 0.0), 0.01);
+        event.check = Check::Node(NodeCheck {
+            id: node::Id(3),
+            position: None,
+            velocity: None,
+            mass: None,
+            radius: None,
+        });
+
+        assert!(event.handle(&simulation()).is_err());
+    }
+
+    #[test]
+    fn checks_stats() {
+        let event = AssertEvent {
+            check: Check::Stats(StatsCheck {
+                tick: Some(0),
+                centre_of_mass: Some(node::Position::from((4.0, 0.0, 0.0))),
+                total_energy: Some(1.0),
+                ..Default::default()
+            }),
+            tolerance: 0.0,
+            stop_on_failure: false,
+            source: None,
+        };
+
+        assert_eq!(
+            Err("Expectation failed - total energy was 0, expected 1 (tolerance 0)".to_string()),
+            event.handle(&simulation())
+        );
+    }
+}
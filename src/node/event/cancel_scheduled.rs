@@ -0,0 +1,4 @@
+#[derive(Clone)]
+pub struct CancelScheduledEvent {
+    pub handle: u32,
+}
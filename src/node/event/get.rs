@@ -1,77 +1,212 @@
 use crate::{
     node::{self, Node},
-    simulation::Simulation,
+    simulation::{
+        adaptive_step::AdaptiveStepMode,
+        units::{Quantity, UnitSystem},
+        Simulation,
+    },
 };
 
+#[derive(Clone)]
 pub enum GetEvent {
     Node(NodeArgs),
+    Orbit(OrbitArgs),
     Tps,
+    Softening,
+    PairForceMode,
+    Stats,
+    Drag,
+    TimeStep,
+    Dimensionality,
+    Units,
+    Emitters,
+    Sinks,
+    CollisionPolicy,
+    Schedule,
+    #[cfg(feature = "scripting")]
+    Hooks,
 }
 
+#[derive(Clone)]
 pub struct NodeArgs {
     pub id: u32,
     pub position: bool,
     pub velocity: bool,
     pub mass: bool,
+    pub radius: bool,
     pub gravitational_constant_override: bool,
     pub dampen_rate: bool,
+    pub drag_coefficient: bool,
     pub freeze: bool,
+    pub locked_axes: bool,
+    pub constraint: bool,
+    pub kinematic: bool,
+}
+
+#[derive(Clone)]
+pub struct OrbitArgs {
+    pub id: u32,
+    /// Node the orbit is around, instead of the node pulling hardest on it
+    pub around: Option<u32>,
 }
 
 impl GetEvent {
-    pub fn handle(&self, simulation: &Simulation) {
+    pub fn handle(&self, simulation: &Simulation) -> Result<(), String> {
         match self {
-            GetEvent::Node(node_args) => node_args.display_node_information(simulation),
+            GetEvent::Node(node_args) => return node_args.display_node_information(simulation),
+            GetEvent::Orbit(orbit_args) => return orbit_args.display_orbit(simulation),
             GetEvent::Tps => println!("tps: {}", simulation.target_tps()),
+            GetEvent::Softening => println!(
+                "softening: {}{}",
+                simulation.softening_length,
+                simulation.units.suffix(Quantity::Length)
+            ),
+            GetEvent::PairForceMode => {
+                println!("pair force mode: {}", simulation.pair_force_mode)
+            }
+            GetEvent::Stats => println!(
+                "{}",
+                simulation.stats().to_display_string(&simulation.units)
+            ),
+            GetEvent::Drag => println!("drag: {}", simulation.drag),
+            GetEvent::TimeStep => {
+                let adaptive_step = &simulation.adaptive_step;
+                println!(
+                    "time step: {}{}",
+                    simulation.time_step,
+                    simulation.units.suffix(Quantity::Time)
+                );
+                println!(
+                    "adaptive step: {}",
+                    adaptive_step.to_display_string(&simulation.units)
+                );
+                if adaptive_step.mode != AdaptiveStepMode::Fixed {
+                    println!(
+                        "sub-step: {}{}",
+                        adaptive_step.time_step(),
+                        simulation.units.suffix(Quantity::Time)
+                    );
+                    println!("rejected steps: {}", adaptive_step.rejected_steps());
+                }
+            }
+            GetEvent::Dimensionality => {
+                println!("dimensionality: {}", simulation.dimensionality())
+            }
+            GetEvent::Units => {
+                println!("units: {}", simulation.units);
+                println!(
+                    "gravitational constant: {}{}",
+                    simulation.gravitational_constant,
+                    simulation.units.suffix(Quantity::GravitationalConstant)
+                );
+            }
+            GetEvent::Emitters => match simulation.emitters.is_empty() {
+                true => println!("No emitters"),
+                false => simulation
+                    .emitters
+                    .iter()
+                    .for_each(|emitter| println!("{}", emitter)),
+            },
+            GetEvent::Schedule => match simulation.schedule.is_empty() {
+                true => println!("Nothing scheduled"),
+                false => simulation
+                    .schedule
+                    .iter()
+                    .for_each(|entry| println!("{}", entry)),
+            },
+            GetEvent::CollisionPolicy => {
+                println!("collision policy: {}", simulation.collision_policy)
+            }
+            GetEvent::Sinks => match simulation.sinks.is_empty() {
+                true => println!("No sinks"),
+                false => simulation
+                    .sinks
+                    .iter()
+                    .for_each(|sink| println!("{}", sink)),
+            },
+            #[cfg(feature = "scripting")]
+            GetEvent::Hooks => match simulation.scripting.is_empty() {
+                true => println!("No hooks"),
+                false => simulation
+                    .scripting
+                    .hooks()
+                    .for_each(|hook| println!("{}", hook)),
+            },
         }
+        Ok(())
     }
 }
 
 impl NodeArgs {
-    pub fn display_node_information(&self, simulation: &Simulation) {
-        let node = match simulation
-            .nodes
-            .iter()
-            .find(|node| node.id == node::Id(self.id))
-        {
+    pub fn display_node_information(&self, simulation: &Simulation) -> Result<(), String> {
+        let node = match simulation.nodes.find(node::Id(self.id)) {
             Some(node) => node,
             None => {
-                println!("Error displaying node information for node with id {} - no node with that id exists", self.id);
-                return;
+                return Err(format!("Error displaying node information for node with id {} - no node with that id exists", self.id));
             }
         };
 
-        println!("{}", self.get_display_string_from_node_args(node));
+        let kinematic = simulation.kinematics.get(&node.id);
+        println!(
+            "{}",
+            self.get_display_string_from_node_args(&node, kinematic, &simulation.units)
+        );
+        Ok(())
     }
 
-    fn get_display_string_from_node_args(&self, node: &Node) -> String {
+    fn get_display_string_from_node_args(
+        &self,
+        node: &Node,
+        kinematic: Option<&node::kinematic::Kinematic>,
+        units: &UnitSystem,
+    ) -> String {
         let mut display_string = format!("Node {}:", node.id.to_string());
         // TODO - Use bitflags crate - https://docs.rs/bitflags/latest/bitflags/
         let no_flags_present = !(self.position
             || self.velocity
             || self.mass
+            || self.radius
             || self.gravitational_constant_override
             || self.dampen_rate
-            || self.freeze);
+            || self.drag_coefficient
+            || self.freeze
+            || self.locked_axes
+            || self.constraint
+            || self.kinematic);
 
         display_string = match self.position || no_flags_present {
             true => format!(
-                "{display_string}\n\tposition: {}",
-                node.position.to_string()
+                "{display_string}\n\tposition: {}{}",
+                node.position.to_string(),
+                units.suffix(Quantity::Length)
             ),
             false => display_string,
         };
 
         display_string = match self.velocity || no_flags_present {
             true => format!(
-                "{display_string}\n\tvelocity: {}",
-                node.velocity.to_string()
+                "{display_string}\n\tvelocity: {}{}",
+                node.velocity.to_string(),
+                units.suffix(Quantity::Velocity)
             ),
             false => display_string,
         };
 
         display_string = match self.mass || no_flags_present {
-            true => format!("{display_string}\n\tmass: {}", node.mass.to_string()),
+            true => format!(
+                "{display_string}\n\tmass: {}{}",
+                node.mass.to_string(),
+                units.suffix(Quantity::Mass)
+            ),
+            false => display_string,
+        };
+
+        display_string = match self.radius || no_flags_present {
+            true => format!(
+                "{display_string}\n\tradius: {}{}",
+                node.radius,
+                units.suffix(Quantity::Length)
+            ),
             false => display_string,
         };
 
@@ -94,11 +229,81 @@ impl NodeArgs {
             false => display_string,
         };
 
+        display_string = match self.drag_coefficient || no_flags_present {
+            true => format!(
+                "{display_string}\n\tdrag coefficient: {}",
+                node.drag_coefficient
+            ),
+            false => display_string,
+        };
+
         display_string = match self.freeze || no_flags_present {
             true => format!("{display_string}\n\tfreeze: {}", node.freeze.to_string()),
             false => display_string,
         };
 
+        display_string = match self.locked_axes || no_flags_present {
+            true => format!("{display_string}\n\tlocked axes: {}", node.locked_axes),
+            false => display_string,
+        };
+
+        display_string = match self.constraint || no_flags_present {
+            true => {
+                let value = match node.constraint {
+                    Some(constraint) => constraint.to_string(),
+                    None => "None".to_string(),
+                };
+                format!("{display_string}\n\tconstraint: {value}")
+            }
+            false => display_string,
+        };
+
+        display_string = match self.kinematic || no_flags_present {
+            true => {
+                let value = match kinematic {
+                    Some(kinematic) => kinematic.path.to_string(),
+                    None => "None".to_string(),
+                };
+                format!("{display_string}\n\tkinematic: {value}")
+            }
+            false => display_string,
+        };
+
         display_string
     }
 }
+
+impl OrbitArgs {
+    pub fn display_orbit(&self, simulation: &Simulation) -> Result<(), String> {
+        let (around, elements, gravitational_parameter) = simulation
+            .orbit_of(node::Id(self.id), self.around.map(node::Id))
+            .map_err(|err| {
+                format!(
+                    "Error displaying orbit of node with id {} - {}",
+                    self.id, err
+                )
+            })?;
+
+        let units = &simulation.units;
+        let length = units.suffix(Quantity::Length);
+        let period = match elements.period(gravitational_parameter) {
+            Some(period) => format!("{}{}", period, units.suffix(Quantity::Time)),
+            None => "None".to_string(),
+        };
+        println!("Node {} around node {}:", self.id, around);
+        println!("\tsemi-major axis: {}{}", elements.semi_major_axis, length);
+        println!("\teccentricity: {}", elements.eccentricity);
+        println!("\tinclination: {} deg", elements.inclination.to_degrees());
+        println!(
+            "\tlongitude of ascending node: {} deg",
+            elements.longitude_of_ascending_node.to_degrees()
+        );
+        println!(
+            "\targument of periapsis: {} deg",
+            elements.argument_of_periapsis.to_degrees()
+        );
+        println!("\ttrue anomaly: {} deg", elements.true_anomaly.to_degrees());
+        println!("\tperiod: {}", period);
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+#[derive(Clone)]
+pub struct RemoveEmitterEvent {
+    pub emitter_id: u32,
+}
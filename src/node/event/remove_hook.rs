@@ -0,0 +1,4 @@
+#[derive(Clone)]
+pub struct RemoveHookEvent {
+    pub hook_id: u32,
+}
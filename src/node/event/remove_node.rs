@@ -1,5 +1,6 @@
 use crate::node::Id;
 
+#[derive(Clone)]
 pub struct RemoveNodeEvent {
     pub node_id: Id,
 }
@@ -0,0 +1,4 @@
+#[derive(Clone)]
+pub struct RemoveSinkEvent {
+    pub sink_id: u32,
+}
@@ -0,0 +1,9 @@
+use crate::{node::Event, simulation::schedule::Trigger};
+
+#[derive(Clone)]
+pub struct ScheduleEvent {
+    pub trigger: Trigger,
+    /// The command that was scheduled, as it was entered
+    pub description: String,
+    pub events: Vec<Event>,
+}
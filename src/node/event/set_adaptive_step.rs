@@ -0,0 +1,9 @@
+use crate::{node::Scalar, simulation::adaptive_step::AdaptiveStepMode};
+
+#[derive(Clone)]
+pub struct SetAdaptiveStepEvent {
+    pub mode: Option<AdaptiveStepMode>,
+    pub tolerance: Option<Scalar>,
+    pub min_time_step: Option<Scalar>,
+    pub max_time_step: Option<Scalar>,
+}
@@ -0,0 +1,6 @@
+use crate::simulation::collision::CollisionPolicy;
+
+#[derive(Clone)]
+pub struct SetCollisionPolicyEvent {
+    pub collision_policy: CollisionPolicy,
+}
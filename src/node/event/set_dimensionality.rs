@@ -0,0 +1,6 @@
+use crate::simulation::dimensionality::Dimensionality;
+
+#[derive(Clone)]
+pub struct SetDimensionalityEvent {
+    pub dimensionality: Dimensionality,
+}
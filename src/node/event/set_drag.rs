@@ -0,0 +1,7 @@
+use crate::node::{drag::DragModel, Scalar};
+
+#[derive(Clone)]
+pub struct SetDragEvent {
+    pub model: Option<DragModel>,
+    pub medium_density: Option<Scalar>,
+}
@@ -1,11 +1,23 @@
 use crate::node;
 
+#[derive(Clone)]
 pub struct SetNodeEvent {
     pub id: node::Id,
     pub position: Option<node::Position>,
     pub velocity: Option<node::Force>,
-    pub mass: Option<f32>,
-    pub gravitational_constant_override: Option<f32>,
-    pub dampen_rate: Option<f32>,
+    /// Node `position` is from, if it was given relative to one
+    pub position_relative_to: Option<node::Id>,
+    /// Node `velocity` is from, if it was given relative to one
+    pub velocity_relative_to: Option<node::Id>,
+    pub mass: Option<node::Scalar>,
+    pub radius: Option<node::Scalar>,
+    pub gravitational_constant_override: Option<node::Scalar>,
+    pub dampen_rate: Option<node::Scalar>,
+    pub drag_coefficient: Option<node::Scalar>,
     pub freeze: Option<bool>,
+    pub locked_axes: Option<node::constraint::Axes>,
+    /// Sets the constraint of the node if present. `Some(None)` removes the nodes constraint
+    pub constraint: Option<Option<node::constraint::Constraint>>,
+    /// Sets the kinematic path of the node if present. `Some(None)` removes the nodes path
+    pub kinematic: Option<Option<node::kinematic::Path>>,
 }
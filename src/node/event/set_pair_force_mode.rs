@@ -0,0 +1,6 @@
+use crate::node::force::PairForceMode;
+
+#[derive(Clone)]
+pub struct SetPairForceModeEvent {
+    pub pair_force_mode: PairForceMode,
+}
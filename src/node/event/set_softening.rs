@@ -0,0 +1,6 @@
+use crate::node::Scalar;
+
+#[derive(Clone)]
+pub struct SetSofteningEvent {
+    pub softening_length: Scalar,
+}
@@ -0,0 +1,9 @@
+use std::path::PathBuf;
+
+#[derive(Clone)]
+pub struct SetStatsLogEvent {
+    /// File to log stats to. Logging is stopped if this is None
+    pub path: Option<PathBuf>,
+    /// Number of ticks between each logged row
+    pub interval: u32,
+}
@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct SetTargetTpsEvent {
     pub target_tps: u32,
 }
@@ -0,0 +1,6 @@
+use crate::node::Scalar;
+
+#[derive(Clone)]
+pub struct SetTimeStepEvent {
+    pub time_step: Scalar,
+}
@@ -0,0 +1,6 @@
+use crate::simulation::units::UnitSystem;
+
+#[derive(Clone)]
+pub struct SetUnitsEvent {
+    pub units: UnitSystem,
+}
@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct StepEvent {
     pub steps: u32,
 }
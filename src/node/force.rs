@@ -6,10 +6,38 @@ use std::{
 
 use cgmath::{self, InnerSpace, Zero};
 
-use super::Position;
+use super::{
+    vector::{ParseContext, Vector},
+    Position, Scalar,
+};
+use crate::simulation::units::Quantity;
 
 #[derive(PartialEq, Debug, Clone, Copy)]
-pub struct Force(pub cgmath::Vector3<f32>);
+pub struct Force(pub cgmath::Vector3<Scalar>);
+
+/// How the forces between each pair of nodes are evaluated
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum PairForceMode {
+    /// Each pair is evaluated once, and the equal and opposite force applied to both nodes.
+    /// Conserves momentum. Gravitational constant overrides are shared by the pair, so an override
+    /// changes the force on its node as well as the force it exerts - an override of 0 stops the
+    /// node pulling or being pulled.
+    #[default]
+    Symmetric,
+    /// The force on each node is evaluated separately, scaled by the gravitational constant
+    /// override of the node exerting the force. This was the behaviour before pair force modes were
+    /// added.
+    Asymmetric,
+}
+
+impl fmt::Display for PairForceMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PairForceMode::Symmetric => write!(f, "symmetric"),
+            PairForceMode::Asymmetric => write!(f, "asymmetric"),
+        }
+    }
+}
 
 impl fmt::Display for Force {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -46,23 +74,23 @@ impl Neg for Force {
     }
 }
 
-impl Mul<f32> for Force {
+impl Mul<Scalar> for Force {
     type Output = Self;
 
-    fn mul(self, rhs: f32) -> Self::Output {
+    fn mul(self, rhs: Scalar) -> Self::Output {
         Self::from(self.0.map(|n| n * rhs))
     }
 }
 
-impl From<(f32, f32, f32)> for Force {
-    fn from(value: (f32, f32, f32)) -> Self {
+impl From<(Scalar, Scalar, Scalar)> for Force {
+    fn from(value: (Scalar, Scalar, Scalar)) -> Self {
         let (x, y, z) = value;
         Self::from(cgmath::Vector3 { x, y, z })
     }
 }
 
-impl From<cgmath::Vector3<f32>> for Force {
-    fn from(value: cgmath::Vector3<f32>) -> Self {
+impl From<cgmath::Vector3<Scalar>> for Force {
+    fn from(value: cgmath::Vector3<Scalar>) -> Self {
         Self(value)
     }
 }
@@ -71,42 +99,18 @@ impl TryFrom<String> for Force {
     type Error = String;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let force_string = value.trim_matches('"').split(',');
-        let positions: Vec<Result<f32, _>> =
-            force_string.map(|s| s.trim().parse::<f32>()).collect();
-        if positions.len() != 3 {
-            return Err("Force must have 3 values".to_string());
-        }
-        let x = match &positions[0] {
-            Ok(number) => *number,
-            Err(_) => {
-                return Err("Force vector x must be an f32".to_string());
-            }
-        };
-        let y = match &positions[1] {
-            Ok(number) => *number,
-            Err(_) => {
-                return Err("Force vector y must be an f32".to_string());
-            }
-        };
-        let z = match &positions[2] {
-            Ok(number) => *number,
-            Err(_) => {
-                return Err("Force vector z must be an f32".to_string());
-            }
-        };
-        Ok(Self(cgmath::Vector3 { x, y, z }))
+        Self::parse(value, &ParseContext::default())
     }
 }
 
-impl Into<(f32, f32, f32)> for Force {
-    fn into(self) -> (f32, f32, f32) {
+impl Into<(Scalar, Scalar, Scalar)> for Force {
+    fn into(self) -> (Scalar, Scalar, Scalar) {
         self.0.into()
     }
 }
 
-impl Into<cgmath::Vector3<f32>> for Force {
-    fn into(self) -> cgmath::Vector3<f32> {
+impl Into<cgmath::Vector3<Scalar>> for Force {
+    fn into(self) -> cgmath::Vector3<Scalar> {
         self.0.into()
     }
 }
@@ -118,15 +122,30 @@ impl PartialOrd for Force {
 }
 
 impl Force {
-    const FORCE_RADIUS: u32 = 5;
+    /// Parses a force written in any of the forms of [`Vector`], which can not be relative to a
+    /// node
+    pub fn parse(value: String, context: &ParseContext) -> Result<Self, String> {
+        let offset =
+            Vector::parse(&value, "Force", Quantity::Velocity, context)?.absolute("Force")?;
+        Ok(Self(offset))
+    }
+
+    /// Distance beyond which nodes do not interact in dimensionless simulations. Simulations in real
+    /// world units have no such limit
+    pub const FORCE_RADIUS: Scalar = 5.0;
+
     pub fn zero() -> Self {
         Self(cgmath::Vector3::zero())
     }
 
+    /// Calculates the force on `node` from all of `others`, ignoring nodes further away than
+    /// `force_radius`
     pub fn calculate_incoming_force(
         node: &super::Node,
         others: &Vec<&super::Node>,
-        default_gravitational_constant: &f32,
+        default_gravitational_constant: &Scalar,
+        softening_length: &Scalar,
+        force_radius: Option<Scalar>,
     ) -> Self {
         let resultant_force = others
             .iter()
@@ -135,6 +154,8 @@ impl Force {
                     node,
                     other,
                     default_gravitational_constant,
+                    softening_length,
+                    force_radius,
                 )
             })
             .sum();
@@ -144,11 +165,123 @@ impl Force {
     fn calculate_incoming_force_from_node(
         node: &super::Node,
         other: &super::Node,
-        default_gravitational_constant: &f32,
+        default_gravitational_constant: &Scalar,
+        softening_length: &Scalar,
+        force_radius: Option<Scalar>,
+    ) -> Self {
+        let g = match other.gravitational_constant_override {
+            Some(gravitational_constant) => gravitational_constant,
+            None => *default_gravitational_constant,
+        };
+        Self::calculate_force_between(node, other, g, softening_length, force_radius)
+    }
+
+    /// Calculates the force on `node` from `other` using a gravitational constant shared by both
+    /// nodes, so that the force on `other` from `node` is exactly the negation of the result.
+    /// Nodes further apart than `force_radius` do not interact.
+    pub fn calculate_pair_force(
+        node: &super::Node,
+        other: &super::Node,
+        default_gravitational_constant: &Scalar,
+        softening_length: &Scalar,
+        force_radius: Option<Scalar>,
+    ) -> Self {
+        let g = Self::pair_gravitational_constant(node, other, default_gravitational_constant);
+        Self::calculate_force_between(node, other, g, softening_length, force_radius)
+    }
+
+    /// The gravitational constant acting between two nodes. If only one node has an override it
+    /// is used for the pair, and if both do the mean of the two overrides is used.
+    pub fn pair_gravitational_constant(
+        node: &super::Node,
+        other: &super::Node,
+        default_gravitational_constant: &Scalar,
+    ) -> Scalar {
+        Self::combine_gravitational_constants(
+            node.gravitational_constant_override,
+            other.gravitational_constant_override,
+            default_gravitational_constant,
+        )
+    }
+
+    /// The gravitational constant acting between two nodes with the given overrides. See
+    /// [`Force::pair_gravitational_constant`]
+    pub fn combine_gravitational_constants(
+        gravitational_constant_override: Option<Scalar>,
+        other_gravitational_constant_override: Option<Scalar>,
+        default_gravitational_constant: &Scalar,
+    ) -> Scalar {
+        match (
+            gravitational_constant_override,
+            other_gravitational_constant_override,
+        ) {
+            (Some(g1), Some(g2)) => (g1 + g2) / 2.0,
+            (Some(g), None) | (None, Some(g)) => g,
+            (None, None) => *default_gravitational_constant,
+        }
+    }
+
+    /// The potential energy between two nodes under the active force law, using the pair
+    /// gravitational constant. With a `force_radius`, beyond which nodes do not interact, the
+    /// potential is shifted to be zero at that radius.
+    pub fn calculate_pair_potential_energy(
+        node: &super::Node,
+        other: &super::Node,
+        default_gravitational_constant: &Scalar,
+        softening_length: &Scalar,
+        force_radius: Option<Scalar>,
+    ) -> Scalar {
+        let r = Position::displacement(&node.position, &other.position).magnitude();
+        // Coincident nodes exert no force on eachother, so are treated as not interacting
+        if !Self::in_range(r, force_radius) || r == 0.0 {
+            return 0.0;
+        }
+        let g = Self::pair_gravitational_constant(node, other, default_gravitational_constant);
+        let softened_distance = |r: Scalar| (r.powf(2.0) + softening_length.powf(2.0)).sqrt();
+        let potential_at_force_radius = match force_radius {
+            Some(force_radius) => 1.0 / softened_distance(force_radius),
+            None => 0.0,
+        };
+        -g * node.mass * other.mass * (1.0 / softened_distance(r) - potential_at_force_radius)
+    }
+
+    /// Whether nodes `distance` apart interact, when they stop interacting beyond `force_radius`
+    pub fn in_range(distance: Scalar, force_radius: Option<Scalar>) -> bool {
+        force_radius.is_none_or(|force_radius| distance <= force_radius)
+    }
+
+    fn calculate_force_between(
+        node: &super::Node,
+        other: &super::Node,
+        g: Scalar,
+        softening_length: &Scalar,
+        force_radius: Option<Scalar>,
     ) -> Self {
-        let displacement = Position::displacement(&node.position, &other.position);
+        Self::between(
+            &node.position,
+            node.mass,
+            &other.position,
+            other.mass,
+            g,
+            softening_length,
+            force_radius,
+        )
+    }
+
+    /// The force on a body from another body, given their positions and masses. Lets callers
+    /// storing positions and masses in separate arrays calculate forces without building nodes.
+    pub fn between(
+        position: &Position,
+        mass: Scalar,
+        other_position: &Position,
+        other_mass: Scalar,
+        g: Scalar,
+        softening_length: &Scalar,
+        force_radius: Option<Scalar>,
+    ) -> Self {
+        let displacement = Position::displacement(position, other_position);
         let magnitude_distance = displacement.magnitude();
-        if magnitude_distance > Self::FORCE_RADIUS as f32 {
+        if !Self::in_range(magnitude_distance, force_radius) {
             return Self::zero();
         }
         // Avoid divide by zero errors
@@ -156,21 +289,20 @@ impl Force {
             return Self::zero();
         }
 
-        // Newtons law of universal gravitation
+        // Newtons law of universal gravitation, with Plummer softening to keep the force
+        // bounded as nodes approach eachother
         // https://en.wikipedia.org/wiki/Newton%27s_law_of_universal_gravitation
-        let g = match other.gravitational_constant_override {
-            Some(gravitational_constant) => gravitational_constant,
-            None => *default_gravitational_constant,
-        };
-        let m1 = node.mass;
-        let m2 = other.mass;
+        // https://en.wikipedia.org/wiki/Plummer_model
+        let m1 = mass;
+        let m2 = other_mass;
         let r = magnitude_distance;
-        let force = g * (m1 * m2 / r.powf(2.0));
+        let softened_r_squared = r.powf(2.0) + softening_length.powf(2.0);
+        let force = g * (m1 * m2 * r / softened_r_squared.powf(1.5));
         let force = force * displacement.normalize();
         Force(force)
     }
 
-    pub fn magnitude(&self) -> f32 {
+    pub fn magnitude(&self) -> Scalar {
         self.0.magnitude()
     }
 }
@@ -180,7 +312,8 @@ mod a_force {
     use cgmath::Zero;
     use rstest::rstest;
 
-    use crate::node::{Force, Id, Node, Position};
+    use crate::node::{vector::ParseContext, Force, Id, Node, Position, Scalar};
+    use crate::simulation::dimensionality::Dimensionality;
 
     #[test]
     fn can_be_displayed() {
@@ -198,10 +331,10 @@ mod a_force {
     #[rstest]
     #[case((0.0, 0.0, 0.0), (1.0, 0.0, 1.0), (2.0, 3.0, 1.0), (3.0, 3.0, 2.0))]
     fn can_be_summed(
-        #[case] force_1: (f32, f32, f32),
-        #[case] force_2: (f32, f32, f32),
-        #[case] force_3: (f32, f32, f32),
-        #[case] expected_sum: (f32, f32, f32),
+        #[case] force_1: (Scalar, Scalar, Scalar),
+        #[case] force_2: (Scalar, Scalar, Scalar),
+        #[case] force_3: (Scalar, Scalar, Scalar),
+        #[case] expected_sum: (Scalar, Scalar, Scalar),
     ) {
         let (x, y, z) = force_1;
         let force_1 = Force(cgmath::Vector3 { x, y, z });
@@ -218,9 +351,9 @@ mod a_force {
     #[rstest]
     #[case((0.0, 0.5, 0.0), (1.0, 0.0, -1.0), (1.0, 0.5, -1.0))]
     fn can_be_added(
-        #[case] force_1: (f32, f32, f32),
-        #[case] force_2: (f32, f32, f32),
-        #[case] expected_force: (f32, f32, f32),
+        #[case] force_1: (Scalar, Scalar, Scalar),
+        #[case] force_2: (Scalar, Scalar, Scalar),
+        #[case] expected_force: (Scalar, Scalar, Scalar),
     ) {
         let (x, y, z) = force_1;
         let force_1 = Force(cgmath::Vector3 { x, y, z });
@@ -234,9 +367,9 @@ mod a_force {
     #[rstest]
     #[case((0.0, 0.5, 0.0), (1.0, 0.0, -1.0), (1.0, 0.5, -1.0))]
     fn can_be_add_assigned(
-        #[case] force_1: (f32, f32, f32),
-        #[case] force_2: (f32, f32, f32),
-        #[case] expected_force: (f32, f32, f32),
+        #[case] force_1: (Scalar, Scalar, Scalar),
+        #[case] force_2: (Scalar, Scalar, Scalar),
+        #[case] expected_force: (Scalar, Scalar, Scalar),
     ) {
         let (x, y, z) = force_1;
         let mut force_1 = Force(cgmath::Vector3 { x, y, z });
@@ -282,6 +415,8 @@ mod a_force {
             &node_a,
             &vec![&node_b],
             &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
         );
 
         let expected_force_on_node_b = Force(cgmath::Vector3 {
@@ -293,6 +428,8 @@ mod a_force {
             &node_b,
             &vec![&node_a],
             &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
         );
 
         assert_eq!(expected_force_on_node_a, force_on_node_a);
@@ -382,7 +519,7 @@ mod a_force {
         let node_b = Node::new(
             Id(2),
             Position(cgmath::Point3 {
-                x: Force::FORCE_RADIUS as f32 + 1.0,
+                x: Force::FORCE_RADIUS as Scalar + 1.0,
                 y: 0.0,
                 z: 0.0,
             }),
@@ -398,6 +535,8 @@ mod a_force {
             &node_a,
             &vec![&node_b],
             &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
         );
 
         let expected_force_on_node_b = Force(cgmath::Vector3 {
@@ -409,12 +548,28 @@ mod a_force {
             &node_b,
             &vec![&node_a],
             &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
         );
 
         assert_eq!(expected_force_on_node_a, force_on_node_a);
         assert_eq!(expected_force_on_node_b, force_on_node_b);
     }
 
+    #[test]
+    fn incoming_force_reaches_any_distance_without_a_force_radius() {
+        let node_a = Node::new(Id(1), Position::from((0.0, 0.0, 0.0)));
+        let node_b = Node::new(Id(2), Position::from((Force::FORCE_RADIUS * 2.0, 0.0, 0.0)));
+
+        let force_on_node_a =
+            super::Force::calculate_incoming_force(&node_a, &vec![&node_b], &1.0, &0.0, None);
+        let force_on_node_b =
+            super::Force::calculate_pair_force(&node_b, &node_a, &1.0, &0.0, None);
+
+        assert_ne!(Force::zero(), force_on_node_a);
+        assert_eq!(force_on_node_a, -force_on_node_b);
+    }
+
     #[test]
     fn incoming_force_is_scaled_by_nodes_gravitational_constant() {
         let mut node_a = Node::new(
@@ -446,6 +601,8 @@ mod a_force {
             &node_a,
             &vec![&node_b],
             &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
         );
 
         // Force is scaled by constant of other node
@@ -458,6 +615,8 @@ mod a_force {
             &node_b,
             &vec![&node_a],
             &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
         );
 
         assert_eq!(expected_force_on_node_a, force_on_node_a);
@@ -485,6 +644,8 @@ mod a_force {
             &node_a,
             &vec![&node_b],
             &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
         );
 
         let expected_force_on_node_b = Force(cgmath::Vector3 {
@@ -496,17 +657,135 @@ mod a_force {
             &node_b,
             &vec![&node_a],
             &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
         );
 
         assert_eq!(expected_force_on_node_a, force_on_node_a);
         assert_eq!(expected_force_on_node_b, force_on_node_b);
     }
 
+    #[test]
+    fn incoming_force_is_bounded_by_softening_length() {
+        let node_a = Node::new(Id(1), Position::from((0.0, 0.0, 0.0)));
+        let node_b = Node::new(Id(2), Position::from((0.001, 0.0, 0.0)));
+        let gravitational_constant = 1.0;
+        let softening_length = 1.0;
+
+        let unsoftened_force = super::Force::calculate_incoming_force(
+            &node_a,
+            &vec![&node_b],
+            &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
+        );
+        let softened_force = super::Force::calculate_incoming_force(
+            &node_a,
+            &vec![&node_b],
+            &gravitational_constant,
+            &softening_length,
+            Some(Force::FORCE_RADIUS),
+        );
+
+        // The Plummer force peaks at 2 / (3 * sqrt(3)) * g * m1 * m2 / softening_length^2
+        assert!(unsoftened_force.magnitude() > 1000.0);
+        assert!(softened_force.magnitude() < 0.39);
+    }
+
+    #[rstest]
+    #[case(None, None, -1.0)]
+    #[case(Some(2.0), None, 2.0)]
+    #[case(None, Some(2.0), 2.0)]
+    #[case(Some(2.0), Some(4.0), 3.0)]
+    fn pair_gravitational_constant_is_shared_by_both_nodes(
+        #[case] override_a: Option<Scalar>,
+        #[case] override_b: Option<Scalar>,
+        #[case] expected_gravitational_constant: Scalar,
+    ) {
+        let mut node_a = Node::new(Id(1), Position::from((0.0, 0.0, 0.0)));
+        node_a.gravitational_constant_override = override_a;
+        let mut node_b = Node::new(Id(2), Position::from((1.0, 0.0, 0.0)));
+        node_b.gravitational_constant_override = override_b;
+
+        assert_eq!(
+            expected_gravitational_constant,
+            super::Force::pair_gravitational_constant(&node_a, &node_b, &-1.0)
+        );
+        assert_eq!(
+            expected_gravitational_constant,
+            super::Force::pair_gravitational_constant(&node_b, &node_a, &-1.0)
+        );
+    }
+
+    #[test]
+    fn pair_forces_are_equal_and_opposite() {
+        let mut node_a = Node::new(Id(1), Position::from((0.0, 0.0, 0.0)));
+        node_a.gravitational_constant_override = Some(-2.0);
+        node_a.mass = 3.0;
+        let node_b = Node::new(Id(2), Position::from((1.0, 2.0, 0.0)));
+
+        let force_on_node_a =
+            super::Force::calculate_pair_force(&node_a, &node_b, &-1.0, &0.0, None);
+        let force_on_node_b =
+            super::Force::calculate_pair_force(&node_b, &node_a, &-1.0, &0.0, None);
+
+        assert_eq!(force_on_node_a, -force_on_node_b);
+    }
+
+    #[rstest]
+    #[case(1.0, -0.8)]
+    #[case(-1.0, 0.8)]
+    fn pair_potential_energy_is_shifted_to_zero_at_force_radius(
+        #[case] gravitational_constant: Scalar,
+        #[case] expected_potential_energy: Scalar,
+    ) {
+        let node_a = Node::new(Id(1), Position::from((0.0, 0.0, 0.0)));
+        let node_b = Node::new(Id(2), Position::from((1.0, 0.0, 0.0)));
+        let node_c = Node::new(Id(3), Position::from((Force::FORCE_RADIUS, 0.0, 0.0)));
+
+        let potential_energy = super::Force::calculate_pair_potential_energy(
+            &node_a,
+            &node_b,
+            &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
+        );
+        let potential_energy_at_force_radius = super::Force::calculate_pair_potential_energy(
+            &node_a,
+            &node_c,
+            &gravitational_constant,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
+        );
+
+        assert!((expected_potential_energy - potential_energy).abs() < 1e-6);
+        assert_eq!(0.0, potential_energy_at_force_radius);
+    }
+
+    #[test]
+    fn reaches_any_distance_without_a_force_radius() {
+        let position = Position::from((0.0, 0.0, 0.0));
+        let far_position = Position::from((10.0, 0.0, 0.0));
+        let limited = Force::between(
+            &position,
+            1.0,
+            &far_position,
+            100.0,
+            1.0,
+            &0.0,
+            Some(Force::FORCE_RADIUS),
+        );
+        let unlimited = Force::between(&position, 1.0, &far_position, 100.0, 1.0, &0.0, None);
+
+        assert_eq!(Force::zero(), limited);
+        assert_eq!(Force::from((1.0, 0.0, 0.0)), unlimited);
+    }
+
     #[rstest]
     #[case((1.0, 0.0, 0.0), 1.0)]
     #[case((-1.0, 0.0, 0.0), 1.0)]
     #[case((3.0, 0.0, 4.0), 5.0)]
-    fn has_magnitude(#[case] force: (f32, f32, f32), #[case] expected_magnitude: f32) {
+    fn has_magnitude(#[case] force: (Scalar, Scalar, Scalar), #[case] expected_magnitude: Scalar) {
         let force = Force::from(force);
         assert_eq!(expected_magnitude, force.magnitude())
     }
@@ -544,9 +823,9 @@ mod a_force {
 
     #[rstest]
     #[case("1.0,2.0", "Force must have 3 values")]
-    #[case("x,2.0,3.0", "Force vector x must be an f32")]
-    #[case("1.0,y,3.0", "Force vector y must be an f32")]
-    #[case("1.0,2.0,z", "Force vector z must be an f32")]
+    #[case("x,2.0,3.0", "Force x must be an f32")]
+    #[case("1.0,y,3.0", "Force y must be an f32")]
+    #[case("1.0,2.0,z", "Force z must be an f32")]
     fn cant_be_created_from_an_invalid_string(
         #[case] value: String,
         #[case] expected_error_message: String,
@@ -558,6 +837,23 @@ mod a_force {
             ),
             Err(err) => err,
         };
+        let expected_error_message =
+            expected_error_message.replace("f32", std::any::type_name::<Scalar>());
         assert_eq!(expected_error_message, error_message)
     }
+
+    #[test]
+    fn can_be_parsed_with_two_values_in_2d() {
+        assert_eq!(
+            Ok(Force::from((1.0, 0.0, -2.0))),
+            Force::parse(
+                "1.0,-2.0".to_string(),
+                &ParseContext {
+                    dimensionality: Dimensionality::TwoD,
+                    ..Default::default()
+                }
+            )
+        );
+        assert!(Force::parse("1.0,-2.0".to_string(), &ParseContext::default()).is_err());
+    }
 }
@@ -0,0 +1,210 @@
+use std::fmt;
+
+use cgmath::InnerSpace;
+
+use super::{consts, Force, Position, Scalar};
+
+/// A scripted path for a node to follow. A kinematic node ignores incoming forces, but still
+/// exerts force on other nodes.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Path {
+    /// Circles `centre` once every `period`, in the plane perpendicular to `normal`
+    Circle {
+        centre: Position,
+        radius: Scalar,
+        period: Scalar,
+        normal: cgmath::Vector3<Scalar>,
+    },
+    /// Moves in a straight line between each keyframe, reaching the next every `interval`, and
+    /// looping back to the first after the last
+    Keyframes {
+        positions: Vec<Position>,
+        interval: Scalar,
+    },
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Path::Circle {
+                centre,
+                radius,
+                period,
+                normal,
+            } => write!(
+                f,
+                "circle around ({}) with radius {}, period {} and normal ({})",
+                centre,
+                radius,
+                period,
+                Force(*normal)
+            ),
+            Path::Keyframes {
+                positions,
+                interval,
+            } => write!(
+                f,
+                "{} keyframes with interval {}",
+                positions.len(),
+                interval
+            ),
+        }
+    }
+}
+
+impl Path {
+    pub fn circle(
+        centre: Position,
+        radius: Scalar,
+        period: Scalar,
+        normal: cgmath::Vector3<Scalar>,
+    ) -> Result<Self, String> {
+        if period <= 0.0 {
+            return Err("Circle period must be greater than 0".to_string());
+        }
+        if normal.magnitude() == 0.0 {
+            return Err("Circle normal must not be zero".to_string());
+        }
+        Ok(Path::Circle {
+            centre,
+            radius,
+            period,
+            normal: normal.normalize(),
+        })
+    }
+
+    pub fn keyframes(positions: Vec<Position>, interval: Scalar) -> Result<Self, String> {
+        if positions.is_empty() {
+            return Err("Keyframes must contain at least one position".to_string());
+        }
+        if interval <= 0.0 {
+            return Err("Keyframe interval must be greater than 0".to_string());
+        }
+        Ok(Path::Keyframes {
+            positions,
+            interval,
+        })
+    }
+
+    /// Position and velocity along the path, `time` after the node started following it
+    pub fn evaluate(&self, time: Scalar) -> (Position, Force) {
+        match self {
+            Path::Circle {
+                centre,
+                radius,
+                period,
+                normal,
+            } => {
+                let (u, v) = Self::perpendicular_basis(*normal);
+                let angular_velocity = 2.0 * consts::PI / period;
+                let angle = angular_velocity * time;
+                let (sin, cos) = angle.sin_cos();
+                let position = centre.0 + (u * cos + v * sin) * *radius;
+                let velocity = (v * cos - u * sin) * (*radius * angular_velocity);
+                (Position(position), Force(velocity))
+            }
+            Path::Keyframes {
+                positions,
+                interval,
+            } => {
+                let frames = time.max(0.0) / interval;
+                let index = frames.floor() as usize % positions.len();
+                let next_index = (index + 1) % positions.len();
+                let progress = frames.fract();
+                let start = positions[index].0;
+                let end = positions[next_index].0;
+                let position = start + (end - start) * progress;
+                let velocity = (end - start) / *interval;
+                (Position(position), Force(velocity))
+            }
+        }
+    }
+
+    /// Two unit vectors perpendicular to eachother and to `normal`, such that `u x v = normal`
+    fn perpendicular_basis(
+        normal: cgmath::Vector3<Scalar>,
+    ) -> (cgmath::Vector3<Scalar>, cgmath::Vector3<Scalar>) {
+        let reference = match normal.x.abs() < 0.9 {
+            true => cgmath::Vector3::unit_x(),
+            false => cgmath::Vector3::unit_y(),
+        };
+        let u = (reference - normal * reference.dot(normal)).normalize();
+        let v = normal.cross(u);
+        (u, v)
+    }
+}
+
+/// A path being followed by a node
+#[derive(PartialEq, Debug, Clone)]
+pub struct Kinematic {
+    pub path: Path,
+    /// Simulation time at which the node started following the path
+    pub start_time: Scalar,
+}
+
+impl Kinematic {
+    pub fn evaluate(&self, time: Scalar) -> (Position, Force) {
+        self.path.evaluate(time - self.start_time)
+    }
+}
+
+#[cfg(test)]
+mod a_path {
+    use cgmath::EuclideanSpace;
+
+    use super::*;
+
+    fn assert_close(expected: cgmath::Vector3<Scalar>, actual: cgmath::Vector3<Scalar>) {
+        assert!(
+            (expected - actual).magnitude() < 1e-5,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn can_follow_a_circle() {
+        let centre = Position::from((1.0, 0.0, 0.0));
+        let path = Path::circle(centre, 2.0, 4.0, cgmath::Vector3::unit_y()).unwrap();
+
+        let (start, start_velocity) = path.evaluate(0.0);
+        let (quarter, _) = path.evaluate(1.0);
+        let (end, _) = path.evaluate(4.0);
+
+        assert_close((2.0, 0.0, 0.0).into(), start.0 - centre.0);
+        assert_close((0.0, 0.0, -2.0).into(), quarter.0 - centre.0);
+        assert_close(start.0 - centre.0, end.0 - centre.0);
+        // Speed is circumference / period
+        let expected_speed = 2.0 * consts::PI * 2.0 / 4.0;
+        assert!((expected_speed - start_velocity.magnitude()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn can_follow_keyframes() {
+        let positions = vec![
+            Position::from((0.0, 0.0, 0.0)),
+            Position::from((2.0, 0.0, 0.0)),
+            Position::from((2.0, 2.0, 0.0)),
+        ];
+        let path = Path::keyframes(positions, 2.0).unwrap();
+
+        let (halfway, velocity) = path.evaluate(1.0);
+        let (second_keyframe, _) = path.evaluate(2.0);
+        let (looping, _) = path.evaluate(5.0);
+
+        assert_close((1.0, 0.0, 0.0).into(), halfway.0.to_vec());
+        assert_close((1.0, 0.0, 0.0).into(), velocity.0);
+        assert_close((2.0, 0.0, 0.0).into(), second_keyframe.0.to_vec());
+        assert_close((1.0, 1.0, 0.0).into(), looping.0.to_vec());
+    }
+
+    #[test]
+    fn cant_be_created_with_invalid_arguments() {
+        let centre = Position::default();
+        assert!(Path::circle(centre, 1.0, 0.0, cgmath::Vector3::unit_y()).is_err());
+        assert!(Path::circle(centre, 1.0, 1.0, (0.0, 0.0, 0.0).into()).is_err());
+        assert!(Path::keyframes(vec![], 1.0).is_err());
+        assert!(Path::keyframes(vec![centre], 0.0).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+use cgmath::InnerSpace;
+
+use super::{consts, Scalar};
+
+/// Keplerian elements of an orbit around another body. Orbits with no inclination are in the XZ
+/// plane, going anticlockwise when viewed from above. Angles are in radians, with angles around the
+/// Y axis measured from the X axis.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct Elements {
+    /// Half of the longest diameter of the orbit. Negative for orbits that escape
+    pub semi_major_axis: Scalar,
+    /// 0 for a circular orbit, and between 0 and 1 for an ellipse
+    pub eccentricity: Scalar,
+    /// Tilt of the orbit from the XZ plane
+    pub inclination: Scalar,
+    /// Angle around the Y axis at which the orbit rises through the XZ plane
+    pub longitude_of_ascending_node: Scalar,
+    /// Angle from the ascending node to the closest point of the orbit
+    pub argument_of_periapsis: Scalar,
+    /// Angle from the closest point of the orbit to the body
+    pub true_anomaly: Scalar,
+}
+
+/// Below this eccentricity or inclination, orbits are treated as circular or flat, as their
+/// periapsis or ascending node can not be told apart
+const DEGENERATE_LIMIT: Scalar = 1.0e-6;
+
+impl Elements {
+    /// Creates the elements of a closed orbit
+    pub fn new(
+        semi_major_axis: Scalar,
+        eccentricity: Scalar,
+        inclination: Scalar,
+        longitude_of_ascending_node: Scalar,
+        argument_of_periapsis: Scalar,
+        true_anomaly: Scalar,
+    ) -> Result<Self, String> {
+        if semi_major_axis <= 0.0 {
+            return Err("Semi-major axis must be greater than 0".to_string());
+        }
+        if !(0.0..1.0).contains(&eccentricity) {
+            return Err("Eccentricity must be at least 0 and less than 1".to_string());
+        }
+        Ok(Self {
+            semi_major_axis,
+            eccentricity,
+            inclination,
+            longitude_of_ascending_node,
+            argument_of_periapsis,
+            true_anomaly,
+        })
+    }
+
+    /// Position and velocity of the body relative to the body it orbits. `gravitational_parameter`
+    /// is the gravitational constant multiplied by the mass of both bodies
+    pub fn state(
+        &self,
+        gravitational_parameter: Scalar,
+    ) -> (cgmath::Vector3<Scalar>, cgmath::Vector3<Scalar>) {
+        let e = self.eccentricity;
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let (sin_o, cos_o) = self.longitude_of_ascending_node.sin_cos();
+        let (sin_w, cos_w) = self.argument_of_periapsis.sin_cos();
+        // Angle from the ascending node to the body
+        let (sin_u, cos_u) = (self.argument_of_periapsis + self.true_anomaly).sin_cos();
+
+        let semi_latus_rectum = self.semi_major_axis * (1.0 - e.powi(2));
+        let r = semi_latus_rectum / (1.0 + e * self.true_anomaly.cos());
+        let position = frame(
+            r * (cos_o * cos_u - sin_o * sin_u * cos_i),
+            r * (sin_o * cos_u + cos_o * sin_u * cos_i),
+            r * sin_u * sin_i,
+        );
+
+        let h = (gravitational_parameter / semi_latus_rectum).sqrt();
+        let sin_term = sin_u + e * sin_w;
+        let cos_term = cos_u + e * cos_w;
+        let velocity = frame(
+            -h * (cos_o * sin_term + sin_o * cos_term * cos_i),
+            -h * (sin_o * sin_term - cos_o * cos_term * cos_i),
+            h * cos_term * sin_i,
+        );
+        (position, velocity)
+    }
+
+    /// The osculating elements of a body with `position` and `velocity` relative to the body it
+    /// orbits, which are the elements of the orbit it would follow if nothing else acted on it.
+    /// The ascending node of a flat orbit is taken to be along the X axis, and the periapsis of a
+    /// circular orbit to be at the ascending node. A body moving straight towards or away from the
+    /// body it orbits, or not moving at all, has no plane of orbit so gives an error
+    pub fn from_state(
+        position: cgmath::Vector3<Scalar>,
+        velocity: cgmath::Vector3<Scalar>,
+        gravitational_parameter: Scalar,
+    ) -> Result<Self, String> {
+        let mu = gravitational_parameter;
+        let r = position.magnitude();
+        let angular_momentum = position.cross(velocity);
+        // Compared to the size of the state, so that the limit does not depend on the units used
+        if angular_momentum.magnitude() <= DEGENERATE_LIMIT * r * velocity.magnitude() {
+            return Err(
+                "it is moving straight towards or away from the body it orbits".to_string(),
+            );
+        }
+        let normal = angular_momentum.normalize();
+        let eccentricity_vector =
+            (position * (velocity.magnitude2() - mu / r) - velocity * position.dot(velocity)) / mu;
+        let eccentricity = eccentricity_vector.magnitude();
+        let specific_energy = velocity.magnitude2() / 2.0 - mu / r;
+
+        let up = frame(0.0, 0.0, 1.0);
+        let inclination = normal.dot(up).clamp(-1.0, 1.0).acos();
+        let node_line = up.cross(normal);
+        let node_line = match node_line.magnitude() < DEGENERATE_LIMIT {
+            true => frame(1.0, 0.0, 0.0),
+            false => node_line.normalize(),
+        };
+        let longitude_of_ascending_node = angle(node_line, frame(1.0, 0.0, 0.0), up);
+
+        // Angles within the plane of the orbit, from the ascending node
+        let argument_of_periapsis = match eccentricity < DEGENERATE_LIMIT {
+            true => 0.0,
+            false => angle(eccentricity_vector, node_line, normal),
+        };
+        let argument_of_latitude = angle(position, node_line, normal);
+
+        Ok(Self {
+            semi_major_axis: -mu / (2.0 * specific_energy),
+            eccentricity,
+            inclination,
+            longitude_of_ascending_node,
+            argument_of_periapsis,
+            true_anomaly: wrap(argument_of_latitude - argument_of_periapsis),
+        })
+    }
+
+    /// Time taken to go once around a closed orbit, or `None` if the orbit escapes
+    pub fn period(&self, gravitational_parameter: Scalar) -> Option<Scalar> {
+        match self.eccentricity < 1.0 && self.semi_major_axis > 0.0 {
+            true => {
+                Some(consts::TAU * (self.semi_major_axis.powi(3) / gravitational_parameter).sqrt())
+            }
+            false => None,
+        }
+    }
+}
+
+/// Converts a vector from the reference frame of orbits, where the orbital plane is XY and Z is
+/// up, to the simulation, where the XZ plane is flat and Y is up
+fn frame(x: Scalar, y: Scalar, z: Scalar) -> cgmath::Vector3<Scalar> {
+    cgmath::Vector3::new(x, z, -y)
+}
+
+/// Angle of `vector` from `from`, anticlockwise around `normal`, between 0 and tau
+fn angle(
+    vector: cgmath::Vector3<Scalar>,
+    from: cgmath::Vector3<Scalar>,
+    normal: cgmath::Vector3<Scalar>,
+) -> Scalar {
+    let across = normal.cross(from);
+    wrap(vector.dot(across).atan2(vector.dot(from)))
+}
+
+/// Wraps `angle` to be at least 0 and less than tau
+fn wrap(angle: Scalar) -> Scalar {
+    // Tiny negative angles would otherwise round up to tau
+    match angle.rem_euclid(consts::TAU) {
+        angle if angle >= consts::TAU => 0.0,
+        angle => angle,
+    }
+}
+
+#[cfg(test)]
+mod an_orbit {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn assert_close(expected: Scalar, actual: Scalar) {
+        assert!(
+            (expected - actual).abs() < 1.0e-4,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn starts_a_circular_orbit_at_the_ascending_node() {
+        let elements = Elements::new(2.0, 0.0, 0.0, 0.0, 0.0, 0.0).unwrap();
+        let (position, velocity) = elements.state(2.0);
+        assert!((position - cgmath::Vector3::new(2.0, 0.0, 0.0)).magnitude() < 1.0e-6);
+        // Anticlockwise when viewed from above is towards -Z
+        assert!((velocity - cgmath::Vector3::new(0.0, 0.0, -1.0)).magnitude() < 1.0e-6);
+    }
+
+    #[test]
+    fn is_fastest_at_periapsis() {
+        let elements = Elements::new(1.0, 0.5, 0.0, 0.0, 0.0, 0.0).unwrap();
+        let (position, velocity) = elements.state(1.0);
+        assert_close(0.5, position.magnitude());
+        // Vis-viva equation, v^2 = mu * (2 / r - 1 / a)
+        assert_close((3.0 as Scalar).sqrt(), velocity.magnitude());
+    }
+
+    #[rstest]
+    #[case(Elements::new(2.0, 0.3, 0.5, 1.0, 2.0, 3.0))]
+    #[case(Elements::new(1.0, 0.1, 3.0, 6.0, 0.5, 0.2))]
+    #[case(Elements::new(5.0, 0.9, 1.5, 0.0, 4.0, 5.5))]
+    fn can_be_recovered_from_the_state_it_gives(#[case] elements: Result<Elements, String>) {
+        let elements = elements.unwrap();
+        let (position, velocity) = elements.state(3.0);
+        let recovered = Elements::from_state(position, velocity, 3.0).unwrap();
+        assert_close(elements.semi_major_axis, recovered.semi_major_axis);
+        assert_close(elements.eccentricity, recovered.eccentricity);
+        assert_close(elements.inclination, recovered.inclination);
+        assert_close(
+            elements.longitude_of_ascending_node,
+            recovered.longitude_of_ascending_node,
+        );
+        assert_close(
+            elements.argument_of_periapsis,
+            recovered.argument_of_periapsis,
+        );
+        assert_close(elements.true_anomaly, recovered.true_anomaly);
+    }
+
+    #[test]
+    fn measures_angles_of_flat_circular_orbits_from_the_x_axis() {
+        let elements = Elements::new(1.0, 0.0, 0.0, 0.0, 0.0, 1.0).unwrap();
+        let (position, velocity) = elements.state(1.0);
+        let recovered = Elements::from_state(position, velocity, 1.0).unwrap();
+        assert_close(0.0, recovered.longitude_of_ascending_node);
+        assert_close(0.0, recovered.argument_of_periapsis);
+        assert_close(1.0, recovered.true_anomaly);
+    }
+
+    #[test]
+    fn gives_a_negative_semi_major_axis_and_no_period_when_escaping() {
+        let position = cgmath::Vector3::new(1.0, 0.0, 0.0);
+        let velocity = cgmath::Vector3::new(0.0, 0.0, -2.0);
+        let elements = Elements::from_state(position, velocity, 1.0).unwrap();
+        assert!(elements.semi_major_axis < 0.0);
+        assert!(elements.eccentricity > 1.0);
+        assert_eq!(None, elements.period(1.0));
+        assert_close(
+            consts::TAU,
+            Elements::new(1.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+                .unwrap()
+                .period(1.0)
+                .unwrap(),
+        );
+    }
+
+    #[rstest]
+    #[case(0.0, 0.0, "Semi-major axis must be greater than 0")]
+    #[case(1.0, 1.0, "Eccentricity must be at least 0 and less than 1")]
+    #[case(1.0, -0.1, "Eccentricity must be at least 0 and less than 1")]
+    fn rejects_orbits_that_are_not_closed(
+        #[case] semi_major_axis: Scalar,
+        #[case] eccentricity: Scalar,
+        #[case] expected_error_message: &str,
+    ) {
+        assert_eq!(
+            Err(expected_error_message.to_string()),
+            Elements::new(semi_major_axis, eccentricity, 0.0, 0.0, 0.0, 0.0)
+        );
+    }
+
+    #[rstest]
+    #[case(cgmath::Vector3::new(-1.0, 0.0, 0.0))]
+    #[case(cgmath::Vector3::new(3.0, 0.0, 0.0))]
+    #[case(cgmath::Vector3::new(0.0, 0.0, 0.0))]
+    fn gives_an_error_for_radial_motion(#[case] velocity: cgmath::Vector3<Scalar>) {
+        let position = cgmath::Vector3::new(2.0, 0.0, 0.0);
+
+        assert!(Elements::from_state(position, velocity, 1.0).is_err());
+    }
+}
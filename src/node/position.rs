@@ -1,7 +1,15 @@
 use std::{fmt, ops::Add};
 
+use cgmath::EuclideanSpace;
+
+use super::{
+    vector::{ParseContext, Vector},
+    Scalar,
+};
+use crate::simulation::units::Quantity;
+
 #[derive(PartialEq, Debug, Clone, Copy)]
-pub struct Position(pub cgmath::Point3<f32>);
+pub struct Position(pub cgmath::Point3<Scalar>);
 
 impl fmt::Display for Position {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -19,21 +27,21 @@ impl Default for Position {
     }
 }
 
-impl From<(f32, f32, f32)> for Position {
-    fn from(value: (f32, f32, f32)) -> Self {
+impl From<(Scalar, Scalar, Scalar)> for Position {
+    fn from(value: (Scalar, Scalar, Scalar)) -> Self {
         let (x, y, z) = value;
         Self::from(cgmath::Point3 { x, y, z })
     }
 }
 
-impl From<cgmath::Point3<f32>> for Position {
-    fn from(value: cgmath::Point3<f32>) -> Self {
+impl From<cgmath::Point3<Scalar>> for Position {
+    fn from(value: cgmath::Point3<Scalar>) -> Self {
         Self(value)
     }
 }
 
-impl From<&cgmath::Point3<f32>> for Position {
-    fn from(value: &cgmath::Point3<f32>) -> Self {
+impl From<&cgmath::Point3<Scalar>> for Position {
+    fn from(value: &cgmath::Point3<Scalar>) -> Self {
         Self(*value)
     }
 }
@@ -42,56 +50,33 @@ impl TryFrom<String> for Position {
     type Error = String;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let pos_string = value.trim_matches('"').split(',');
-        let positions: Vec<Result<f32, _>> = pos_string.map(|s| s.trim().parse::<f32>()).collect();
-        if positions.len() != 3 {
-            return Err("Position must have 3 values".to_string());
-        }
-        let x = match &positions[0] {
-            Ok(number) => *number,
-            Err(_) => {
-                return Err("Position x must be an f32".to_string());
-            }
-        };
-        let y = match &positions[1] {
-            Ok(number) => *number,
-            Err(_) => {
-                return Err("Position y must be an f32".to_string());
-            }
-        };
-        let z = match &positions[2] {
-            Ok(number) => *number,
-            Err(_) => {
-                return Err("Position z must be an f32".to_string());
-            }
-        };
-        Ok(Self(cgmath::Point3 { x, y, z }))
+        Self::parse(value, &ParseContext::default())
     }
 }
 
-impl Into<(f32, f32, f32)> for Position {
-    fn into(self) -> (f32, f32, f32) {
+impl Into<(Scalar, Scalar, Scalar)> for Position {
+    fn into(self) -> (Scalar, Scalar, Scalar) {
         self.0.into()
     }
 }
 
-impl Into<cgmath::Point3<f32>> for Position {
-    fn into(self) -> cgmath::Point3<f32> {
+impl Into<cgmath::Point3<Scalar>> for Position {
+    fn into(self) -> cgmath::Point3<Scalar> {
         self.0.into()
     }
 }
 
-impl Into<cgmath::Point3<f32>> for &Position {
-    fn into(self) -> cgmath::Point3<f32> {
+impl Into<cgmath::Point3<Scalar>> for &Position {
+    fn into(self) -> cgmath::Point3<Scalar> {
         self.0.into()
     }
 }
 
-impl Add<cgmath::Vector3<f32>> for Position {
+impl Add<cgmath::Vector3<Scalar>> for Position {
     type Output = Self;
 
-    fn add(self, rhs: cgmath::Vector3<f32>) -> Self::Output {
-        let point_3 = Into::<cgmath::Point3<f32>>::into(self) + rhs;
+    fn add(self, rhs: cgmath::Vector3<Scalar>) -> Self::Output {
+        let point_3 = Into::<cgmath::Point3<Scalar>>::into(self) + rhs;
         Self::from(point_3)
     }
 }
@@ -101,32 +86,41 @@ impl Add<super::Force> for Position {
 
     fn add(self, rhs: super::Force) -> Self::Output {
         let point_3 =
-            Into::<cgmath::Point3<f32>>::into(self) + Into::<cgmath::Vector3<f32>>::into(rhs);
+            Into::<cgmath::Point3<Scalar>>::into(self) + Into::<cgmath::Vector3<Scalar>>::into(rhs);
         Self::from(point_3)
     }
 }
 
 impl Position {
-    pub fn distance_to(&self, other: &Position) -> cgmath::Vector3<f32> {
+    /// Parses a position written in any of the forms of [`Vector`], which can not be relative to a
+    /// node
+    pub fn parse(value: String, context: &ParseContext) -> Result<Self, String> {
+        let offset =
+            Vector::parse(&value, "Position", Quantity::Length, context)?.absolute("Position")?;
+        Ok(Self(cgmath::Point3::from_vec(offset)))
+    }
+
+    pub fn distance_to(&self, other: &Position) -> cgmath::Vector3<Scalar> {
         (self.0 - other.0).map(|n| n.abs())
     }
 
-    pub fn displacement(&self, other: &Position) -> cgmath::Vector3<f32> {
-        let other_point = Into::<cgmath::Point3<f32>>::into(other);
-        let self_point = Into::<cgmath::Point3<f32>>::into(self);
+    pub fn displacement(&self, other: &Position) -> cgmath::Vector3<Scalar> {
+        let other_point = Into::<cgmath::Point3<Scalar>>::into(other);
+        let self_point = Into::<cgmath::Point3<Scalar>>::into(self);
         other_point - self_point
     }
 }
 
 #[cfg(test)]
 mod a_position {
-    use crate::node::{Force, Position};
+    use crate::node::{vector::ParseContext, Force, Position, Scalar};
+    use crate::simulation::dimensionality::Dimensionality;
     use rstest::rstest;
 
     #[test]
     fn default_is_0_0_0() {
         assert_eq!(
-            cgmath::Point3::<f32>::new(0.0, 0.0, 0.0),
+            cgmath::Point3::<Scalar>::new(0.0, 0.0, 0.0),
             Position::default().0
         )
     }
@@ -139,16 +133,16 @@ mod a_position {
     #[case((1.0, 0.0, 0.0), (-1.0, 0.0, 0.0), (2.0, 0.0, 0.0))]
     #[case((-1.0, 0.0, 0.0), (1.0, 0.0, 0.0), (2.0, 0.0, 0.0))]
     fn can_calculate_distance_to_another_position(
-        #[case] position: (f32, f32, f32),
-        #[case] other: (f32, f32, f32),
-        #[case] expected_distance: (f32, f32, f32),
+        #[case] position: (Scalar, Scalar, Scalar),
+        #[case] other: (Scalar, Scalar, Scalar),
+        #[case] expected_distance: (Scalar, Scalar, Scalar),
     ) {
         let (x, y, z) = position;
         let position = Position(cgmath::Point3 { x, y, z });
         let (x, y, z) = other;
         let other = Position(cgmath::Point3 { x, y, z });
         let (x, y, z) = expected_distance;
-        let expected_distance = cgmath::Vector3::<f32>::new(x, y, z);
+        let expected_distance = cgmath::Vector3::<Scalar>::new(x, y, z);
         assert_eq!(position.distance_to(&other), expected_distance);
     }
 
@@ -309,6 +303,36 @@ mod a_position {
             ),
             Err(err) => err,
         };
+        let expected_error_message =
+            expected_error_message.replace("f32", std::any::type_name::<Scalar>());
         assert_eq!(expected_error_message, error_message)
     }
+
+    #[cfg(feature = "f64")]
+    #[test]
+    fn keeps_precision_far_from_the_origin() {
+        let position = Position::from((1.0e8, 0.0, 0.0)) + cgmath::Vector3::new(1.0, 0.0, 0.0);
+        assert_eq!(1.0e8 + 1.0, position.0.x)
+    }
+
+    #[rstest]
+    #[case("1.0,2.0", Dimensionality::TwoD, Ok(Position::from((1.0, 0.0, 2.0))))]
+    #[case("1.0,2.0,3.0", Dimensionality::TwoD, Ok(Position::from((1.0, 2.0, 3.0))))]
+    #[case("1.0,2.0", Dimensionality::ThreeD, Err("Position must have 3 values".to_string()))]
+    fn can_be_parsed_with_two_values_in_2d(
+        #[case] value: String,
+        #[case] dimensionality: Dimensionality,
+        #[case] expected_result: Result<Position, String>,
+    ) {
+        assert_eq!(
+            expected_result,
+            Position::parse(
+                value,
+                &ParseContext {
+                    dimensionality,
+                    ..Default::default()
+                }
+            )
+        )
+    }
 }
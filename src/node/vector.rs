@@ -0,0 +1,349 @@
+use cgmath::Zero;
+
+use super::{Id, Scalar};
+use crate::simulation::{
+    dimensionality::Dimensionality,
+    units::{Quantity, UnitSystem},
+};
+
+/// What vectors are parsed against. Vectors are parsed before their events reach the simulation,
+/// so this needs to be kept as the simulation will be when they are handled
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct ParseContext {
+    pub dimensionality: Dimensionality,
+    pub units: UnitSystem,
+}
+
+/// A vector parsed from an argument. These can be written as
+///
+/// - "x,y,z", "(x,y,z)" or "[x,y,z]", or with two components "x,z" if the simulation is 2D
+/// - "sph(r,theta,phi)", where theta is the angle from the Y axis, and phi is the angle around it
+///   from the X axis towards the Z axis
+/// - "cyl(r,phi,y)", with phi being the angle around the Y axis from the X axis towards the Z axis
+/// - "node(id)", for the position or velocity of a node when the vector is used
+///
+/// Any of these can be added or subtracted, ie. "node(1) + sph(2km, 90, 45)". Values can be
+/// followed by a unit such as "km", "AU", "km/s" or "rad", which is converted into the units of
+/// the simulation.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Vector {
+    pub offset: cgmath::Vector3<Scalar>,
+    /// Node whose position or velocity `offset` is from, when the vector is used
+    pub relative_to: Option<Id>,
+}
+
+impl Default for Vector {
+    fn default() -> Self {
+        Self {
+            offset: cgmath::Vector3::zero(),
+            relative_to: None,
+        }
+    }
+}
+
+impl ParseContext {
+    /// Parses a single value measuring `quantity`, which may be followed by a unit such as "km" or
+    /// "deg". `name` describes the value in errors
+    pub fn scalar(&self, value: &str, name: &str, quantity: Quantity) -> Result<Scalar, String> {
+        let value = value.trim();
+        let unit_start = value
+            .trim_end_matches(|char: char| char.is_alphabetic() || char == '/')
+            .len();
+        let (number, unit) = value.split_at(unit_start);
+        let number = number
+            .trim()
+            .parse::<Scalar>()
+            .map_err(|_| format!("{} must be an {}", name, std::any::type_name::<Scalar>()))?;
+        self.units.convert(number, unit, quantity)
+    }
+}
+
+impl Vector {
+    /// Parses `value` as a vector measuring `quantity`. `name` describes the vector in errors
+    pub fn parse(
+        value: &str,
+        name: &str,
+        quantity: Quantity,
+        context: &ParseContext,
+    ) -> Result<Self, String> {
+        let value = value.trim().trim_matches('"').trim();
+        let mut parser = Parser {
+            name,
+            quantity,
+            context,
+            rest: value,
+        };
+        // Plain components are kept to their own parsing, so a leading "-" is part of a value
+        if !value.contains(['(', '[']) {
+            return Ok(Self {
+                offset: parser.components(value.split(',').collect())?,
+                relative_to: None,
+            });
+        }
+        parser.expression()
+    }
+
+    /// Gives the offset of a vector that is not relative to a node
+    pub fn absolute(self, name: &str) -> Result<cgmath::Vector3<Scalar>, String> {
+        match self.relative_to {
+            Some(_) => Err(format!("{} can not be relative to a node", name)),
+            None => Ok(self.offset),
+        }
+    }
+}
+
+struct Parser<'a> {
+    name: &'a str,
+    quantity: Quantity,
+    context: &'a ParseContext,
+    /// What is left to parse
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn expression(&mut self) -> Result<Vector, String> {
+        let mut vector = Vector::default();
+        let mut sign = match self.eat('-') {
+            true => -1.0,
+            false => {
+                self.eat('+');
+                1.0
+            }
+        };
+        loop {
+            self.term(sign, &mut vector)?;
+            sign = if self.eat('+') {
+                1.0
+            } else if self.eat('-') {
+                -1.0
+            } else {
+                break;
+            };
+        }
+        match self.rest.is_empty() {
+            true => Ok(vector),
+            false => Err(format!("Unexpected '{}' in {}", self.rest, self.name)),
+        }
+    }
+
+    /// Adds the next term to `vector`, after multiplying it by `sign`
+    fn term(&mut self, sign: Scalar, vector: &mut Vector) -> Result<(), String> {
+        self.rest = self.rest.trim_start();
+        let function_end = self
+            .rest
+            .find(|char: char| !char.is_ascii_alphabetic())
+            .unwrap_or(self.rest.len());
+        let (function, rest) = self.rest.split_at(function_end);
+        self.rest = rest;
+        let close = match (function, self.eat('('), self.eat('[')) {
+            (_, true, _) => ')',
+            ("", _, true) => ']',
+            _ => return Err(format!("Expected a vector in {}", self.name)),
+        };
+        let Some((arguments, rest)) = self.rest.split_once(close) else {
+            return Err(format!("Missing closing '{}' in {}", close, self.name));
+        };
+        self.rest = rest;
+        let arguments: Vec<&str> = arguments.split(',').collect();
+
+        let offset = match function {
+            "" => self.components(arguments)?,
+            "sph" => {
+                let [radius, theta, phi] = self.arguments(function, &arguments)?;
+                let radius = self.scalar(radius, self.quantity, "radius")?;
+                let theta = self.scalar(theta, Quantity::Angle, "polar angle")?;
+                let phi = self.scalar(phi, Quantity::Angle, "azimuth")?;
+                cgmath::Vector3::new(
+                    radius * theta.sin() * phi.cos(),
+                    radius * theta.cos(),
+                    radius * theta.sin() * phi.sin(),
+                )
+            }
+            "cyl" => {
+                let [radius, phi, y] = self.arguments(function, &arguments)?;
+                let radius = self.scalar(radius, self.quantity, "radius")?;
+                let phi = self.scalar(phi, Quantity::Angle, "azimuth")?;
+                let y = self.scalar(y, self.quantity, "height")?;
+                cgmath::Vector3::new(radius * phi.cos(), y, radius * phi.sin())
+            }
+            "node" => {
+                let id = match arguments[..] {
+                    [id] => id.trim().parse::<u32>().map_err(|_| {
+                        format!("Node id in {} must be a positive integer", self.name)
+                    })?,
+                    _ => return Err(format!("node() in {} takes one id", self.name)),
+                };
+                if sign < 0.0 || vector.relative_to.is_some() {
+                    return Err(format!(
+                        "{} can only be relative to one node, which is added",
+                        self.name
+                    ));
+                }
+                vector.relative_to = Some(Id(id));
+                return Ok(());
+            }
+            _ => return Err(format!("Unknown function '{}' in {}", function, self.name)),
+        };
+        vector.offset += offset * sign;
+        Ok(())
+    }
+
+    fn arguments<'b>(&self, function: &str, arguments: &[&'b str]) -> Result<[&'b str; 3], String> {
+        <[&str; 3]>::try_from(arguments)
+            .map_err(|_| format!("{}() in {} takes 3 values", function, self.name))
+    }
+
+    /// Parses the components of a vector, which may be "x,z" if the simulation is 2D
+    fn components(&self, components: Vec<&str>) -> Result<cgmath::Vector3<Scalar>, String> {
+        let components = self
+            .context
+            .dimensionality
+            .expand_components(components, self.name)?;
+        let [x, y, z] = <[&str; 3]>::try_from(components)
+            .map_err(|_| format!("{} must have 3 values", self.name))?;
+        Ok(cgmath::Vector3::new(
+            self.scalar(x, self.quantity, "x")?,
+            self.scalar(y, self.quantity, "y")?,
+            self.scalar(z, self.quantity, "z")?,
+        ))
+    }
+
+    /// Parses a number, which may be followed by a unit
+    fn scalar(&self, value: &str, quantity: Quantity, description: &str) -> Result<Scalar, String> {
+        self.context
+            .scalar(value, &format!("{} {}", self.name, description), quantity)
+    }
+
+    /// Skips whitespace, then `char` if it is next
+    fn eat(&mut self, char: char) -> bool {
+        self.rest = self.rest.trim_start();
+        match self.rest.strip_prefix(char) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod a_vector {
+    use cgmath::InnerSpace;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::simulation::units::{Unit, Units};
+
+    fn parse(value: &str) -> Result<Vector, String> {
+        Vector::parse(
+            value,
+            "Position",
+            Quantity::Length,
+            &ParseContext::default(),
+        )
+    }
+
+    fn offset(x: Scalar, y: Scalar, z: Scalar) -> Result<Vector, String> {
+        Ok(Vector {
+            offset: cgmath::Vector3::new(x, y, z),
+            relative_to: None,
+        })
+    }
+
+    #[rstest]
+    #[case("1,2,3", offset(1.0, 2.0, 3.0))]
+    #[case("-1, 2, 3", offset(-1.0, 2.0, 3.0))]
+    #[case("(1, 2, 3)", offset(1.0, 2.0, 3.0))]
+    #[case("[1,2,3]", offset(1.0, 2.0, 3.0))]
+    #[case("-(1,2,3)", offset(-1.0, -2.0, -3.0))]
+    #[case("(1,2,3) - [1,1,1] + (0,0,2)", offset(0.0, 1.0, 4.0))]
+    #[case("(1e3,0,0)", offset(1000.0, 0.0, 0.0))]
+    #[case("cyl(2, 0, 5)", offset(2.0, 5.0, 0.0))]
+    fn can_be_parsed_from_a_valid_string(
+        #[case] value: &str,
+        #[case] expected_result: Result<Vector, String>,
+    ) {
+        assert_eq!(expected_result, parse(value))
+    }
+
+    #[test]
+    fn can_be_given_in_spherical_coordinates() {
+        let vector = parse("sph(2, 90, 90)").unwrap();
+        assert!((vector.offset - cgmath::Vector3::new(0.0, 0.0, 2.0)).magnitude() < 1.0e-5);
+        let vector = parse("sph(2, 0.5rad, 0)").unwrap();
+        let expected = cgmath::Vector3::new(
+            2.0 * (0.5 as Scalar).sin(),
+            2.0 * (0.5 as Scalar).cos(),
+            0.0,
+        );
+        assert!((vector.offset - expected).magnitude() < 1.0e-5);
+    }
+
+    #[test]
+    fn can_be_relative_to_a_node() {
+        assert_eq!(
+            Ok(Vector {
+                offset: cgmath::Vector3::new(2.0, 0.0, 0.0),
+                relative_to: Some(Id(1)),
+            }),
+            parse("node(1) + (2,0,0)")
+        );
+        assert!(parse("node(1) + (2,0,0)")
+            .unwrap()
+            .absolute("Position")
+            .is_err());
+    }
+
+    #[rstest]
+    #[case("(1,2)", "Position must have 3 values")]
+    #[case("(1,2,3", "Missing closing ')' in Position")]
+    #[case("(1,2,3) 4", "Unexpected '4' in Position")]
+    #[case("(1,y,3)", "Position y must be an f32")]
+    #[case("(1ly,0,0)", "Unknown unit of length 'ly'")]
+    #[case(
+        "(1km,0,0)",
+        "Can not use 'km' as the simulation is dimensionless - set its units first"
+    )]
+    #[case("sph(1,2)", "sph() in Position takes 3 values")]
+    #[case("pol(1,2,3)", "Unknown function 'pol' in Position")]
+    #[case(
+        "node(1) + node(2)",
+        "Position can only be relative to one node, which is added"
+    )]
+    #[case(
+        "(1,2,3) - node(2)",
+        "Position can only be relative to one node, which is added"
+    )]
+    fn cant_be_parsed_from_an_invalid_string(
+        #[case] value: &str,
+        #[case] expected_error_message: String,
+    ) {
+        let expected_error_message =
+            expected_error_message.replace("f32", std::any::type_name::<Scalar>());
+        assert_eq!(Err(expected_error_message), parse(value))
+    }
+
+    #[test]
+    fn converts_units_with_the_unit_system() {
+        let context = ParseContext {
+            dimensionality: Dimensionality::TwoD,
+            units: UnitSystem::Physical(Units {
+                length: Unit::length("km").unwrap(),
+                ..Units::SI
+            }),
+        };
+        assert_eq!(
+            offset(2.0, 0.0, 0.5),
+            Vector::parse("(2km, 500m)", "Position", Quantity::Length, &context)
+        );
+        assert_eq!(
+            offset(2.0, 0.0, -0.003),
+            Vector::parse("2km,0,-3m", "Position", Quantity::Length, &context)
+        );
+        assert_eq!(
+            offset(0.0, 0.0, 3.0),
+            Vector::parse("0,0,3km/s", "Force", Quantity::Velocity, &context)
+        );
+    }
+}
@@ -1,92 +1,966 @@
+pub mod adaptive_step;
+pub mod collision;
+pub mod dimensionality;
+pub mod emitter;
+pub mod node_store;
+pub mod random;
+pub mod schedule;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod sink;
+pub mod stats;
+pub mod units;
+
+use std::collections::HashMap;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use cgmath::{EuclideanSpace, InnerSpace};
+
 use super::node;
 
 #[derive(Clone)]
 pub struct Simulation {
-    pub nodes: Vec<node::Node>,
+    pub nodes: node_store::NodeStore,
     target_tps: u32,
-    pub gravitational_constant: f32,
+    pub gravitational_constant: node::Scalar,
+    /// Plummer softening length, used to keep forces between nearby nodes bounded.
+    /// 0 is no softening.
+    pub softening_length: node::Scalar,
+    /// Distance beyond which nodes do not interact, if any. Set by the units of the simulation
+    pub force_radius: Option<node::Scalar>,
+    pub pair_force_mode: node::force::PairForceMode,
+    pub drag: node::drag::Drag,
+    /// Simulated time that passes each step
+    pub time_step: node::Scalar,
+    /// Real world units values are measured in, which decide the gravitational constant
+    pub units: units::UnitSystem,
+    /// How each step is split into sub-steps
+    pub adaptive_step: adaptive_step::AdaptiveStep,
+    pub stats_log: Option<stats::StatsLog>,
+    /// Paths followed by kinematic nodes, which move independently of incoming forces
+    pub kinematics: HashMap<node::Id, node::kinematic::Kinematic>,
+    pub emitters: Vec<emitter::Emitter>,
+    pub sinks: Vec<sink::Sink>,
+    pub collision_policy: collision::CollisionPolicy,
+    /// Events waiting to be handled at a later tick or time
+    pub schedule: schedule::Schedule,
+    /// Scripts run at the start of every step
+    #[cfg(feature = "scripting")]
+    pub scripting: scripting::ScriptHost,
+    /// Nodes that merged during the last step
+    merges: Vec<collision::Merge>,
+    /// Lowest id above every id that has been given to a node
+    next_id: u32,
+    dimensionality: dimensionality::Dimensionality,
+    tick: u64,
+    time: node::Scalar,
 }
 
 impl Simulation {
     pub fn new() -> Simulation {
-        let nodes = Vec::new();
+        let nodes = node_store::NodeStore::new();
         Simulation {
             nodes,
             target_tps: 60,
-            gravitational_constant: -1.0,
+            gravitational_constant: units::UnitSystem::default().gravitational_constant(),
+            softening_length: 0.0,
+            force_radius: units::UnitSystem::default().force_radius(),
+            pair_force_mode: node::force::PairForceMode::default(),
+            drag: node::drag::Drag::default(),
+            time_step: 1.0,
+            units: units::UnitSystem::default(),
+            adaptive_step: adaptive_step::AdaptiveStep::default(),
+            stats_log: None,
+            kinematics: HashMap::new(),
+            emitters: Vec::new(),
+            sinks: Vec::new(),
+            collision_policy: collision::CollisionPolicy::default(),
+            schedule: schedule::Schedule::new(),
+            #[cfg(feature = "scripting")]
+            scripting: scripting::ScriptHost::new(),
+            merges: Vec::new(),
+            next_id: 0,
+            dimensionality: dimensionality::Dimensionality::default(),
+            tick: 0,
+            time: 0.0,
         }
     }
 
-    pub fn add_node(&mut self, node: node::Node) {
+    pub fn add_node(&mut self, mut node: node::Node) {
+        self.dimensionality
+            .apply(&mut node.position, &mut node.velocity);
+        self.next_id = self.next_id.max(node.id.0.saturating_add(1));
         self.nodes.push(node)
     }
 
+    /// An id that has not been given to any node, for nodes created by the simulation itself
+    pub fn allocate_id(&mut self) -> node::Id {
+        let id = node::Id(self.next_id);
+        self.next_id = self.next_id.saturating_add(1);
+        id
+    }
+
     pub fn remove_node(&mut self, id: node::Id) {
-        self.nodes.retain(|node| node.id != id)
+        self.nodes.retain(|node| node.id != id);
+        self.kinematics.remove(&id);
     }
 
     pub fn step(&mut self) {
-        let nodes = self.nodes.clone();
+        self.step_by(self.time_step)
+    }
+
+    /// Steps the simulation forward by `time_step`, regardless of the time step it is set to
+    pub fn step_by(&mut self, time_step: node::Scalar) {
+        for event in self.schedule.take_due(self.tick, self.time) {
+            if let Err(err) = self.handle_event(event) {
+                println!("{}", err);
+            }
+        }
+        #[cfg(feature = "scripting")]
+        self.run_hooks();
+        match self.adaptive_step.mode {
+            adaptive_step::AdaptiveStepMode::Fixed => {
+                let forces = self.calculate_forces(&self.nodes);
+                self.advance(time_step, &forces);
+            }
+            adaptive_step::AdaptiveStepMode::Acceleration
+            | adaptive_step::AdaptiveStepMode::ErrorEstimate => self.step_adaptively(time_step),
+            adaptive_step::AdaptiveStepMode::Block => self.step_in_blocks(time_step),
+        }
+        self.dampen(time_step);
+        self.collide();
+        self.absorb_into_sinks();
+        self.emit_nodes(time_step);
+
+        self.tick += 1;
+        self.record_stats();
+    }
+
+    /// Nodes that have merged since the merges were last taken, including over several steps run
+    /// by a single event
+    pub fn merges(&self) -> &[collision::Merge] {
+        &self.merges
+    }
+
+    /// Gives the nodes that have merged since the merges were last taken, so that they can be
+    /// reported. Merges build up until they are taken
+    pub fn take_merges(&mut self) -> Vec<collision::Merge> {
+        std::mem::take(&mut self.merges)
+    }
+
+    fn collide(&mut self) {
+        match self.collision_policy {
+            collision::CollisionPolicy::None => {}
+            collision::CollisionPolicy::Merge => self.merge_colliding_nodes(),
+        }
+    }
+
+    /// Merges every pair of overlapping nodes. Merged nodes can go on to merge with further nodes
+    /// in the same step. Kinematic nodes follow their paths, so are never merged.
+    fn merge_colliding_nodes(&mut self) {
+        let mut nodes: Vec<node::Node> = self.nodes.iter().collect();
+        let mut removed = vec![false; nodes.len()];
+        for i in 0..nodes.len() {
+            if removed[i] || self.kinematics.contains_key(&nodes[i].id) {
+                continue;
+            }
+            for j in (i + 1)..nodes.len() {
+                if removed[j]
+                    || self.kinematics.contains_key(&nodes[j].id)
+                    || !nodes[i].overlaps(&nodes[j])
+                {
+                    continue;
+                }
+                let merged = nodes[i].merge(&nodes[j]);
+                let removed_id = match merged.id == nodes[i].id {
+                    true => nodes[j].id,
+                    false => nodes[i].id,
+                };
+                let merge = collision::Merge {
+                    survivor: merged.id,
+                    removed: removed_id,
+                };
+                self.merges.push(merge);
+                nodes[i] = merged;
+                removed[j] = true;
+            }
+        }
+        if !removed.contains(&true) {
+            return;
+        }
+        self.nodes = nodes
+            .into_iter()
+            .zip(removed)
+            .filter(|(_, removed)| !removed)
+            .map(|(node, _)| node)
+            .collect();
+    }
+
+    /// Removes every node that has entered a sink
+    fn absorb_into_sinks(&mut self) {
+        if self.sinks.is_empty() {
+            return;
+        }
+        let absorbed: Vec<node::Id> = self
+            .nodes
+            .iter()
+            .filter(|node| {
+                self.sinks
+                    .iter_mut()
+                    .any(|sink| sink.absorb(&node.position))
+            })
+            .map(|node| node.id)
+            .collect();
+        if absorbed.is_empty() {
+            return;
+        }
+        self.nodes.retain(|node| !absorbed.contains(&node.id));
+        for id in absorbed {
+            self.kinematics.remove(&id);
+        }
+    }
+
+    fn emit_nodes(&mut self, time_step: node::Scalar) {
+        let mut emitters = std::mem::take(&mut self.emitters);
+        for emitter in emitters.iter_mut() {
+            for node in emitter.emit(time_step, || self.allocate_id()) {
+                self.add_node(node);
+            }
+        }
+        self.emitters = emitters;
+    }
+
+    pub fn add_emitter(&mut self, emitter: emitter::Emitter) -> Result<(), String> {
+        match self.emitters.iter().any(|other| other.id == emitter.id) {
+            true => return Err(format!("An emitter with id {} already exists", emitter.id)),
+            false => self.emitters.push(emitter),
+        }
+        Ok(())
+    }
+
+    pub fn remove_emitter(&mut self, id: u32) {
+        self.emitters.retain(|emitter| emitter.id != id);
+    }
+
+    #[cfg(feature = "scripting")]
+    pub fn add_hook(&mut self, event: node::AddHookEvent) -> Result<(), String> {
+        // The host is taken out of the simulation while the script runs against it
+        let mut scripting = std::mem::take(&mut self.scripting);
+        let result = scripting
+            .add_hook(event.id, event.name, &event.source, event.budget, self)
+            .map_err(|err| format!("Error adding hook {} - {}", event.id, err));
+        self.scripting = scripting;
+        result
+    }
+
+    #[cfg(feature = "scripting")]
+    pub fn remove_hook(&mut self, id: u32) -> Result<(), String> {
+        match self.scripting.remove_hook(id) {
+            true => Ok(()),
+            false => Err(format!(
+                "Error removing hook {} - no hook with that id exists",
+                id
+            )),
+        }
+    }
+
+    #[cfg(feature = "scripting")]
+    fn run_hooks(&mut self) {
+        if self.scripting.is_empty() {
+            return;
+        }
+        let mut scripting = std::mem::take(&mut self.scripting);
+        scripting.run_tick_hooks(self);
+        self.scripting = scripting;
+    }
+
+    pub fn add_sink(&mut self, sink: sink::Sink) -> Result<(), String> {
+        match self.sinks.iter().any(|other| other.id == sink.id) {
+            true => return Err(format!("A sink with id {} already exists", sink.id)),
+            false => self.sinks.push(sink),
+        }
+        Ok(())
+    }
+
+    pub fn remove_sink(&mut self, id: u32) {
+        self.sinks.retain(|sink| sink.id != id);
+    }
+
+    /// Covers the time step with sub-steps chosen by the adaptive step mode. The last sub-step is
+    /// shortened so that the step ends exactly one time step later. Under the error estimate mode
+    /// the accepted trial sub-step is kept, and the forces found at its end start the next one.
+    fn step_adaptively(&mut self, time_step: node::Scalar) {
+        let mut remaining = time_step;
+        let mut end_forces = None;
+        while remaining > 0.0 {
+            let forces = end_forces
+                .take()
+                .unwrap_or_else(|| self.calculate_forces(&self.nodes));
+            let mut adaptive_step = self.adaptive_step;
+            let time_step = match adaptive_step.mode {
+                adaptive_step::AdaptiveStepMode::ErrorEstimate => {
+                    let mut trial = None;
+                    let time_step =
+                        adaptive_step.choose_from_error_estimate(remaining, |time_step| {
+                            let (error, nodes, forces) = self.try_sub_step(&forces, time_step);
+                            trial = Some((nodes, forces));
+                            error
+                        });
+                    let (nodes, forces) = trial.expect("The accepted sub-step was tried last");
+                    self.time += time_step;
+                    self.nodes = nodes;
+                    end_forces = Some(forces);
+                    time_step
+                }
+                _ => {
+                    let time_step = adaptive_step
+                        .choose_from_acceleration(self.max_acceleration(&forces), remaining);
+                    self.advance(time_step, &forces);
+                    time_step
+                }
+            };
+            self.adaptive_step = adaptive_step;
+            remaining -= time_step;
+        }
+    }
+
+    /// Gives every node a sub-step of a power of two fraction of `time_step`, chosen from its own
+    /// acceleration at the start of the step. A node is only kicked and moved at the start of each
+    /// of its own sub-steps, using the forces on it at that time, so nodes on coarse levels are left
+    /// alone while the shortest sub-steps are taken. Forces are only calculated for the nodes
+    /// starting a sub-step, so a few quickly accelerating nodes do not slow the rest of the
+    /// simulation down, though every sub-step still goes over each node to find them.
+    ///
+    /// Nodes starting a sub-step are kicked by nodes that are not, which are not kicked back until
+    /// their own sub-step starts, so momentum is not conserved even with symmetric pair forces.
+    fn step_in_blocks(&mut self, time_step: node::Scalar) {
+        let mut forces = self.calculate_forces(&self.nodes);
+        let masses = self.nodes.masses();
+        let accelerations: Vec<node::Scalar> = (0..forces.len())
+            .map(|index| match self.responds_to_force(index) {
+                true => forces[index].magnitude() / masses[index],
+                false => 0.0,
+            })
+            .collect();
+        let levels = self
+            .adaptive_step
+            .choose_block_levels(&accelerations, time_step);
+        let max_level = levels.iter().copied().max().unwrap_or(0);
+        let sub_step = self.adaptive_step.time_step();
+
+        for sub_tick in 0..(1u64 << max_level) {
+            let starting: Vec<bool> = levels
+                .iter()
+                .map(|level| sub_tick % (1u64 << (max_level - level)) == 0)
+                .collect();
+            if sub_tick > 0 {
+                forces = self.calculate_forces_on(&self.nodes, &starting);
+            }
+            self.time += sub_step;
+            let node_time_step = |index: usize| {
+                starting[index].then(|| time_step / (1u64 << levels[index]) as node::Scalar)
+            };
+            self.nodes.accelerate(&forces, node_time_step);
+            if self.drag.model != node::drag::DragModel::Legacy {
+                self.nodes.slow(&self.drag, node_time_step);
+            }
+            self.nodes.drift(node_time_step);
+            let mut nodes = std::mem::take(&mut self.nodes);
+            self.follow_paths(&mut nodes, self.time);
+            self.nodes = nodes;
+        }
+    }
+
+    /// Moves every node forward by `time_step` under the given forces
+    fn advance(&mut self, time_step: node::Scalar, forces: &[node::Force]) {
+        self.time += time_step;
+        let mut nodes = std::mem::take(&mut self.nodes);
+        self.move_nodes(&mut nodes, time_step, forces, self.time);
+        self.nodes = nodes;
+    }
+
+    /// Moves `nodes` forward by `time_step` under the given forces, to end at `time`
+    fn move_nodes(
+        &self,
+        nodes: &mut node_store::NodeStore,
+        time_step: node::Scalar,
+        forces: &[node::Force],
+        time: node::Scalar,
+    ) {
+        let every_node = |_| Some(time_step);
+        nodes.accelerate(forces, every_node);
+        // Legacy dampening is applied once the whole step has been taken. See [`Simulation::dampen`]
+        if self.drag.model != node::drag::DragModel::Legacy {
+            nodes.slow(&self.drag, every_node);
+        }
+        nodes.drift(every_node);
+        self.follow_paths(nodes, time);
+    }
+
+    /// Dampens every node by its dampen rate under the legacy drag model. This happens once each
+    /// step, after the nodes have moved, so nodes are dampened by the same amount however many
+    /// sub-steps the step is split into. Kinematic nodes follow their paths, so are not dampened.
+    fn dampen(&mut self, time_step: node::Scalar) {
+        if self.drag.model != node::drag::DragModel::Legacy {
+            return;
+        }
+        let kinematic: Vec<bool> = self
+            .nodes
+            .ids()
+            .iter()
+            .map(|id| self.kinematics.contains_key(id))
+            .collect();
+        self.nodes
+            .slow(&self.drag, |index| (!kinematic[index]).then_some(time_step));
+    }
+
+    /// Moves kinematic nodes to where their paths are at `time`, and keeps every node within the
+    /// dimensionality of the simulation
+    fn follow_paths(&self, nodes: &mut node_store::NodeStore, time: node::Scalar) {
+        let (kinematics, dimensionality) = (&self.kinematics, self.dimensionality);
+        nodes.update_motion(|id, position, velocity| {
+            if let Some(kinematic) = kinematics.get(&id) {
+                (*position, *velocity) = kinematic.evaluate(time);
+            }
+            dimensionality.apply(position, velocity);
+        });
+    }
+
+    /// Whether the node at `index` is moved by the forces acting on it
+    fn responds_to_force(&self, index: usize) -> bool {
+        let node = self
+            .nodes
+            .get(index)
+            .expect("Index is within the node store");
+        !node.freeze && !self.kinematics.contains_key(&node.id)
+    }
+
+    fn max_acceleration(&self, forces: &[node::Force]) -> node::Scalar {
+        let masses = self.nodes.masses();
+        (0..forces.len())
+            .filter(|index| self.responds_to_force(*index))
+            .map(|index| forces[index].magnitude() / masses[index])
+            .fold(0.0, node::Scalar::max)
+    }
+
+    /// Takes a trial sub-step of `time_step`, giving an estimate of its error along with the moved
+    /// nodes and the forces on them at the end of it.
+    ///
+    /// The error is the greatest distance, for any node moved by forces, between where the sub-step
+    /// leaves it and where a trapezoidal step would, using its accelerations `a` at the start and
+    /// `a_end` at the end. The sub-step moves the node `v * dt + a * dt^2` from where it started,
+    /// and the trapezoidal step `v * dt + (a + a_end) * dt^2 / 4`, which is accurate to a higher
+    /// order, so the distance between them estimates the error of the sub-step. The distance is
+    /// found from the accelerations alone, as subtracting positions far from the origin loses the
+    /// precision needed for small tolerances. Drag is left out.
+    fn try_sub_step(
+        &self,
+        forces: &[node::Force],
+        time_step: node::Scalar,
+    ) -> (node::Scalar, node_store::NodeStore, Vec<node::Force>) {
+        let mut nodes = self.nodes.clone();
+        self.move_nodes(&mut nodes, time_step, forces, self.time + time_step);
+        let end_forces = self.calculate_forces(&nodes);
+
+        let masses = self.nodes.masses();
+        let error = (0..forces.len())
+            .filter(|index| self.responds_to_force(*index))
+            .map(|index| {
+                let difference = forces[index] * 3.0 + -end_forces[index];
+                difference.magnitude() / masses[index] * time_step * time_step / 4.0
+            })
+            .fold(0.0, node::Scalar::max);
+        (error, nodes, end_forces)
+    }
 
-        let others = nodes.clone();
-        let node_force_function = |node: &mut node::Node| -> node::Force {
-            let others = &others.iter().filter(|n| n != &node).collect();
-            node::Force::calculate_incoming_force(node, others, &self.gravitational_constant)
+    fn record_stats(&self) {
+        let stats_log = match &self.stats_log {
+            Some(stats_log) if stats_log.is_due(self.tick) => stats_log,
+            _ => return,
         };
+        if let Err(err) = stats_log.record(&self.stats()) {
+            println!(
+                "Error writing stats to {} - {}",
+                stats_log.path.display(),
+                err
+            );
+        }
+    }
+
+    /// Conserved quantities and extent of the simulation
+    pub fn stats(&self) -> stats::Stats {
+        stats::Stats::new(self)
+    }
+
+    /// Number of steps the simulation has taken
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Simulated time that has passed
+    pub fn time(&self) -> node::Scalar {
+        self.time
+    }
 
-        for node in self.nodes.iter_mut() {
-            node.step(node_force_function);
+    pub fn dimensionality(&self) -> dimensionality::Dimensionality {
+        self.dimensionality
+    }
+
+    /// Position of the node with `id`, as an offset from the origin. Vectors that are not relative
+    /// to a node are from the origin itself
+    fn position_of(&self, id: Option<node::Id>) -> Result<node::Force, String> {
+        let Some(id) = id else {
+            return Ok(node::Force::zero());
+        };
+        match self.nodes.find(id) {
+            Some(node) => Ok(node::Force(node.position.0.to_vec())),
+            None => Err(format!("no node with id {} exists", id)),
+        }
+    }
+
+    /// Velocity of the node with `id`, or no velocity if there is no node to be relative to
+    fn velocity_of(&self, id: Option<node::Id>) -> Result<node::Force, String> {
+        let Some(id) = id else {
+            return Ok(node::Force::zero());
+        };
+        match self.nodes.find(id) {
+            Some(node) => Ok(node.velocity),
+            None => Err(format!("no node with id {} exists", id)),
         }
     }
 
-    pub fn handle_event(&mut self, event: node::Event) {
+    /// Adds a node in orbit around another node, starting from the position, velocity and mass the
+    /// other node has now
+    pub fn add_orbiter(&mut self, add_orbiter_event: node::AddOrbiterEvent) -> Result<(), String> {
+        let mut node = add_orbiter_event.node;
+        let error = |err: String| format!("Error adding node with id {} - {}", node.id, err);
+        let around = add_orbiter_event.around;
+        let Some(parent) = self.nodes.find(around) else {
+            return Err(error(format!("no node with id {} exists", around)));
+        };
+        let elements = add_orbiter_event.elements;
+        let apoapsis = elements.semi_major_axis * (1.0 + elements.eccentricity);
+        match self.force_radius {
+            Some(force_radius) if !node::Force::in_range(apoapsis, Some(force_radius)) => {
+                return Err(error(format!(
+                    "its orbit would reach {} from node {}, beyond the force radius of {} where nodes stop interacting",
+                    apoapsis, around, force_radius
+                )));
+            }
+            _ => (),
+        }
+        let gravitational_parameter = self
+            .gravitational_parameter(&node, &parent)
+            .map_err(error)?;
+        let (offset, velocity) = elements.state(gravitational_parameter);
+        node.position = parent.position + offset;
+        node.velocity = Self::orbited_velocity(&parent) + node::Force::from(velocity);
+        self.add_node(node);
+        Ok(())
+    }
+
+    /// The osculating elements of the orbit of node `id` around node `around`, or around the node
+    /// pulling hardest on it if not given. Also gives the node orbited, and the gravitational
+    /// parameter of the orbit
+    pub fn orbit_of(
+        &self,
+        id: node::Id,
+        around: Option<node::Id>,
+    ) -> Result<(node::Id, node::orbit::Elements, node::Scalar), String> {
+        let Some(node) = self.nodes.find(id) else {
+            return Err(format!("no node with id {} exists", id));
+        };
+        let parent = match around {
+            Some(around) => match self.nodes.find(around) {
+                Some(parent) if parent.id == id => {
+                    return Err(format!("node {} can not orbit itself", id))
+                }
+                Some(parent) if !self.interacts(&node, &parent) => {
+                    return Err(format!(
+                        "node {} is beyond the force radius of node {}, so does not orbit it",
+                        id, around
+                    ))
+                }
+                Some(parent) => parent,
+                None => return Err(format!("no node with id {} exists", around)),
+            },
+            None => self.strongest_pull_on(&node).ok_or_else(|| {
+                format!(
+                    "node {} is not pulled towards any node, so has no orbit",
+                    id
+                )
+            })?,
+        };
+        let gravitational_parameter = self.gravitational_parameter(&node, &parent)?;
+        let elements = node::orbit::Elements::from_state(
+            node::Position::displacement(&parent.position, &node.position),
+            node.velocity.0 - Self::orbited_velocity(&parent).0,
+            gravitational_parameter,
+        )
+        .map_err(|err| format!("node {} has no orbit as {}", id, err))?;
+        Ok((parent.id, elements, gravitational_parameter))
+    }
+
+    /// Velocity of a node being orbited. Frozen nodes keep the velocity forces give them, but do not
+    /// move with it
+    fn orbited_velocity(parent: &node::Node) -> node::Force {
+        match parent.freeze {
+            true => node::Force::zero(),
+            false => parent.velocity,
+        }
+    }
+
+    /// Whether two nodes are close enough to exert forces on eachother
+    fn interacts(&self, node: &node::Node, other: &node::Node) -> bool {
+        let distance = node::Position::displacement(&node.position, &other.position).magnitude();
+        node::Force::in_range(distance, self.force_radius)
+    }
+
+    /// The node whose gravity pulls hardest on `node`, ignoring nodes that push it away or are too
+    /// far away to interact with it
+    fn strongest_pull_on(&self, node: &node::Node) -> Option<node::Node> {
+        self.nodes
+            .iter()
+            .filter(|other| other.id != node.id && other.position != node.position)
+            .filter(|other| self.interacts(node, other))
+            .map(|other| {
+                let g = node::Force::pair_gravitational_constant(
+                    node,
+                    &other,
+                    &self.gravitational_constant,
+                );
+                let distance = node::Position::displacement(&node.position, &other.position);
+                (g * other.mass / distance.magnitude2(), other)
+            })
+            .filter(|(acceleration, _)| *acceleration > 0.0)
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, other)| other)
+    }
+
+    /// The gravitational constant between two nodes multiplied by their combined mass, which decides
+    /// how `node` orbits `parent`. A parent that is not moved by forces does not fall towards the
+    /// node, so only its own mass counts
+    fn gravitational_parameter(
+        &self,
+        node: &node::Node,
+        parent: &node::Node,
+    ) -> Result<node::Scalar, String> {
+        let g =
+            node::Force::pair_gravitational_constant(node, parent, &self.gravitational_constant);
+        if g <= 0.0 {
+            return Err(format!(
+                "gravity between nodes {} and {} pushes them apart, so they can not orbit eachother",
+                node.id, parent.id
+            ));
+        }
+        match parent.freeze || self.kinematics.contains_key(&parent.id) {
+            true => Ok(g * parent.mass),
+            false => Ok(g * (node.mass + parent.mass)),
+        }
+    }
+
+    /// Sets the dimensionality, moving every node onto the XZ plane when switching to 2D
+    pub fn set_dimensionality(&mut self, dimensionality: dimensionality::Dimensionality) {
+        self.dimensionality = dimensionality;
+        self.nodes
+            .update_motion(|_, position, velocity| dimensionality.apply(position, velocity));
+    }
+
+    fn calculate_forces(&self, nodes: &node_store::NodeStore) -> Vec<node::Force> {
+        match self.pair_force_mode {
+            node::force::PairForceMode::Symmetric => self.calculate_symmetric_forces(nodes),
+            node::force::PairForceMode::Asymmetric => self.calculate_asymmetric_forces(nodes),
+        }
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn calculate_symmetric_forces(&self, nodes: &node_store::NodeStore) -> Vec<node::Force> {
+        let node_count = nodes.len();
+        let mut forces = vec![node::Force::zero(); node_count];
+        for i in 0..node_count {
+            for j in (i + 1)..node_count {
+                let force = self.calculate_pair_force(nodes, i, j);
+                forces[i] += force;
+                forces[j] += -force;
+            }
+        }
+        forces
+    }
+
+    /// Rows of pairs are shared between one group per thread, each summing into its own forces, so
+    /// every pair is still evaluated once and only one set of forces is kept per thread. Taking
+    /// every nth row evens out the long rows at the start and the short rows at the end. The groups
+    /// are added together in the same order, so results are the same for the same number of
+    /// threads.
+    #[cfg(feature = "parallel")]
+    fn calculate_symmetric_forces(&self, nodes: &node_store::NodeStore) -> Vec<node::Force> {
+        let node_count = nodes.len();
+        let group_count = rayon::current_num_threads().clamp(1, node_count.max(1));
+        let group_forces: Vec<Vec<node::Force>> = (0..group_count)
+            .into_par_iter()
+            .map(|group| {
+                let mut forces = vec![node::Force::zero(); node_count];
+                for i in (group..node_count).step_by(group_count) {
+                    for j in (i + 1)..node_count {
+                        let force = self.calculate_pair_force(nodes, i, j);
+                        forces[i] += force;
+                        forces[j] += -force;
+                    }
+                }
+                forces
+            })
+            .collect();
+        (0..node_count)
+            .into_par_iter()
+            .map(|i| group_forces.iter().map(|forces| forces[i]).sum())
+            .collect()
+    }
+
+    fn calculate_asymmetric_forces(&self, nodes: &node_store::NodeStore) -> Vec<node::Force> {
+        self.calculate_forces_on(nodes, &vec![true; nodes.len()])
+    }
+
+    /// Forces on the nodes marked as included, found by summing the force from every other node.
+    /// Nodes that are not included are given no force.
+    fn calculate_forces_on(
+        &self,
+        nodes: &node_store::NodeStore,
+        included: &[bool],
+    ) -> Vec<node::Force> {
+        let node_count = nodes.len();
+        #[cfg(feature = "parallel")]
+        let indexes = (0..node_count).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let indexes = 0..node_count;
+        indexes
+            .map(|i| match included[i] {
+                true => (0..node_count)
+                    .filter(|j| *j != i)
+                    .map(|j| self.calculate_incoming_force(nodes, i, j))
+                    .sum(),
+                false => node::Force::zero(),
+            })
+            .collect()
+    }
+
+    /// Force on the node at index `i` from the node at index `j`, under the pair force mode
+    fn calculate_incoming_force(
+        &self,
+        nodes: &node_store::NodeStore,
+        i: usize,
+        j: usize,
+    ) -> node::Force {
+        match self.pair_force_mode {
+            node::force::PairForceMode::Symmetric => self.calculate_pair_force(nodes, i, j),
+            node::force::PairForceMode::Asymmetric => {
+                let overrides = nodes.gravitational_constant_overrides();
+                let g = overrides[j].unwrap_or(self.gravitational_constant);
+                self.calculate_force_between(nodes, i, j, g)
+            }
+        }
+    }
+
+    /// Force on the node at index `i` from the node at index `j`, using the gravitational constant
+    /// shared by the pair. See [`node::Force::calculate_pair_force`]
+    fn calculate_pair_force(
+        &self,
+        nodes: &node_store::NodeStore,
+        i: usize,
+        j: usize,
+    ) -> node::Force {
+        let overrides = nodes.gravitational_constant_overrides();
+        let g = node::Force::combine_gravitational_constants(
+            overrides[i],
+            overrides[j],
+            &self.gravitational_constant,
+        );
+        self.calculate_force_between(nodes, i, j, g)
+    }
+
+    fn calculate_force_between(
+        &self,
+        nodes: &node_store::NodeStore,
+        i: usize,
+        j: usize,
+        g: node::Scalar,
+    ) -> node::Force {
+        let positions = nodes.positions();
+        let masses = nodes.masses();
+        node::Force::between(
+            &positions[i],
+            masses[i],
+            &positions[j],
+            masses[j],
+            g,
+            &self.softening_length,
+            self.force_radius,
+        )
+    }
+
+    /// Handles an event sent to the simulation, giving an error if it could not be applied
+    pub fn handle_event(&mut self, event: node::Event) -> Result<(), String> {
         match event {
-            node::Event::AddNode(add_node_event) => self.add_node(add_node_event.node),
+            node::Event::AddNode(mut add_node_event) => {
+                let origin = match self.position_of(add_node_event.position_relative_to) {
+                    Ok(origin) => origin,
+                    Err(err) => {
+                        return Err(format!(
+                            "Error adding node with id {} - {}",
+                            add_node_event.node.id, err
+                        ))
+                    }
+                };
+                add_node_event.node.position = add_node_event.node.position + origin;
+                self.add_node(add_node_event.node)
+            }
+            node::Event::AddOrbiter(add_orbiter_event) => {
+                return self.add_orbiter(add_orbiter_event)
+            }
+            node::Event::AddEmitter(add_emitter_event) => {
+                return self.add_emitter(add_emitter_event.emitter)
+            }
+            node::Event::AddSink(add_sink_event) => return self.add_sink(add_sink_event.sink),
+            node::Event::RemoveEmitter(remove_emitter_event) => {
+                self.remove_emitter(remove_emitter_event.emitter_id)
+            }
+            node::Event::RemoveSink(remove_sink_event) => {
+                self.remove_sink(remove_sink_event.sink_id)
+            }
+            #[cfg(feature = "scripting")]
+            node::Event::AddHook(add_hook_event) => return self.add_hook(add_hook_event),
+            #[cfg(feature = "scripting")]
+            node::Event::RemoveHook(remove_hook_event) => {
+                return self.remove_hook(remove_hook_event.hook_id)
+            }
             node::Event::RemoveNode(remove_node_event) => {
                 self.remove_node(remove_node_event.node_id)
             }
             node::Event::SetTargetTps(set_target_tps_event) => {
                 self.set_target_tps(set_target_tps_event.target_tps)
             }
-            node::Event::SetNode(set_node_event) => {
-                let node: &mut node::Node = match self
-                    .nodes
-                    .iter_mut()
-                    .find(|node| node.id == set_node_event.id)
-                {
-                    Some(node) => node,
-                    None => {
-                        println!("No node with id {} was found", set_node_event.id);
-                        return;
-                    }
-                };
-
-                if let Some(position) = set_node_event.position {
-                    node.position = position
-                };
-                if let Some(velocity) = set_node_event.velocity {
-                    node.velocity = velocity
-                };
-                if let Some(mass) = set_node_event.mass {
-                    node.mass = mass
-                };
-                if let Some(g) = set_node_event.gravitational_constant_override {
-                    node.gravitational_constant_override = Some(g)
+            node::Event::SetPairForceMode(set_pair_force_mode_event) => {
+                self.pair_force_mode = set_pair_force_mode_event.pair_force_mode
+            }
+            node::Event::SetDrag(set_drag_event) => {
+                if let Some(model) = set_drag_event.model {
+                    self.drag.model = model
                 };
-                if let Some(dampen_rate) = set_node_event.dampen_rate {
-                    node.dampen_rate = dampen_rate
+                if let Some(medium_density) = set_drag_event.medium_density {
+                    self.drag.medium_density = medium_density
                 };
-                if let Some(freeze) = set_node_event.freeze {
-                    node.freeze = freeze
+            }
+            node::Event::SetDimensionality(set_dimensionality_event) => {
+                self.set_dimensionality(set_dimensionality_event.dimensionality)
+            }
+            node::Event::SetUnits(set_units_event) => self.set_units(set_units_event.units),
+            node::Event::SetTimeStep(set_time_step_event) => {
+                self.time_step = set_time_step_event.time_step
+            }
+            node::Event::SetCollisionPolicy(set_collision_policy_event) => {
+                self.collision_policy = set_collision_policy_event.collision_policy
+            }
+            node::Event::SetAdaptiveStep(set_adaptive_step_event) => {
+                return self.set_adaptive_step(set_adaptive_step_event)
+            }
+            node::Event::SetStatsLog(set_stats_log_event) => {
+                return self.set_stats_log(set_stats_log_event)
+            }
+            node::Event::SetSoftening(set_softening_event) => {
+                self.set_softening_length(set_softening_event.softening_length)
+            }
+            node::Event::SetNode(set_node_event) => {
+                let id = set_node_event.id;
+                let position_origin = self.position_of(set_node_event.position_relative_to);
+                let velocity_origin = self.velocity_of(set_node_event.velocity_relative_to);
+                let (position_origin, velocity_origin) = match (position_origin, velocity_origin) {
+                    (Ok(position_origin), Ok(velocity_origin)) => {
+                        (position_origin, velocity_origin)
+                    }
+                    (Err(err), _) | (_, Err(err)) => {
+                        return Err(format!("Error setting node with id {} - {}", id, err))
+                    }
                 };
+                let found = self.nodes.modify(id, |node| {
+                    if let Some(position) = set_node_event.position {
+                        node.position = position + position_origin
+                    };
+                    if let Some(velocity) = set_node_event.velocity {
+                        node.velocity = velocity + velocity_origin
+                    };
+                    if let Some(mass) = set_node_event.mass {
+                        node.mass = mass
+                    };
+                    if let Some(radius) = set_node_event.radius {
+                        node.radius = radius.abs()
+                    };
+                    if let Some(g) = set_node_event.gravitational_constant_override {
+                        node.gravitational_constant_override = Some(g)
+                    };
+                    if let Some(dampen_rate) = set_node_event.dampen_rate {
+                        node.dampen_rate = dampen_rate
+                    };
+                    if let Some(drag_coefficient) = set_node_event.drag_coefficient {
+                        node.drag_coefficient = drag_coefficient
+                    };
+                    if let Some(freeze) = set_node_event.freeze {
+                        node.freeze = freeze
+                    };
+                    self.dimensionality
+                        .apply(&mut node.position, &mut node.velocity);
+                    if let Some(locked_axes) = set_node_event.locked_axes {
+                        node.locked_axes = locked_axes
+                    };
+                    if let Some(constraint) = set_node_event.constraint {
+                        node.constraint = constraint
+                    };
+                    match set_node_event.kinematic {
+                        Some(Some(path)) => {
+                            let kinematic = node::kinematic::Kinematic {
+                                path,
+                                start_time: self.time,
+                            };
+                            (node.position, node.velocity) = kinematic.evaluate(self.time);
+                            self.kinematics.insert(node.id, kinematic);
+                        }
+                        Some(None) => _ = self.kinematics.remove(&node.id),
+                        None => {}
+                    };
+                });
+                if !found {
+                    return Err(format!("No node with id {} was found", id));
+                }
             }
-            node::Event::Get(get_event) => get_event.handle(self),
+            node::Event::Get(get_event) => return get_event.handle(self),
+            node::Event::Assert(assert_event) => return assert_event.handle(self),
             node::Event::Step(step_event) => {
                 for _ in 0..step_event.steps {
                     self.step()
                 }
             }
+            node::Event::Schedule(schedule_event) => {
+                let description = schedule_event.description.clone();
+                let trigger = schedule_event.trigger;
+                match self.schedule.add(
+                    trigger,
+                    schedule_event.description,
+                    schedule_event.events,
+                    self.tick,
+                    self.time,
+                ) {
+                    Ok(handle) => println!("Scheduled {}: {} - {}", handle, trigger, description),
+                    Err(err) => return Err(format!("Error scheduling command - {}", err)),
+                }
+            }
+            node::Event::CancelScheduled(cancel_scheduled_event) => {
+                let handle = cancel_scheduled_event.handle;
+                if !self.schedule.cancel(handle) {
+                    return Err(format!("Nothing is scheduled with handle {}", handle));
+                }
+            }
         }
+        Ok(())
     }
 
     pub fn target_tps(&self) -> u32 {
@@ -96,10 +970,56 @@ impl Simulation {
     pub fn set_target_tps(&mut self, target_tps: u32) {
         self.target_tps = target_tps;
     }
+
+    fn set_stats_log(&mut self, event: node::SetStatsLogEvent) -> Result<(), String> {
+        self.stats_log = None;
+        if let Some(path) = event.path {
+            let stats_log = stats::StatsLog::create(path.clone(), event.interval)
+                .map_err(|err| format!("Error creating stats log {} - {}", path.display(), err))?;
+            self.stats_log = Some(stats_log);
+        }
+        Ok(())
+    }
+
+    fn set_adaptive_step(&mut self, event: node::SetAdaptiveStepEvent) -> Result<(), String> {
+        let mut adaptive_step = self.adaptive_step;
+        if let Some(mode) = event.mode {
+            adaptive_step.mode = mode
+        };
+        if let Some(tolerance) = event.tolerance {
+            adaptive_step.tolerance = tolerance
+        };
+        if let Some(min_time_step) = event.min_time_step {
+            adaptive_step.min_time_step = min_time_step
+        };
+        if let Some(max_time_step) = event.max_time_step {
+            adaptive_step.max_time_step = max_time_step
+        };
+        adaptive_step
+            .validate()
+            .map_err(|err| format!("Error setting adaptive step - {}", err))?;
+        adaptive_step.reset();
+        self.adaptive_step = adaptive_step;
+        Ok(())
+    }
+
+    pub fn set_softening_length(&mut self, softening_length: node::Scalar) {
+        self.softening_length = softening_length.abs();
+    }
+
+    /// Measures the simulation in `units`, using the gravitational constant and force radius they
+    /// give
+    pub fn set_units(&mut self, units: units::UnitSystem) {
+        self.units = units;
+        self.gravitational_constant = units.gravitational_constant();
+        self.force_radius = units.force_radius();
+    }
 }
 
 #[cfg(test)]
 mod a_simulation {
+    use cgmath::InnerSpace;
+
     use crate::node;
 
     use super::*;
@@ -124,7 +1044,7 @@ mod a_simulation {
         simulation.add_node(node);
 
         assert_eq!(1, simulation.nodes.len());
-        assert_eq!(&node, simulation.nodes.get(0).unwrap());
+        assert_eq!(node, simulation.nodes.get(0).unwrap());
     }
 
     #[test]
@@ -143,7 +1063,7 @@ mod a_simulation {
         simulation.remove_node(id);
 
         assert_eq!(0, simulation.nodes.len());
-        assert!(!simulation.nodes.contains(&node));
+        assert_eq!(None, simulation.nodes.find(id));
     }
 
     #[test]
@@ -194,9 +1114,13 @@ mod a_simulation {
             position: node::Position::from((0.0, 0.0, 0.0)),
             velocity: node::Force::from((1.0, 0.0, 0.0)),
             mass: 1.0,
+            radius: 0.0,
             gravitational_constant_override: None,
             dampen_rate: 0.0,
+            drag_coefficient: 0.0,
             freeze: false,
+            locked_axes: node::constraint::Axes::none(),
+            constraint: None,
         };
 
         simulation.add_node(node_a);
@@ -212,7 +1136,7 @@ mod a_simulation {
 
         let step_event = node::Event::Step(node::event::step::StepEvent { steps: 1 });
 
-        simulation.handle_event(step_event);
+        simulation.handle_event(step_event).unwrap();
 
         {
             let node = simulation
@@ -223,4 +1147,927 @@ mod a_simulation {
             assert_ne!(node::Position::from((0.0, 0.0, 0.0)), node.position)
         };
     }
+
+    fn max_speed_over_steps(simulation: &mut Simulation, steps: u32) -> node::Scalar {
+        let mut max_speed: node::Scalar = 0.0;
+        for _ in 0..steps {
+            simulation.step();
+            for node in simulation.nodes.iter() {
+                max_speed = max_speed.max(node.velocity.magnitude());
+            }
+        }
+        max_speed
+    }
+
+    fn near_coincident_simulation() -> Simulation {
+        let mut simulation = Simulation::new();
+        simulation.gravitational_constant = 1.0;
+        for (id, x) in [(1, 0.0), (2, 0.001)] {
+            let mut node = node::Node::new(node::Id(id), node::Position::from((x, 0.0, 0.0)));
+            node.dampen_rate = 0.0;
+            simulation.add_node(node);
+        }
+        simulation
+    }
+
+    #[test]
+    fn keeps_velocity_bounded_for_near_coincident_nodes_when_softened() {
+        let mut simulation = near_coincident_simulation();
+        simulation.set_softening_length(1.0);
+
+        let max_speed = max_speed_over_steps(&mut simulation, 100);
+
+        assert!(max_speed.is_finite());
+        assert!(max_speed < 1.0, "max speed was {max_speed}");
+    }
+
+    #[test]
+    fn launches_near_coincident_nodes_when_not_softened() {
+        let mut simulation = near_coincident_simulation();
+
+        let max_speed = max_speed_over_steps(&mut simulation, 100);
+
+        assert!(max_speed > 1000.0, "max speed was {max_speed}");
+    }
+
+    #[rstest::rstest]
+    #[case(adaptive_step::AdaptiveStepMode::Acceleration)]
+    #[case(adaptive_step::AdaptiveStepMode::ErrorEstimate)]
+    fn separates_near_coincident_nodes_accurately_with_adaptive_steps(
+        #[case] mode: adaptive_step::AdaptiveStepMode,
+    ) {
+        let mut simulation = near_coincident_simulation();
+        simulation.gravitational_constant = -1.0;
+        simulation
+            .handle_event(node::Event::SetAdaptiveStep(node::SetAdaptiveStepEvent {
+                mode: Some(mode),
+                tolerance: Some(1e-6),
+                min_time_step: Some(1e-9),
+                max_time_step: None,
+            }))
+            .unwrap();
+
+        let max_speed = max_speed_over_steps(&mut simulation, 100);
+
+        // The potential energy of the repelling pair gives each node a speed of about 31.6
+        assert!((max_speed - 31.6).abs() < 1.0, "max speed was {max_speed}");
+        assert!((simulation.time() - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rejects_sub_steps_that_are_too_inaccurate() {
+        let mut simulation = near_coincident_simulation();
+        simulation.adaptive_step.mode = adaptive_step::AdaptiveStepMode::ErrorEstimate;
+
+        simulation.step();
+
+        assert!(simulation.adaptive_step.rejected_steps() > 0);
+        assert_eq!(1, simulation.tick());
+    }
+
+    #[test]
+    fn estimates_the_error_of_a_sub_step() {
+        let simulation = bound_pair_simulation();
+        let forces = simulation.calculate_forces(&simulation.nodes);
+        let time_step = 0.01;
+        let mut whole = simulation.clone();
+        let mut reference = simulation.clone();
+
+        let (estimated_error, ..) = simulation.try_sub_step(&forces, time_step);
+        whole.step_by(time_step);
+        for _ in 0..1000 {
+            reference.step_by(time_step / 1000.0);
+        }
+
+        let error = bound_pair_distance_between(&whole, &reference);
+        assert!(
+            error / 4.0 < estimated_error && estimated_error < error,
+            "estimated an error of {estimated_error} for an error of {error}"
+        );
+    }
+
+    #[test]
+    fn keeps_the_accepted_trial_sub_step() {
+        let mut fixed = bound_pair_simulation();
+        let mut estimated = bound_pair_simulation();
+        estimated.adaptive_step.mode = adaptive_step::AdaptiveStepMode::ErrorEstimate;
+        estimated.adaptive_step.tolerance = 1.0;
+
+        fixed.step_by(0.01);
+        estimated.step_by(0.01);
+
+        assert_eq!(0, estimated.adaptive_step.rejected_steps());
+        assert_eq!(0.0, bound_pair_distance_between(&fixed, &estimated));
+    }
+
+    #[test]
+    fn ignores_invalid_adaptive_steps() {
+        let mut simulation = Simulation::new();
+
+        assert!(simulation
+            .handle_event(node::Event::SetAdaptiveStep(node::SetAdaptiveStepEvent {
+                mode: Some(adaptive_step::AdaptiveStepMode::Acceleration),
+                tolerance: None,
+                min_time_step: Some(2.0),
+                max_time_step: Some(1.0),
+            }))
+            .is_err());
+
+        assert_eq!(
+            adaptive_step::AdaptiveStep::default(),
+            simulation.adaptive_step
+        );
+    }
+
+    /// A tightly bound pair in a circular orbit, with a distant node that barely moves
+    fn bound_pair_simulation() -> Simulation {
+        let mut simulation = Simulation::new();
+        simulation.gravitational_constant = 1.0;
+        simulation.drag.model = node::drag::DragModel::Linear;
+        // Each node orbits the centre of the pair at a radius of 0.05, with v^2 / r = G / d^2
+        let nodes = [
+            ((0.0, 0.0, 0.0), (0.0, 0.0, -(5.0 as node::Scalar).sqrt())),
+            ((0.1, 0.0, 0.0), (0.0, 0.0, (5.0 as node::Scalar).sqrt())),
+            ((3.0, 0.0, 0.0), (0.0, 0.0, 0.0)),
+        ];
+        for (id, (position, velocity)) in nodes.into_iter().enumerate() {
+            let mut node = node::Node::new(node::Id(id as u32), node::Position::from(position));
+            node.velocity = node::Force::from(velocity);
+            node.drag_coefficient = 0.0;
+            simulation.add_node(node);
+        }
+        simulation
+    }
+
+    /// Largest distance between the bound pair in two simulations
+    fn bound_pair_distance_between(a: &Simulation, b: &Simulation) -> node::Scalar {
+        a.nodes
+            .iter()
+            .zip(b.nodes.iter())
+            .take(2)
+            .map(|(a, b)| (a.position.0 - b.position.0).magnitude())
+            .fold(0.0, node::Scalar::max)
+    }
+
+    #[test]
+    fn matches_a_small_fixed_time_step_with_block_time_steps() {
+        let mut coarse = bound_pair_simulation();
+        let mut block = bound_pair_simulation();
+        block.adaptive_step.mode = adaptive_step::AdaptiveStepMode::Block;
+        let mut reference = bound_pair_simulation();
+        let sub_steps = 16;
+
+        for _ in 0..20 {
+            coarse.step_by(0.05);
+            block.step_by(0.05);
+            for _ in 0..sub_steps {
+                reference.step_by(0.05 / sub_steps as node::Scalar);
+            }
+        }
+
+        // The pair is put on the finest level, while the distant node takes whole steps
+        assert_eq!(
+            0.05 / sub_steps as node::Scalar,
+            block.adaptive_step.time_step()
+        );
+        assert!((block.time() - reference.time()).abs() < 1e-4);
+        let block_error = bound_pair_distance_between(&block, &reference);
+        let coarse_error = bound_pair_distance_between(&coarse, &reference);
+        assert!(
+            block_error < 1e-3,
+            "block time steps were off by {block_error}"
+        );
+        assert!(
+            coarse_error > 0.01,
+            "coarse time steps were off by {coarse_error}"
+        );
+    }
+
+    fn colliding_simulation(collision_policy: collision::CollisionPolicy) -> Simulation {
+        let mut simulation = Simulation::new();
+        simulation.gravitational_constant = 0.0;
+        simulation.collision_policy = collision_policy;
+        let nodes = [
+            (1, -1.0, 1.0, 1.0),
+            (2, 1.0, -1.0, 2.0),
+            (3, 10.0, 0.0, 1.0),
+        ];
+        for (id, x, velocity, mass) in nodes {
+            let mut node = node::Node::new(node::Id(id), node::Position::from((x, 0.0, 0.0)));
+            node.velocity = node::Force::from((velocity, 0.0, 0.0));
+            node.mass = mass;
+            node.radius = 0.5;
+            node.dampen_rate = 0.0;
+            simulation.add_node(node);
+        }
+        simulation
+    }
+
+    #[test]
+    fn does_not_conserve_momentum_with_block_time_steps() {
+        let mut fixed = bound_pair_simulation();
+        let mut block = bound_pair_simulation();
+        block.adaptive_step.mode = adaptive_step::AdaptiveStepMode::Block;
+
+        for _ in 0..20 {
+            fixed.step_by(0.05);
+            block.step_by(0.05);
+        }
+
+        // The distant node is only kicked once a step, while the pair is kicked every sub-step
+        let fixed_momentum = total_momentum(&fixed).magnitude();
+        let block_momentum = total_momentum(&block).magnitude();
+        assert!(fixed_momentum < 1e-5, "momentum was {fixed_momentum}");
+        assert!(block_momentum > 1e-4, "momentum was {block_momentum}");
+    }
+
+    #[test]
+    fn merges_colliding_nodes() {
+        let mut simulation = colliding_simulation(collision::CollisionPolicy::Merge);
+        let initial_momentum = total_momentum(&simulation);
+
+        simulation.step();
+
+        let merge = collision::Merge {
+            survivor: node::Id(2),
+            removed: node::Id(1),
+        };
+        assert_eq!(&[merge], simulation.merges());
+        assert_eq!(2, simulation.nodes.len());
+        assert_eq!(None, simulation.nodes.find(node::Id(1)));
+        let merged = simulation.nodes.find(node::Id(2)).unwrap();
+        assert_eq!(3.0, merged.mass);
+        assert_eq!(initial_momentum, total_momentum(&simulation));
+
+        assert_eq!(vec![merge], simulation.take_merges());
+        simulation.step();
+        assert!(simulation.merges().is_empty());
+    }
+
+    #[test]
+    fn lets_colliding_nodes_pass_through_eachother_without_a_collision_policy() {
+        let mut simulation = colliding_simulation(collision::CollisionPolicy::None);
+
+        simulation.step();
+
+        assert_eq!(3, simulation.nodes.len());
+        assert!(simulation.merges().is_empty());
+    }
+
+    #[test]
+    fn gives_emitted_nodes_unused_ids() {
+        let mut simulation = Simulation::new();
+        simulation.add_node(node::Node::new(node::Id(4), node::Position::default()));
+        let emitter = emitter::Emitter::new(
+            1,
+            node::Position::from((1.0, 0.0, 0.0)),
+            2.0,
+            node::Force::from((0.0, 0.0, 1.0)),
+            emitter::VelocityDistribution::Fixed,
+            1.0,
+            0,
+        )
+        .unwrap();
+        simulation.add_emitter(emitter).unwrap();
+
+        simulation.step();
+        simulation.step();
+
+        let ids: Vec<node::Id> = simulation.nodes.iter().map(|node| node.id).collect();
+        assert_eq!(
+            vec![4, 5, 6, 7, 8],
+            ids.iter().map(|id| id.0).collect::<Vec<_>>()
+        );
+        assert_eq!(node::Id(9), simulation.allocate_id());
+    }
+
+    #[test]
+    fn removes_nodes_that_enter_a_sink() {
+        let mut simulation = Simulation::new();
+        simulation.gravitational_constant = 0.0;
+        for (id, x) in [(1, 0.0), (2, 5.0)] {
+            let mut node = node::Node::new(node::Id(id), node::Position::from((x, 0.0, 0.0)));
+            node.velocity = node::Force::from((1.0, 0.0, 0.0));
+            node.dampen_rate = 0.0;
+            simulation.add_node(node);
+        }
+        simulation
+            .add_sink(sink::Sink::new(1, node::Position::from((2.0, 0.0, 0.0)), 0.5).unwrap())
+            .unwrap();
+
+        simulation.step();
+        assert_eq!(2, simulation.nodes.len());
+        simulation.step();
+
+        assert_eq!(None, simulation.nodes.find(node::Id(1)));
+        assert!(simulation.nodes.find(node::Id(2)).is_some());
+        assert_eq!(1, simulation.sinks[0].absorbed());
+    }
+
+    #[test]
+    fn keeps_emitters_and_sinks_in_copies_of_the_simulation() {
+        let mut simulation = Simulation::new();
+        simulation
+            .add_sink(sink::Sink::new(1, node::Position::default(), 1.0).unwrap())
+            .unwrap();
+        assert!(simulation
+            .add_sink(sink::Sink::new(1, node::Position::default(), 2.0).unwrap())
+            .is_err());
+
+        let copy = simulation.clone();
+
+        assert_eq!(1, copy.sinks.len());
+        assert_eq!(simulation.sinks, copy.sinks);
+    }
+
+    fn total_momentum(simulation: &Simulation) -> node::Force {
+        simulation
+            .nodes
+            .iter()
+            .map(|node| node.velocity * node.mass)
+            .sum()
+    }
+
+    fn simulation_with_mixed_overrides() -> Simulation {
+        let mut simulation = Simulation::new();
+        let positions = [(0.0, 0.0, 0.0), (1.0, 0.5, 0.0), (-0.5, 1.0, 1.0)];
+        for (id, position) in positions.into_iter().enumerate() {
+            let mut node = node::Node::new(node::Id(id as u32), node::Position::from(position));
+            node.mass = 1.0 + id as node::Scalar;
+            node.dampen_rate = 0.0;
+            simulation.add_node(node);
+        }
+        simulation.nodes.modify(node::Id(0), |node| {
+            node.gravitational_constant_override = Some(2.0)
+        });
+        simulation
+    }
+
+    #[test]
+    fn conserves_momentum_with_symmetric_pair_forces() {
+        let mut simulation = simulation_with_mixed_overrides();
+        simulation.pair_force_mode = node::force::PairForceMode::Symmetric;
+
+        for _ in 0..10 {
+            simulation.step();
+        }
+
+        assert!(total_momentum(&simulation).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn does_not_conserve_momentum_with_asymmetric_gravitational_constant_overrides() {
+        let mut simulation = simulation_with_mixed_overrides();
+        simulation.pair_force_mode = node::force::PairForceMode::Asymmetric;
+
+        simulation.step();
+
+        assert!(total_momentum(&simulation).magnitude() > 0.1);
+    }
+
+    #[test]
+    fn symmetric_and_asymmetric_modes_match_without_overrides() {
+        let mut symmetric = simulation_with_mixed_overrides();
+        symmetric.nodes.modify(node::Id(0), |node| {
+            node.gravitational_constant_override = None
+        });
+        let mut asymmetric = symmetric.clone();
+        asymmetric.pair_force_mode = node::force::PairForceMode::Asymmetric;
+
+        symmetric.step();
+        asymmetric.step();
+
+        for (a, b) in symmetric.nodes.iter().zip(asymmetric.nodes.iter()) {
+            assert!((a.position.0 - b.position.0).magnitude() < 1e-6);
+        }
+    }
+
+    #[rstest::rstest]
+    #[case(node::force::PairForceMode::Symmetric, false)]
+    #[case(node::force::PairForceMode::Asymmetric, true)]
+    fn only_pulls_nodes_with_a_zero_gravitational_constant_override_in_asymmetric_mode(
+        #[case] pair_force_mode: node::force::PairForceMode,
+        #[case] expected_to_be_pulled: bool,
+    ) {
+        let mut simulation = simulation_with_mixed_overrides();
+        simulation.pair_force_mode = pair_force_mode;
+        simulation.nodes.modify(node::Id(0), |node| {
+            node.gravitational_constant_override = Some(0.0)
+        });
+
+        simulation.step();
+
+        let pulled = simulation
+            .nodes
+            .find(node::Id(0))
+            .unwrap()
+            .velocity
+            .magnitude()
+            > 0.0;
+        assert_eq!(expected_to_be_pulled, pulled);
+    }
+
+    #[test]
+    fn conserves_energy_with_symmetric_pair_forces() {
+        let mut simulation = simulation_with_mixed_overrides();
+        simulation.nodes.modify(node::Id(0), |node| {
+            node.gravitational_constant_override = Some(0.002)
+        });
+        simulation.set_softening_length(0.5);
+        simulation.gravitational_constant = 0.001;
+        let initial_energy = simulation.stats().total_energy();
+
+        for _ in 0..100 {
+            simulation.step();
+        }
+
+        let relative_energy_drift =
+            ((simulation.stats().total_energy() - initial_energy) / initial_energy).abs();
+        assert!(
+            relative_energy_drift < 0.02,
+            "energy drifted by {relative_energy_drift}"
+        );
+    }
+
+    #[test]
+    fn slows_nodes_by_the_same_amount_regardless_of_time_step() {
+        let mut simulation = Simulation::new();
+        simulation.drag = node::drag::Drag {
+            model: node::drag::DragModel::Linear,
+            medium_density: 1.0,
+        };
+        let mut node = node::Node::new(node::Id(1), node::Position::default());
+        node.velocity = node::Force::from((1.0, 0.0, 0.0));
+        simulation.add_node(node);
+        let mut subdivided_simulation = simulation.clone();
+        subdivided_simulation.time_step = 0.1;
+
+        simulation.step();
+        for _ in 0..10 {
+            subdivided_simulation.step();
+        }
+
+        let velocity = simulation.nodes.get(0).unwrap().velocity.magnitude();
+        let subdivided_velocity = subdivided_simulation
+            .nodes
+            .get(0)
+            .unwrap()
+            .velocity
+            .magnitude();
+        assert!((velocity - subdivided_velocity).abs() < 1e-5);
+    }
+
+    #[rstest::rstest]
+    #[case(adaptive_step::AdaptiveStepMode::Acceleration)]
+    #[case(adaptive_step::AdaptiveStepMode::ErrorEstimate)]
+    #[case(adaptive_step::AdaptiveStepMode::Block)]
+    fn dampens_nodes_by_the_same_amount_with_fixed_and_adaptive_steps(
+        #[case] mode: adaptive_step::AdaptiveStepMode,
+    ) {
+        let mut fixed = Simulation::new();
+        let mut node = node::Node::new(node::Id(1), node::Position::default());
+        node.velocity = node::Force::from((1.0, 0.0, 0.0));
+        node.dampen_rate = 0.5;
+        fixed.add_node(node);
+        let mut adaptive = fixed.clone();
+        adaptive.adaptive_step.mode = mode;
+        adaptive.adaptive_step.max_time_step = 0.1;
+
+        fixed.step_by(0.5);
+        adaptive.step_by(0.5);
+
+        let speed = |simulation: &Simulation| simulation.nodes.get(0).unwrap().velocity.magnitude();
+        assert!(adaptive.adaptive_step.time_step() < 0.5);
+        assert_eq!(0.5, speed(&fixed));
+        assert_eq!(speed(&fixed), speed(&adaptive));
+    }
+
+    #[test]
+    fn moves_kinematic_nodes_along_their_path_while_they_exert_force() {
+        let mut simulation = Simulation::new();
+        simulation.time_step = 0.5;
+        let mut kinematic_node = node::Node::new(node::Id(1), node::Position::default());
+        kinematic_node.mass = 100.0;
+        simulation.add_node(kinematic_node);
+        simulation.add_node(node::Node::new(
+            node::Id(2),
+            node::Position::from((3.0, 0.0, 0.0)),
+        ));
+        let path = node::kinematic::Path::keyframes(
+            vec![
+                node::Position::from((0.0, 0.0, 0.0)),
+                node::Position::from((0.0, 0.0, 1.0)),
+            ],
+            1.0,
+        )
+        .unwrap();
+        simulation
+            .handle_event(node::Event::SetNode(node::event::set_node::SetNodeEvent {
+                id: node::Id(1),
+                position: None,
+                velocity: None,
+                position_relative_to: None,
+                velocity_relative_to: None,
+                mass: None,
+                radius: None,
+                gravitational_constant_override: None,
+                dampen_rate: None,
+                drag_coefficient: None,
+                freeze: None,
+                locked_axes: None,
+                constraint: None,
+                kinematic: Some(Some(path)),
+            }))
+            .unwrap();
+
+        simulation.step();
+
+        let kinematic_node = simulation.nodes.get(0).unwrap();
+        let other_node = simulation.nodes.get(1).unwrap();
+        assert_eq!(
+            node::Position::from((0.0, 0.0, 0.5)),
+            kinematic_node.position
+        );
+        assert_eq!(node::Force::from((0.0, 0.0, 1.0)), kinematic_node.velocity);
+        assert!(other_node.position.0.x > 3.0, "Kinematic node still pushes");
+    }
+
+    #[test]
+    fn adds_nodes_relative_to_other_nodes_when_the_event_is_handled() {
+        let mut simulation = Simulation::new();
+        let mut parent = node::Node::new(node::Id(1), node::Position::from((1.0, 2.0, 3.0)));
+        parent.velocity = node::Force::from((0.0, 1.0, 0.0));
+        simulation.add_node(parent);
+
+        simulation
+            .handle_event(node::Event::AddNode(node::AddNodeEvent {
+                node: node::Node::new(node::Id(2), node::Position::from((2.0, 0.0, 0.0))),
+                position_relative_to: Some(node::Id(1)),
+            }))
+            .unwrap();
+        simulation
+            .handle_event(node::Event::SetNode(node::event::set_node::SetNodeEvent {
+                id: node::Id(2),
+                position: None,
+                velocity: Some(node::Force::from((0.0, 0.0, 1.0))),
+                position_relative_to: None,
+                velocity_relative_to: Some(node::Id(1)),
+                mass: None,
+                radius: None,
+                gravitational_constant_override: None,
+                dampen_rate: None,
+                drag_coefficient: None,
+                freeze: None,
+                locked_axes: None,
+                constraint: None,
+                kinematic: None,
+            }))
+            .unwrap();
+
+        let child = simulation.nodes.find(node::Id(2)).unwrap();
+        assert_eq!(node::Position::from((3.0, 2.0, 3.0)), child.position);
+        assert_eq!(node::Force::from((0.0, 1.0, 1.0)), child.velocity);
+        assert!(simulation
+            .handle_event(node::Event::AddNode(node::AddNodeEvent {
+                node: node::Node::new(node::Id(3), node::Position::default()),
+                position_relative_to: Some(node::Id(4)),
+            }))
+            .is_err());
+    }
+
+    /// Adds a node orbiting a parent at `semi_major_axis`, and checks it is still on the same orbit
+    /// most of the way around
+    fn assert_orbiter_keeps_to_its_orbit(
+        mut simulation: Simulation,
+        parent_velocity: node::Force,
+        semi_major_axis: node::Scalar,
+        time_step: node::Scalar,
+    ) {
+        let mut parent = node::Node::new(node::Id(1), node::Position::from((1.0, 0.0, 0.0)));
+        parent.velocity = parent_velocity;
+        parent.dampen_rate = 0.0;
+        simulation.add_node(parent);
+        let mut orbiter = node::Node::new(node::Id(2), node::Position::default());
+        orbiter.dampen_rate = 0.0;
+        let elements =
+            node::orbit::Elements::new(semi_major_axis, 0.1, 0.3, 0.0, 0.0, 0.0).unwrap();
+
+        simulation
+            .handle_event(node::Event::AddOrbiter(node::AddOrbiterEvent {
+                node: orbiter,
+                around: node::Id(1),
+                elements,
+            }))
+            .unwrap();
+        let (around, orbit, gravitational_parameter) =
+            simulation.orbit_of(node::Id(2), None).unwrap();
+        assert_eq!(node::Id(1), around);
+        assert!((orbit.semi_major_axis / semi_major_axis - 1.0).abs() < 1.0e-4);
+        assert!((orbit.eccentricity - 0.1).abs() < 1.0e-4);
+
+        simulation.time_step = time_step;
+        let period = orbit.period(gravitational_parameter).unwrap();
+        for _ in 0..(0.8 * period / time_step) as u32 {
+            simulation.step();
+        }
+        let (_, orbit, _) = simulation.orbit_of(node::Id(2), None).unwrap();
+        assert!(
+            (orbit.semi_major_axis / semi_major_axis - 1.0).abs() < 5.0e-3,
+            "semi-major axis was {}",
+            orbit.semi_major_axis
+        );
+        assert!((orbit.eccentricity - 0.1).abs() < 5.0e-3);
+        assert!((orbit.inclination - 0.3).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn adds_orbiters_that_keep_to_their_orbit() {
+        let mut simulation = Simulation::new();
+        simulation.gravitational_constant = 1.0;
+        let parent_velocity = node::Force::from((0.0, 0.5, 0.0));
+        assert_orbiter_keeps_to_its_orbit(simulation, parent_velocity, 2.0, 0.01);
+    }
+
+    #[test]
+    fn adds_orbiters_beyond_the_dimensionless_force_radius_in_real_world_units() {
+        let mut simulation = Simulation::new();
+        simulation.set_units(units::UnitSystem::Physical(units::Units::ASTRONOMICAL));
+        // Jupiter, around a Sun that starts at rest and recoils slowly, with a period of about
+        // 3000 days as both have the mass of the Sun
+        assert_orbiter_keeps_to_its_orbit(simulation, node::Force::zero(), 5.2, 4.0);
+    }
+
+    #[test]
+    fn can_not_add_orbiters_that_would_leave_the_force_radius() {
+        let mut simulation = Simulation::new();
+        simulation.gravitational_constant = 1.0;
+        simulation.add_node(node::Node::new(node::Id(1), node::Position::default()));
+        let elements = node::orbit::Elements::new(4.0, 0.5, 0.0, 0.0, 0.0, 0.0).unwrap();
+
+        let result = simulation.handle_event(node::Event::AddOrbiter(node::AddOrbiterEvent {
+            node: node::Node::new(node::Id(2), node::Position::default()),
+            around: node::Id(1),
+            elements,
+        }));
+
+        assert_eq!(
+            Err("Error adding node with id 2 - its orbit would reach 6 from node 1, beyond the force radius of 5 where nodes stop interacting".to_string()),
+            result
+        );
+    }
+
+    #[test]
+    fn only_finds_orbits_around_nodes_within_the_force_radius() {
+        let mut simulation = Simulation::new();
+        simulation.gravitational_constant = 1.0;
+        let mut far_node = node::Node::new(node::Id(1), node::Position::from((10.0, 0.0, 0.0)));
+        far_node.mass = 1000.0;
+        simulation.add_node(far_node);
+        let mut orbiter = node::Node::new(node::Id(2), node::Position::default());
+        orbiter.velocity = node::Force::from((0.1, 0.0, 0.0));
+        simulation.add_node(orbiter);
+        simulation.add_node(node::Node::new(
+            node::Id(3),
+            node::Position::from((0.0, 0.0, 1.0)),
+        ));
+
+        let (around, _, _) = simulation.orbit_of(node::Id(2), None).unwrap();
+        assert_eq!(node::Id(3), around);
+        assert!(simulation.orbit_of(node::Id(2), Some(node::Id(1))).is_err());
+    }
+
+    #[test]
+    fn can_not_add_orbiters_around_nodes_that_push_them_away() {
+        let mut simulation = Simulation::new();
+        simulation.add_node(node::Node::new(node::Id(1), node::Position::default()));
+        let result = simulation.handle_event(node::Event::AddOrbiter(node::AddOrbiterEvent {
+            node: node::Node::new(node::Id(2), node::Position::default()),
+            around: node::Id(1),
+            elements: node::orbit::Elements::new(1.0, 0.0, 0.0, 0.0, 0.0, 0.0).unwrap(),
+        }));
+        assert_eq!(
+            Err("Error adding node with id 2 - gravity between nodes 2 and 1 pushes them apart, so they can not orbit eachother".to_string()),
+            result
+        );
+        assert!(simulation.orbit_of(node::Id(1), None).is_err());
+    }
+
+    #[test]
+    fn has_no_orbit_when_moving_straight_towards_the_node_orbited() {
+        let mut simulation = Simulation::new();
+        simulation.gravitational_constant = 1.0;
+        simulation.add_node(node::Node::new(node::Id(1), node::Position::default()));
+        let mut faller = node::Node::new(node::Id(2), node::Position::from((2.0, 0.0, 0.0)));
+        faller.velocity = node::Force::from((-1.0, 0.0, 0.0));
+        simulation.add_node(faller);
+
+        assert!(simulation.orbit_of(node::Id(2), None).is_err());
+    }
+
+    #[test]
+    fn uses_the_real_gravitational_constant_of_its_units() {
+        let mut simulation = Simulation::new();
+        assert_eq!(-1.0, simulation.gravitational_constant);
+
+        simulation
+            .handle_event(node::Event::SetUnits(node::SetUnitsEvent {
+                units: units::UnitSystem::Physical(units::Units::ASTRONOMICAL),
+            }))
+            .unwrap();
+        assert_eq!(
+            units::Units::ASTRONOMICAL.gravitational_constant(),
+            simulation.gravitational_constant
+        );
+
+        simulation.set_units(units::UnitSystem::Dimensionless);
+        assert_eq!(-1.0, simulation.gravitational_constant);
+    }
+
+    #[test]
+    fn attracts_nodes_at_any_distance_in_real_world_units() {
+        let mut simulation = Simulation::new();
+        simulation.set_units(units::UnitSystem::Physical(units::Units::SI));
+        for (id, x) in [(1, 0.0), (2, 10.0)] {
+            let mut node = node::Node::new(node::Id(id), node::Position::from((x, 0.0, 0.0)));
+            node.mass = 1.0e10;
+            node.dampen_rate = 0.0;
+            simulation.add_node(node);
+        }
+
+        simulation.step();
+
+        let node = simulation.nodes.find(node::Id(1)).unwrap();
+        assert!(node.velocity.0.x > 0.0, "velocity was {}", node.velocity);
+        assert!(simulation.stats().potential_energy < 0.0);
+        simulation.set_units(units::UnitSystem::Dimensionless);
+        assert_eq!(Some(node::Force::FORCE_RADIUS), simulation.force_radius);
+    }
+
+    #[test]
+    fn forgets_kinematic_paths_of_removed_nodes() {
+        let mut simulation = Simulation::new();
+        simulation.add_node(node::Node::new(node::Id(1), node::Position::default()));
+        let path = node::kinematic::Path::keyframes(vec![node::Position::default()], 1.0);
+        simulation.kinematics.insert(
+            node::Id(1),
+            node::kinematic::Kinematic {
+                path: path.unwrap(),
+                start_time: 0.0,
+            },
+        );
+
+        simulation.remove_node(node::Id(1));
+
+        assert!(simulation.kinematics.is_empty());
+    }
+
+    #[test]
+    fn keeps_nodes_on_the_xz_plane_in_2d() {
+        let mut simulation = Simulation::new();
+        let mut node = node::Node::new(node::Id(1), node::Position::from((0.0, 1.0, 0.0)));
+        node.velocity = node::Force::from((0.0, 1.0, 0.0));
+        simulation.add_node(node);
+        simulation.add_node(node::Node::new(
+            node::Id(2),
+            node::Position::from((1.0, -1.0, 1.0)),
+        ));
+
+        simulation.set_dimensionality(dimensionality::Dimensionality::TwoD);
+        for _ in 0..10 {
+            simulation.step();
+        }
+
+        for node in simulation.nodes.iter() {
+            assert_eq!(0.0, node.position.0.y);
+            assert_eq!(0.0, node.velocity.0.y);
+        }
+        assert_ne!(
+            node::Position::from((1.0, 0.0, 1.0)),
+            simulation.nodes.get(1).unwrap().position
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    fn run_on_threads(
+        threads: usize,
+        pair_force_mode: node::force::PairForceMode,
+    ) -> node_store::NodeStore {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            let mut simulation = Simulation::new();
+            simulation.pair_force_mode = pair_force_mode;
+            simulation.softening_length = 0.1;
+            for id in 0..64 {
+                let (x, y, z) = (id % 4, id / 16, (id / 4) % 4);
+                let position = (x as node::Scalar, y as node::Scalar, z as node::Scalar);
+                simulation.add_node(node::Node::new(node::Id(id), position.into()));
+            }
+            for _ in 0..20 {
+                simulation.step();
+            }
+            simulation.nodes
+        })
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn gives_identical_results_on_the_same_number_of_threads() {
+        let symmetric = node::force::PairForceMode::Symmetric;
+
+        assert_eq!(run_on_threads(4, symmetric), run_on_threads(4, symmetric));
+        assert_eq!(run_on_threads(3, symmetric), run_on_threads(3, symmetric));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn gives_identical_asymmetric_results_on_any_number_of_threads() {
+        let asymmetric = node::force::PairForceMode::Asymmetric;
+
+        assert_eq!(run_on_threads(1, asymmetric), run_on_threads(4, asymmetric));
+    }
+
+    #[test]
+    fn counts_ticks() {
+        let mut simulation = Simulation::new();
+        assert_eq!(0, simulation.tick());
+
+        simulation.step();
+        simulation.step();
+
+        assert_eq!(2, simulation.tick());
+    }
+
+    fn schedule_event(trigger: schedule::Trigger, target_tps: u32) -> node::Event {
+        node::Event::Schedule(node::ScheduleEvent {
+            trigger,
+            description: format!("set tps {}", target_tps),
+            events: vec![node::Event::SetTargetTps(node::SetTargetTpsEvent {
+                target_tps,
+            })],
+        })
+    }
+
+    #[test]
+    fn handles_scheduled_events_before_the_step_from_their_tick() {
+        let mut simulation = Simulation::new();
+        simulation
+            .handle_event(schedule_event(schedule::Trigger::AtTick(2), 7))
+            .unwrap();
+
+        simulation
+            .handle_event(node::Event::Step(node::event::step::StepEvent { steps: 2 }))
+            .unwrap();
+        assert_ne!(7, simulation.target_tps);
+
+        simulation
+            .handle_event(node::Event::Step(node::event::step::StepEvent { steps: 1 }))
+            .unwrap();
+        assert_eq!(7, simulation.target_tps);
+        assert!(simulation.schedule.is_empty());
+    }
+
+    #[test]
+    fn repeats_scheduled_events_until_cancelled() {
+        let mut simulation = Simulation::new();
+        simulation
+            .handle_event(schedule_event(schedule::Trigger::Every(2), 1))
+            .unwrap();
+        simulation
+            .handle_event(node::Event::Step(node::event::step::StepEvent { steps: 3 }))
+            .unwrap();
+        assert_eq!(1, simulation.target_tps);
+
+        simulation.target_tps = 60;
+        simulation
+            .handle_event(node::Event::Step(node::event::step::StepEvent { steps: 2 }))
+            .unwrap();
+        assert_eq!(1, simulation.target_tps);
+
+        simulation.target_tps = 60;
+        let handle = simulation.schedule.iter().next().unwrap().handle;
+        simulation
+            .handle_event(node::Event::CancelScheduled(node::CancelScheduledEvent {
+                handle,
+            }))
+            .unwrap();
+        simulation
+            .handle_event(node::Event::Step(node::event::step::StepEvent { steps: 4 }))
+            .unwrap();
+        assert_eq!(60, simulation.target_tps);
+    }
+
+    #[test]
+    pub fn can_handle_set_softening_event() {
+        let mut simulation = Simulation::new();
+        let event = node::Event::SetSoftening(node::SetSofteningEvent {
+            softening_length: 0.5,
+        });
+
+        simulation.handle_event(event).unwrap();
+
+        assert_eq!(0.5, simulation.softening_length);
+    }
 }
@@ -0,0 +1,321 @@
+use std::fmt;
+
+use super::units::{Quantity, UnitSystem};
+use crate::node::Scalar;
+
+/// How the sub-steps taken within each step of the simulation are chosen
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum AdaptiveStepMode {
+    /// Each step is taken as a single sub-step of the full time step
+    #[default]
+    Fixed,
+    /// Sub-steps last as long as the node with the greatest acceleration takes to move `tolerance`
+    /// from rest
+    Acceleration,
+    /// Sub-steps are sized so that the distance between where they leave each node and where a
+    /// trapezoidal step using the forces at their end would stays within `tolerance`. Sub-steps
+    /// with a larger difference are rejected and retried with a smaller time step. This is not a
+    /// Runge-Kutta error estimate, as sub-steps are taken with semi-implicit Euler, but like an
+    /// embedded pair the forces at the end of an accepted sub-step start the next one
+    ErrorEstimate,
+    /// Each node takes sub-steps of a power of two fraction of the time step, as long as it takes
+    /// the node to move `tolerance` from rest under its own acceleration. Does not conserve
+    /// momentum, as nodes on different levels are kicked at different times
+    Block,
+}
+
+impl fmt::Display for AdaptiveStepMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdaptiveStepMode::Fixed => write!(f, "fixed"),
+            AdaptiveStepMode::Acceleration => write!(f, "acceleration"),
+            AdaptiveStepMode::ErrorEstimate => write!(f, "error-estimate"),
+            AdaptiveStepMode::Block => write!(f, "block"),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct AdaptiveStep {
+    pub mode: AdaptiveStepMode,
+    /// Distance nodes may move, or be in error by, over a single sub-step
+    pub tolerance: Scalar,
+    pub min_time_step: Scalar,
+    pub max_time_step: Scalar,
+    time_step: Scalar,
+    rejected_steps: u64,
+}
+
+impl Default for AdaptiveStep {
+    fn default() -> Self {
+        AdaptiveStep {
+            mode: AdaptiveStepMode::default(),
+            tolerance: 0.001,
+            min_time_step: 0.00001,
+            max_time_step: 1.0,
+            time_step: 1.0,
+            rejected_steps: 0,
+        }
+    }
+}
+
+impl AdaptiveStep {
+    /// Never shrink a sub-step by more than this factor after an error estimate
+    const MIN_SCALE: Scalar = 0.2;
+    /// Never grow a sub-step by more than this factor after an error estimate
+    const MAX_SCALE: Scalar = 5.0;
+    /// Aim below the tolerance, so the next sub-step is less likely to be rejected
+    const SAFETY_FACTOR: Scalar = 0.9;
+    /// Never split a step into more than 2^MAX_BLOCK_LEVEL sub-steps
+    const MAX_BLOCK_LEVEL: u32 = 20;
+
+    /// Describes the adaptive step, with each value followed by the unit it is measured in
+    pub fn to_display_string(&self, units: &UnitSystem) -> String {
+        let time = units.suffix(Quantity::Time);
+        format!(
+            "mode: {}, tolerance: {}{}, min time step: {}{}, max time step: {}{}",
+            self.mode,
+            self.tolerance,
+            units.suffix(Quantity::Length),
+            self.min_time_step,
+            time,
+            self.max_time_step,
+            time
+        )
+    }
+
+    /// Length of the last sub-step that was taken, or the next sub-step that will be tried when
+    /// estimating error
+    pub fn time_step(&self) -> Scalar {
+        self.time_step
+    }
+
+    /// Number of sub-steps that have been rejected and retried since the adaptive step was set
+    pub fn rejected_steps(&self) -> u64 {
+        self.rejected_steps
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tolerance <= 0.0 {
+            return Err("Tolerance must be greater than 0".to_string());
+        }
+        if self.min_time_step <= 0.0 {
+            return Err("Min time step must be greater than 0".to_string());
+        }
+        if self.min_time_step > self.max_time_step {
+            return Err("Min time step must not be greater than max time step".to_string());
+        }
+        Ok(())
+    }
+
+    /// Starts a new run of sub-steps from the max time step
+    pub fn reset(&mut self) {
+        self.time_step = self.max_time_step;
+        self.rejected_steps = 0;
+    }
+
+    /// Chooses a sub-step from the greatest acceleration of any node, no longer than `remaining`
+    pub fn choose_from_acceleration(
+        &mut self,
+        max_acceleration: Scalar,
+        remaining: Scalar,
+    ) -> Scalar {
+        // Distance moved from rest is a * dt^2 / 2
+        self.time_step = match max_acceleration > 0.0 {
+            true => self.clamp((2.0 * self.tolerance / max_acceleration).sqrt()),
+            false => self.max_time_step,
+        };
+        self.time_step.min(remaining)
+    }
+
+    /// Chooses a sub-step no longer than `remaining`, using `estimate_error` to find the error of
+    /// a sub-step of a given length. Sub-steps are shrunk and retried until their error is within
+    /// the tolerance, or they reach the min time step. Sub-steps with an infinite or NaN error are
+    /// retried at the min time step.
+    pub fn choose_from_error_estimate<F>(
+        &mut self,
+        remaining: Scalar,
+        mut estimate_error: F,
+    ) -> Scalar
+    where
+        F: FnMut(Scalar) -> Scalar,
+    {
+        loop {
+            let time_step = self.time_step.min(remaining);
+            let error = estimate_error(time_step);
+            let accepted = error <= self.tolerance || time_step <= self.min_time_step;
+            self.time_step = match error.is_finite() {
+                true => self.scale(time_step, error),
+                // An error that is not a number gives no idea of how far to shrink, so the
+                // sub-step is shrunk as far as it can go
+                false => self.min_time_step,
+            };
+            if accepted {
+                return time_step;
+            }
+            self.rejected_steps += 1;
+        }
+    }
+
+    /// Chooses the block level of every node from its acceleration. A node on level `n` takes
+    /// sub-steps of `time_step / 2^n`. Levels are the lowest that keep sub-steps within the
+    /// tolerance and the max time step, without going below the min time step.
+    pub fn choose_block_levels(&mut self, accelerations: &[Scalar], time_step: Scalar) -> Vec<u32> {
+        let level_for = |max_sub_step: Scalar| match max_sub_step < time_step {
+            true => (time_step / max_sub_step).log2().ceil() as u32,
+            false => 0,
+        };
+        let max_level = match self.min_time_step < time_step {
+            true => (time_step / self.min_time_step).log2().floor() as u32,
+            false => 0,
+        }
+        .min(Self::MAX_BLOCK_LEVEL);
+        let min_level = level_for(self.max_time_step).min(max_level);
+        let levels: Vec<u32> = accelerations
+            .iter()
+            .map(|acceleration| {
+                // Distance moved from rest is a * dt^2 / 2
+                let level = match *acceleration > 0.0 {
+                    true => level_for((2.0 * self.tolerance / acceleration).sqrt()),
+                    false => 0,
+                };
+                level.clamp(min_level, max_level)
+            })
+            .collect();
+        let finest_level = levels.iter().copied().max().unwrap_or(min_level);
+        self.time_step = time_step / (1u64 << finest_level) as Scalar;
+        levels
+    }
+
+    fn scale(&self, time_step: Scalar, error: Scalar) -> Scalar {
+        // The error of a sub-step shrinks with the square of the time step
+        let scale = match error > 0.0 {
+            true => (Self::SAFETY_FACTOR * (self.tolerance / error).sqrt())
+                .clamp(Self::MIN_SCALE, Self::MAX_SCALE),
+            false => Self::MAX_SCALE,
+        };
+        self.clamp(time_step * scale)
+    }
+
+    fn clamp(&self, time_step: Scalar) -> Scalar {
+        time_step.min(self.max_time_step).max(self.min_time_step)
+    }
+}
+
+#[cfg(test)]
+mod an_adaptive_step {
+    use super::*;
+
+    #[test]
+    fn takes_the_max_time_step_without_acceleration() {
+        let mut adaptive_step = AdaptiveStep::default();
+
+        assert_eq!(1.0, adaptive_step.choose_from_acceleration(0.0, 2.0));
+        assert_eq!(0.5, adaptive_step.choose_from_acceleration(0.0, 0.5));
+    }
+
+    #[test]
+    fn takes_shorter_steps_under_greater_acceleration() {
+        let mut adaptive_step = AdaptiveStep {
+            tolerance: 2.0,
+            ..Default::default()
+        };
+
+        assert_eq!(0.5, adaptive_step.choose_from_acceleration(16.0, 1.0));
+        let time_step = adaptive_step.choose_from_acceleration(1e20, 1.0);
+        assert_eq!(adaptive_step.min_time_step, time_step);
+    }
+
+    #[test]
+    fn rejects_steps_until_the_error_is_within_tolerance() {
+        let mut adaptive_step = AdaptiveStep::default();
+        let mut tried = vec![];
+
+        let time_step = adaptive_step.choose_from_error_estimate(1.0, |time_step| {
+            tried.push(time_step);
+            time_step * time_step
+        });
+
+        assert!(time_step * time_step <= adaptive_step.tolerance);
+        assert_eq!(tried.len() as u64 - 1, adaptive_step.rejected_steps());
+        assert!(adaptive_step.rejected_steps() > 0);
+        assert!(tried.windows(2).all(|pair| pair[1] < pair[0]));
+    }
+
+    #[test]
+    fn grows_steps_when_the_error_is_small() {
+        let mut adaptive_step = AdaptiveStep {
+            time_step: 0.125,
+            ..Default::default()
+        };
+
+        let time_step = adaptive_step.choose_from_error_estimate(1.0, |_| 0.0);
+
+        assert_eq!(0.125, time_step);
+        assert_eq!(0.625, adaptive_step.time_step());
+        assert_eq!(0, adaptive_step.rejected_steps());
+    }
+
+    #[test]
+    fn accepts_the_min_time_step_whatever_the_error() {
+        let mut adaptive_step = AdaptiveStep::default();
+
+        let time_step = adaptive_step.choose_from_error_estimate(1.0, |_| Scalar::INFINITY);
+
+        assert_eq!(adaptive_step.min_time_step, time_step);
+    }
+
+    #[test]
+    fn shrinks_to_the_min_time_step_when_the_error_is_not_a_number() {
+        let mut adaptive_step = AdaptiveStep::default();
+        let mut tried = vec![];
+
+        let time_step = adaptive_step.choose_from_error_estimate(1.0, |time_step| {
+            tried.push(time_step);
+            Scalar::NAN
+        });
+
+        assert_eq!(adaptive_step.min_time_step, time_step);
+        assert_eq!(vec![1.0, adaptive_step.min_time_step], tried);
+        assert_eq!(1, adaptive_step.rejected_steps());
+    }
+
+    #[test]
+    fn puts_nodes_on_block_levels_by_acceleration() {
+        let mut adaptive_step = AdaptiveStep {
+            tolerance: 0.5,
+            ..Default::default()
+        };
+
+        // Accelerations with ideal sub-steps of 1, 0.5, 0.1 and 0.01
+        let levels = adaptive_step.choose_block_levels(&[0.0, 4.0, 100.0, 10000.0], 1.0);
+
+        assert_eq!(vec![0, 1, 4, 7], levels);
+        assert_eq!(1.0 / 128.0, adaptive_step.time_step());
+    }
+
+    #[test]
+    fn keeps_block_levels_within_the_min_and_max_time_step() {
+        let mut adaptive_step = AdaptiveStep {
+            tolerance: 0.5,
+            min_time_step: 0.1,
+            max_time_step: 0.5,
+            ..Default::default()
+        };
+
+        let levels = adaptive_step.choose_block_levels(&[0.0, 10000.0], 1.0);
+
+        assert_eq!(vec![1, 3], levels);
+    }
+
+    #[test]
+    fn is_invalid_if_min_time_step_is_greater_than_max() {
+        let adaptive_step = AdaptiveStep {
+            min_time_step: 2.0,
+            ..Default::default()
+        };
+
+        assert!(adaptive_step.validate().is_err());
+        assert!(AdaptiveStep::default().validate().is_ok());
+    }
+}
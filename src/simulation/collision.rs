@@ -0,0 +1,41 @@
+use std::fmt;
+
+use crate::node;
+
+/// What happens when two nodes come within their combined radius of eachother
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum CollisionPolicy {
+    /// Nodes pass through eachother
+    #[default]
+    None,
+    /// Nodes merge into a single node, conserving mass and momentum
+    Merge,
+}
+
+impl fmt::Display for CollisionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CollisionPolicy::None => write!(f, "none"),
+            CollisionPolicy::Merge => write!(f, "merge"),
+        }
+    }
+}
+
+/// Two nodes that merged into one
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Merge {
+    /// Id of the merged node
+    pub survivor: node::Id,
+    /// Id of the node that was removed from the simulation
+    pub removed: node::Id,
+}
+
+impl fmt::Display for Merge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Node {} merged into node {}",
+            self.removed, self.survivor
+        )
+    }
+}
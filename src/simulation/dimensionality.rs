@@ -0,0 +1,96 @@
+use std::fmt;
+
+use crate::node;
+
+/// Number of axes nodes are integrated along. 2D simulations are restricted to the XZ plane, so
+/// they can be viewed top-down.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Dimensionality {
+    TwoD,
+    #[default]
+    ThreeD,
+}
+
+impl fmt::Display for Dimensionality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Dimensionality::TwoD => write!(f, "2d"),
+            Dimensionality::ThreeD => write!(f, "3d"),
+        }
+    }
+}
+
+impl Dimensionality {
+    /// Moves a node onto the XZ plane if the simulation is 2D, and removes any velocity that would
+    /// take it off of the plane
+    pub fn apply(&self, position: &mut node::Position, velocity: &mut node::Force) {
+        if *self == Dimensionality::TwoD {
+            position.0.y = 0.0;
+            velocity.0.y = 0.0;
+        }
+    }
+
+    /// Expands the two components "a,b" of a vector into the three components "a,0,b" when the
+    /// simulation is 2D. Three components are left as they are, so they can be parsed as normal.
+    pub fn expand_components<'a>(
+        &self,
+        mut components: Vec<&'a str>,
+        name: &str,
+    ) -> Result<Vec<&'a str>, String> {
+        if *self == Dimensionality::ThreeD {
+            return Ok(components);
+        }
+        match components.len() {
+            2 => {
+                components.insert(1, "0");
+                Ok(components)
+            }
+            3 => Ok(components),
+            _ => Err(format!("{name} must have 2 or 3 values")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod a_dimensionality {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(Dimensionality::TwoD, &["1", "2"], &["1", "0", "2"])]
+    #[case(Dimensionality::TwoD, &["1", " 2"], &["1", "0", " 2"])]
+    #[case(Dimensionality::TwoD, &["1", "2", "3"], &["1", "2", "3"])]
+    #[case(Dimensionality::ThreeD, &["1", "2"], &["1", "2"])]
+    fn expands_two_component_vectors_in_2d(
+        #[case] dimensionality: Dimensionality,
+        #[case] components: &[&str],
+        #[case] expected_components: &[&str],
+    ) {
+        assert_eq!(
+            Ok(expected_components.to_vec()),
+            dimensionality.expand_components(components.to_vec(), "Position")
+        );
+    }
+
+    #[test]
+    fn rejects_vectors_with_the_wrong_number_of_components_in_2d() {
+        assert_eq!(
+            Err("Position must have 2 or 3 values".to_string()),
+            Dimensionality::TwoD.expand_components(vec!["1"], "Position")
+        );
+    }
+
+    #[test]
+    fn flattens_nodes_onto_the_xz_plane_in_2d() {
+        let mut position = node::Position::from((1.0, 2.0, 3.0));
+        let mut velocity = node::Force::from((1.0, 1.0, 1.0));
+
+        Dimensionality::ThreeD.apply(&mut position, &mut velocity);
+        assert_eq!(node::Position::from((1.0, 2.0, 3.0)), position);
+
+        Dimensionality::TwoD.apply(&mut position, &mut velocity);
+        assert_eq!(node::Position::from((1.0, 0.0, 3.0)), position);
+        assert_eq!(node::Force::from((1.0, 0.0, 1.0)), velocity);
+    }
+}
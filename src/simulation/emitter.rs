@@ -0,0 +1,264 @@
+use std::fmt;
+
+use super::random::Random;
+use crate::node::{self, Force, Node, Position, Scalar};
+
+/// How the velocities of emitted nodes are spread around the mean velocity
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum VelocityDistribution {
+    /// Every node is emitted with the mean velocity
+    Fixed,
+    /// Velocities are spread evenly within a sphere of radius `spread` around the mean
+    Uniform { spread: Scalar },
+    /// Each component of the velocity is normally distributed around the mean, with a standard
+    /// deviation of `spread`
+    Normal { spread: Scalar },
+}
+
+impl fmt::Display for VelocityDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VelocityDistribution::Fixed => write!(f, "fixed"),
+            VelocityDistribution::Uniform { spread } => write!(f, "uniform with spread {}", spread),
+            VelocityDistribution::Normal { spread } => write!(f, "normal with spread {}", spread),
+        }
+    }
+}
+
+/// Spawns nodes at a point at a steady rate
+#[derive(PartialEq, Debug, Clone)]
+pub struct Emitter {
+    pub id: u32,
+    pub position: Position,
+    /// Nodes emitted per unit of simulated time
+    pub rate: Scalar,
+    pub velocity: Force,
+    pub velocity_distribution: VelocityDistribution,
+    /// Mass of every emitted node
+    pub mass: Scalar,
+    random: Random,
+    /// Fraction of a node carried over from previous steps
+    pending: Scalar,
+    emitted: u64,
+}
+
+impl fmt::Display for Emitter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Emitter {}:\n\tposition: {}\n\trate: {}\n\tvelocity: {}\n\tvelocity distribution: {}\n\tmass: {}\n\temitted: {}",
+            self.id,
+            self.position,
+            self.rate,
+            self.velocity,
+            self.velocity_distribution,
+            self.mass,
+            self.emitted
+        )
+    }
+}
+
+impl Emitter {
+    /// Never emit more than this many nodes in one step, however long the step or high the rate
+    pub const MAX_NODES_PER_STEP: u64 = 10_000;
+
+    /// Creates an emitter whose velocities are drawn from a random sequence starting at `seed`
+    pub fn new(
+        id: u32,
+        position: Position,
+        rate: Scalar,
+        velocity: Force,
+        velocity_distribution: VelocityDistribution,
+        mass: Scalar,
+        seed: u64,
+    ) -> Result<Self, String> {
+        if !rate.is_finite() || rate < 0.0 {
+            return Err("Emitter rate must be a finite number that is not negative".to_string());
+        }
+        if mass <= 0.0 {
+            return Err("Emitter mass must be greater than 0".to_string());
+        }
+        let spread = match velocity_distribution {
+            VelocityDistribution::Fixed => 0.0,
+            VelocityDistribution::Uniform { spread } | VelocityDistribution::Normal { spread } => {
+                spread
+            }
+        };
+        if spread < 0.0 {
+            return Err("Emitter velocity spread must not be negative".to_string());
+        }
+        Ok(Emitter {
+            id,
+            position,
+            rate,
+            velocity,
+            velocity_distribution,
+            mass,
+            random: Random::new(seed),
+            pending: 0.0,
+            emitted: 0,
+        })
+    }
+
+    /// Number of nodes the emitter has spawned
+    pub fn emitted(&self) -> u64 {
+        self.emitted
+    }
+
+    /// Nodes emitted over `dt`, up to [`Emitter::MAX_NODES_PER_STEP`]. Nodes over the limit are
+    /// dropped rather than carried over, so an emitter that cannot keep up does not fall further
+    /// behind every step. Each node is given an id from `allocate_id`.
+    pub fn emit<F>(&mut self, dt: Scalar, mut allocate_id: F) -> Vec<Node>
+    where
+        F: FnMut() -> node::Id,
+    {
+        let max_count = Self::MAX_NODES_PER_STEP as Scalar;
+        self.pending += self.rate * dt;
+        let count = self.pending.floor().min(max_count);
+        self.pending = match self.pending < max_count {
+            true => self.pending - count,
+            false => 0.0,
+        };
+        self.emitted += count as u64;
+        (0..count as u64)
+            .map(|_| {
+                let mut node = Node::new(allocate_id(), self.position);
+                node.mass = self.mass;
+                node.velocity = self.next_velocity();
+                node
+            })
+            .collect()
+    }
+
+    fn next_velocity(&mut self) -> Force {
+        let offset = match self.velocity_distribution {
+            VelocityDistribution::Fixed => cgmath::Vector3::new(0.0, 0.0, 0.0),
+            VelocityDistribution::Uniform { spread } => self.random.next_in_unit_sphere() * spread,
+            VelocityDistribution::Normal { spread } => {
+                cgmath::Vector3::new(
+                    self.random.next_standard_normal(),
+                    self.random.next_standard_normal(),
+                    self.random.next_standard_normal(),
+                ) * spread
+            }
+        };
+        self.velocity + Force(offset)
+    }
+}
+
+#[cfg(test)]
+mod an_emitter {
+    use cgmath::InnerSpace;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn emitter(rate: Scalar, velocity_distribution: VelocityDistribution) -> Emitter {
+        Emitter::new(
+            1,
+            Position::from((1.0, 2.0, 3.0)),
+            rate,
+            Force::from((1.0, 0.0, 0.0)),
+            velocity_distribution,
+            2.0,
+            0,
+        )
+        .unwrap()
+    }
+
+    fn allocate_from(first: u32) -> impl FnMut() -> node::Id {
+        let mut next = first;
+        move || {
+            next += 1;
+            node::Id(next - 1)
+        }
+    }
+
+    #[test]
+    fn emits_nodes_at_its_rate() {
+        let mut emitter = emitter(2.5, VelocityDistribution::Fixed);
+        let mut allocate_id = allocate_from(10);
+
+        let first = emitter.emit(1.0, &mut allocate_id);
+        let second = emitter.emit(1.0, &mut allocate_id);
+
+        assert_eq!(2, first.len());
+        assert_eq!(3, second.len());
+        assert_eq!(5, emitter.emitted());
+        let ids: Vec<node::Id> = first.iter().chain(&second).map(|node| node.id).collect();
+        assert_eq!((10..15).map(node::Id).collect::<Vec<_>>(), ids);
+    }
+
+    #[test]
+    fn emits_nodes_at_its_position_with_its_velocity_and_mass() {
+        let mut emitter = emitter(1.0, VelocityDistribution::Fixed);
+
+        let node = emitter.emit(1.0, allocate_from(0))[0];
+
+        assert_eq!(Position::from((1.0, 2.0, 3.0)), node.position);
+        assert_eq!(Force::from((1.0, 0.0, 0.0)), node.velocity);
+        assert_eq!(2.0, node.mass);
+    }
+
+    #[test]
+    fn spreads_velocities_within_a_sphere() {
+        let mut emitter = emitter(100.0, VelocityDistribution::Uniform { spread: 0.5 });
+
+        let nodes = emitter.emit(1.0, allocate_from(0));
+
+        assert!(nodes
+            .iter()
+            .all(|node| (node.velocity.0 - emitter.velocity.0).magnitude() <= 0.5));
+        assert!(nodes
+            .windows(2)
+            .any(|pair| pair[0].velocity != pair[1].velocity));
+    }
+
+    #[test]
+    fn emits_the_same_velocities_from_the_same_seed() {
+        let mut a = emitter(10.0, VelocityDistribution::Normal { spread: 1.0 });
+        let mut b = a.clone();
+
+        let a_velocities: Vec<Force> = a
+            .emit(1.0, allocate_from(0))
+            .iter()
+            .map(|n| n.velocity)
+            .collect();
+        let b_velocities: Vec<Force> = b
+            .emit(1.0, allocate_from(0))
+            .iter()
+            .map(|n| n.velocity)
+            .collect();
+
+        assert_eq!(a_velocities, b_velocities);
+    }
+
+    #[test]
+    fn emits_no_more_than_the_max_nodes_in_a_step() {
+        let mut emitter = emitter(1e9, VelocityDistribution::Fixed);
+
+        let first = emitter.emit(1.0, allocate_from(0));
+        let second = emitter.emit(0.0, allocate_from(0));
+
+        assert_eq!(Emitter::MAX_NODES_PER_STEP as usize, first.len());
+        assert!(second.is_empty(), "Dropped nodes are not carried over");
+    }
+
+    #[rstest]
+    #[case(-1.0)]
+    #[case(Scalar::INFINITY)]
+    #[case(Scalar::NAN)]
+    fn cannot_be_created_with_an_invalid_rate(#[case] rate: Scalar) {
+        let emitter = Emitter::new(
+            1,
+            Position::default(),
+            rate,
+            Force::zero(),
+            VelocityDistribution::Fixed,
+            1.0,
+            0,
+        );
+
+        assert!(emitter.is_err());
+    }
+}
@@ -0,0 +1,400 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::node::{self, constraint, drag, Node, Scalar};
+
+/// Nodes stored as a structure of arrays, so that the values used by the force calculation are
+/// contiguous in memory. Nodes are read and written as [`Node`] values, which act as a view onto
+/// a single index of the store, while stepping updates the arrays in place.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct NodeStore {
+    ids: Vec<node::Id>,
+    positions: Vec<node::Position>,
+    velocities: Vec<node::Force>,
+    masses: Vec<Scalar>,
+    radii: Vec<Scalar>,
+    gravitational_constant_overrides: Vec<Option<Scalar>>,
+    dampen_rates: Vec<Scalar>,
+    drag_coefficients: Vec<Scalar>,
+    freezes: Vec<bool>,
+    locked_axes: Vec<constraint::Axes>,
+    constraints: Vec<Option<constraint::Constraint>>,
+}
+
+impl NodeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn push(&mut self, node: Node) {
+        self.ids.push(node.id);
+        self.positions.push(node.position);
+        self.velocities.push(node.velocity);
+        self.masses.push(node.mass);
+        self.radii.push(node.radius);
+        self.gravitational_constant_overrides
+            .push(node.gravitational_constant_override);
+        self.dampen_rates.push(node.dampen_rate);
+        self.drag_coefficients.push(node.drag_coefficient);
+        self.freezes.push(node.freeze);
+        self.locked_axes.push(node.locked_axes);
+        self.constraints.push(node.constraint);
+    }
+
+    /// The node at `index`, in the order nodes were added
+    pub fn get(&self, index: usize) -> Option<Node> {
+        match index < self.len() {
+            true => Some(self.view(index)),
+            false => None,
+        }
+    }
+
+    pub fn find(&self, id: node::Id) -> Option<Node> {
+        self.index_of(id).map(|index| self.view(index))
+    }
+
+    pub fn index_of(&self, id: node::Id) -> Option<usize> {
+        self.ids.iter().position(|node_id| *node_id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Node> + '_ {
+        (0..self.len()).map(|index| self.view(index))
+    }
+
+    /// Applies `modify` to the node with the given id, and stores the result. Returns false if no
+    /// node has the id.
+    pub fn modify<F>(&mut self, id: node::Id, modify: F) -> bool
+    where
+        F: FnOnce(&mut Node),
+    {
+        let index = match self.index_of(id) {
+            Some(index) => index,
+            None => return false,
+        };
+        let mut node = self.view(index);
+        modify(&mut node);
+        self.set(index, node);
+        true
+    }
+
+    /// Keeps the nodes `keep` is true for, in the same order, compacting each array in place
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&Node) -> bool,
+    {
+        let kept: Vec<bool> = self.iter().map(|node| keep(&node)).collect();
+        if kept.iter().all(|kept| *kept) {
+            return;
+        }
+        compact(&mut self.ids, &kept);
+        compact(&mut self.positions, &kept);
+        compact(&mut self.velocities, &kept);
+        compact(&mut self.masses, &kept);
+        compact(&mut self.radii, &kept);
+        compact(&mut self.gravitational_constant_overrides, &kept);
+        compact(&mut self.dampen_rates, &kept);
+        compact(&mut self.drag_coefficients, &kept);
+        compact(&mut self.freezes, &kept);
+        compact(&mut self.locked_axes, &kept);
+        compact(&mut self.constraints, &kept);
+    }
+
+    /// Applies `update` to the id, position and velocity of every node, changing the positions and
+    /// velocities in place
+    pub fn update_motion<F>(&mut self, update: F)
+    where
+        F: Fn(node::Id, &mut node::Position, &mut node::Force) + Send + Sync,
+    {
+        let ids = &self.ids;
+        for_each_motion(
+            &mut self.positions,
+            &mut self.velocities,
+            |index, position, velocity| update(ids[index], position, velocity),
+        );
+    }
+
+    /// Changes the velocity of every node by the force on it, over the time step `time_step` gives
+    /// the node at each index. Nodes given no time step are left alone
+    pub fn accelerate<F>(&mut self, forces: &[node::Force], time_step: F)
+    where
+        F: Fn(usize) -> Option<Scalar> + Send + Sync,
+    {
+        let masses = &self.masses;
+        for_each(&mut self.velocities, |index, velocity| {
+            if let Some(dt) = time_step(index) {
+                *velocity += forces[index] * (dt / masses[index]);
+            }
+        });
+    }
+
+    /// Slows every node with `drag`, over the time step `time_step` gives the node at each index.
+    /// Nodes given no time step are left alone. See [`drag::Drag::apply`]
+    pub fn slow<F>(&mut self, drag: &drag::Drag, time_step: F)
+    where
+        F: Fn(usize) -> Option<Scalar> + Send + Sync,
+    {
+        let (masses, dampen_rates, drag_coefficients) =
+            (&self.masses, &self.dampen_rates, &self.drag_coefficients);
+        for_each(&mut self.velocities, |index, velocity| {
+            if let Some(dt) = time_step(index) {
+                *velocity = drag.apply_to(
+                    *velocity,
+                    masses[index],
+                    dampen_rates[index],
+                    drag_coefficients[index],
+                    dt,
+                );
+            }
+        });
+    }
+
+    /// Moves every node with its velocity, over the time step `time_step` gives the node at each
+    /// index, keeping it within its constraints. Frozen nodes and nodes given no time step do not
+    /// move
+    pub fn drift<F>(&mut self, time_step: F)
+    where
+        F: Fn(usize) -> Option<Scalar> + Send + Sync,
+    {
+        let (freezes, locked_axes, constraints) =
+            (&self.freezes, &self.locked_axes, &self.constraints);
+        for_each_motion(
+            &mut self.positions,
+            &mut self.velocities,
+            |index, position, velocity| {
+                let Some(dt) = time_step(index) else {
+                    return;
+                };
+                let previous_position = *position;
+                if !freezes[index] {
+                    *position = *position + *velocity * dt;
+                }
+                locked_axes[index].apply(&previous_position, position, velocity);
+                if let Some(constraint) = constraints[index] {
+                    constraint.apply(position, velocity);
+                }
+            },
+        );
+    }
+
+    pub fn ids(&self) -> &[node::Id] {
+        &self.ids
+    }
+
+    pub fn positions(&self) -> &[node::Position] {
+        &self.positions
+    }
+
+    pub fn velocities(&self) -> &[node::Force] {
+        &self.velocities
+    }
+
+    pub fn masses(&self) -> &[Scalar] {
+        &self.masses
+    }
+
+    pub fn gravitational_constant_overrides(&self) -> &[Option<Scalar>] {
+        &self.gravitational_constant_overrides
+    }
+
+    fn view(&self, index: usize) -> Node {
+        Node {
+            id: self.ids[index],
+            position: self.positions[index],
+            velocity: self.velocities[index],
+            mass: self.masses[index],
+            radius: self.radii[index],
+            gravitational_constant_override: self.gravitational_constant_overrides[index],
+            dampen_rate: self.dampen_rates[index],
+            drag_coefficient: self.drag_coefficients[index],
+            freeze: self.freezes[index],
+            locked_axes: self.locked_axes[index],
+            constraint: self.constraints[index],
+        }
+    }
+
+    fn set(&mut self, index: usize, node: Node) {
+        self.ids[index] = node.id;
+        self.positions[index] = node.position;
+        self.velocities[index] = node.velocity;
+        self.masses[index] = node.mass;
+        self.radii[index] = node.radius;
+        self.gravitational_constant_overrides[index] = node.gravitational_constant_override;
+        self.dampen_rates[index] = node.dampen_rate;
+        self.drag_coefficients[index] = node.drag_coefficient;
+        self.freezes[index] = node.freeze;
+        self.locked_axes[index] = node.locked_axes;
+        self.constraints[index] = node.constraint;
+    }
+}
+
+/// Removes the values of `column` that are not kept, keeping the rest in order
+fn compact<T>(column: &mut Vec<T>, kept: &[bool]) {
+    let mut kept = kept.iter();
+    column.retain(|_| *kept.next().expect("Every value has been checked"));
+}
+
+/// Applies `update` to every value of `column` with its index, on every core with the `parallel`
+/// feature
+fn for_each<T, F>(column: &mut [T], update: F)
+where
+    T: Send,
+    F: Fn(usize, &mut T) + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    let values = column.par_iter_mut();
+    #[cfg(not(feature = "parallel"))]
+    let values = column.iter_mut();
+    values
+        .enumerate()
+        .for_each(|(index, value)| update(index, value));
+}
+
+/// Applies `update` to every position and velocity with their index, on every core with the
+/// `parallel` feature
+fn for_each_motion<F>(positions: &mut [node::Position], velocities: &mut [node::Force], update: F)
+where
+    F: Fn(usize, &mut node::Position, &mut node::Force) + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    let motion = positions.par_iter_mut().zip(velocities);
+    #[cfg(not(feature = "parallel"))]
+    let motion = positions.iter_mut().zip(velocities);
+    motion
+        .enumerate()
+        .for_each(|(index, (position, velocity))| update(index, position, velocity));
+}
+
+impl FromIterator<Node> for NodeStore {
+    fn from_iter<T: IntoIterator<Item = Node>>(nodes: T) -> Self {
+        let mut store = NodeStore::new();
+        for node in nodes {
+            store.push(node);
+        }
+        store
+    }
+}
+
+#[cfg(test)]
+mod a_node_store {
+    use super::*;
+
+    fn store_with_nodes(ids: &[u32]) -> NodeStore {
+        let mut store = NodeStore::new();
+        for id in ids {
+            let position = node::Position::from((*id as Scalar, 0.0, 0.0));
+            store.push(Node::new(node::Id(*id), position));
+        }
+        store
+    }
+
+    #[test]
+    fn returns_the_nodes_pushed_to_it() {
+        let mut node = Node::new(node::Id(3), node::Position::from((1.0, 2.0, 3.0)));
+        node.mass = 4.0;
+        node.gravitational_constant_override = Some(-2.0);
+        node.freeze = true;
+        let mut store = NodeStore::new();
+
+        store.push(node);
+
+        assert_eq!(Some(node), store.get(0));
+        assert_eq!(Some(node), store.find(node::Id(3)));
+        assert_eq!(None, store.get(1));
+        assert_eq!(None, store.find(node::Id(1)));
+    }
+
+    #[test]
+    fn keeps_values_in_contiguous_arrays() {
+        let store = store_with_nodes(&[1, 2, 3]);
+
+        let xs: Vec<Scalar> = store
+            .positions()
+            .iter()
+            .map(|position| position.0.x)
+            .collect();
+
+        assert_eq!(vec![1.0, 2.0, 3.0], xs);
+        assert_eq!(&[1.0, 1.0, 1.0], store.masses());
+    }
+
+    #[test]
+    fn can_modify_a_node() {
+        let mut store = store_with_nodes(&[1, 2]);
+
+        let found = store.modify(node::Id(2), |node| node.mass = 5.0);
+
+        assert!(found);
+        assert_eq!(5.0, store.find(node::Id(2)).unwrap().mass);
+        assert_eq!(1.0, store.find(node::Id(1)).unwrap().mass);
+        assert!(!store.modify(node::Id(3), |node| node.mass = 5.0));
+    }
+
+    #[test]
+    fn can_remove_nodes() {
+        let mut store = store_with_nodes(&[1, 2, 3]);
+
+        store.retain(|node| node.id != node::Id(2));
+
+        let ids: Vec<node::Id> = store.iter().map(|node| node.id).collect();
+        assert_eq!(vec![node::Id(1), node::Id(3)], ids);
+        assert_eq!(3.0, store.positions()[1].0.x);
+    }
+
+    #[test]
+    fn only_updates_motion_when_updating_motion() {
+        let mut store = store_with_nodes(&[1, 2]);
+
+        store.update_motion(|id, _, velocity| {
+            *velocity = node::Force::from((id.0 as Scalar, 0.0, 0.0));
+        });
+
+        assert_eq!(node::Force::from((2.0, 0.0, 0.0)), store.velocities()[1]);
+        assert_eq!(&[1.0, 1.0], store.masses());
+    }
+
+    #[test]
+    fn kicks_slows_and_drifts_nodes() {
+        let mut node = Node::new(node::Id(1), node::Position::from((1.0, 2.0, 3.0)));
+        node.mass = 2.0;
+        node.dampen_rate = 0.5;
+        node.velocity = node::Force::from((1.0, 0.0, 0.0));
+        node.locked_axes = constraint::Axes {
+            x: false,
+            y: true,
+            z: false,
+        };
+        let mut frozen = Node::new(node::Id(2), node::Position::default());
+        frozen.freeze = true;
+        let mut store: NodeStore = [node, frozen].into_iter().collect();
+        let force = node::Force::from((1.0, 1.0, 1.0));
+        let drag = drag::Drag {
+            model: drag::DragModel::Legacy,
+            medium_density: 0.0,
+        };
+
+        store.accelerate(&[force, force], |_| Some(0.5));
+        store.slow(&drag, |index| (index == 0).then_some(0.5));
+        store.drift(|index| (index == 0).then_some(0.5));
+
+        // Kicked to (1.25, 0.25, 0.25), dampened by half, then moved for half a time step with y
+        // locked
+        assert_eq!(
+            node::Position::from((1.3125, 2.0, 3.0625)),
+            store.positions()[0]
+        );
+        assert_eq!(
+            node::Force::from((0.625, 0.0, 0.125)),
+            store.velocities()[0]
+        );
+        assert_eq!(node::Position::default(), store.positions()[1]);
+        assert_eq!(node::Force::from((0.5, 0.5, 0.5)), store.velocities()[1]);
+    }
+}
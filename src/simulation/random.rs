@@ -0,0 +1,107 @@
+use crate::node::Scalar;
+
+/// Small deterministic random number generator (SplitMix64), so that a simulation replays the same
+/// way every time it is run from the same seed
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        // https://prng.di.unimi.it/splitmix64.c
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A value in the range [0, 1)
+    pub fn next_scalar(&mut self) -> Scalar {
+        let value = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        // Rounding to a lower precision scalar could otherwise give exactly 1
+        (value as Scalar).min(1.0 - Scalar::EPSILON)
+    }
+
+    /// A value from the normal distribution with a mean of 0 and standard deviation of 1
+    pub fn next_standard_normal(&mut self) -> Scalar {
+        // Box-Muller transform
+        let u1 = 1.0 - self.next_scalar();
+        let u2 = self.next_scalar();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * crate::node::consts::PI * u2).cos()
+    }
+
+    /// A point chosen uniformly from within the unit sphere
+    pub fn next_in_unit_sphere(&mut self) -> cgmath::Vector3<Scalar> {
+        loop {
+            let point = cgmath::Vector3::new(
+                2.0 * self.next_scalar() - 1.0,
+                2.0 * self.next_scalar() - 1.0,
+                2.0 * self.next_scalar() - 1.0,
+            );
+            if cgmath::InnerSpace::magnitude2(point) <= 1.0 {
+                return point;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod a_random {
+    use cgmath::InnerSpace;
+
+    use super::*;
+
+    #[test]
+    fn gives_the_same_values_from_the_same_seed() {
+        let mut a = Random::new(7);
+        let mut b = Random::new(7);
+        let mut c = Random::new(8);
+
+        let a_values: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        let b_values: Vec<u64> = (0..5).map(|_| b.next_u64()).collect();
+        let c_values: Vec<u64> = (0..5).map(|_| c.next_u64()).collect();
+
+        assert_eq!(a_values, b_values);
+        assert_ne!(a_values, c_values);
+    }
+
+    #[test]
+    fn gives_scalars_between_zero_and_one() {
+        let mut random = Random::new(1);
+
+        let values: Vec<Scalar> = (0..1000).map(|_| random.next_scalar()).collect();
+
+        assert!(values.iter().all(|value| (0.0..1.0).contains(value)));
+        let mean = values.iter().sum::<Scalar>() / values.len() as Scalar;
+        assert!((mean - 0.5).abs() < 0.05, "mean was {mean}");
+    }
+
+    #[test]
+    fn gives_normally_distributed_values() {
+        let mut random = Random::new(2);
+
+        let values: Vec<Scalar> = (0..10000).map(|_| random.next_standard_normal()).collect();
+
+        let mean = values.iter().sum::<Scalar>() / values.len() as Scalar;
+        let variance = values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<Scalar>()
+            / values.len() as Scalar;
+        assert!(mean.abs() < 0.05, "mean was {mean}");
+        assert!((variance - 1.0).abs() < 0.05, "variance was {variance}");
+    }
+
+    #[test]
+    fn gives_points_within_the_unit_sphere() {
+        let mut random = Random::new(3);
+
+        assert!((0..1000).all(|_| random.next_in_unit_sphere().magnitude() <= 1.0));
+    }
+}
@@ -0,0 +1,350 @@
+use std::fmt;
+
+use crate::node::{self, Scalar};
+
+/// When a scheduled command runs. Scheduled commands run at the start of a step, before any nodes
+/// move, so they happen at the same point however the steps are taken.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Trigger {
+    /// Once, at the start of the first step taken from the tick
+    AtTick(u64),
+    /// Once, at the start of the first step taken from the simulated time
+    AtTime(Scalar),
+    /// Repeatedly, once every `n` ticks from when it was scheduled
+    Every(u64),
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Trigger::AtTick(tick) => write!(f, "at tick {}", tick),
+            Trigger::AtTime(time) => write!(f, "at time {}", time),
+            Trigger::Every(ticks) => write!(f, "every {} ticks", ticks),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Entry {
+    pub handle: u32,
+    pub trigger: Trigger,
+    /// The command that was scheduled, as it was entered
+    pub description: String,
+    events: Vec<node::Event>,
+    /// Tick the entry next runs at, for repeating entries
+    next_tick: u64,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} - {}",
+            self.handle, self.trigger, self.description
+        )
+    }
+}
+
+impl Entry {
+    fn is_due(&self, tick: u64, time: Scalar) -> bool {
+        match self.trigger {
+            Trigger::AtTick(at_tick) => tick >= at_tick,
+            Trigger::AtTime(at_time) => time >= at_time,
+            Trigger::Every(_) => tick >= self.next_tick,
+        }
+    }
+}
+
+/// Events waiting to be handled at a later tick or simulated time
+#[derive(Clone, Default)]
+pub struct Schedule {
+    entries: Vec<Entry>,
+    next_handle: u32,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedules `events` to be handled when `trigger` fires, returning the handle used to cancel
+    /// them. `tick` and `time` are the current tick and simulated time of the simulation.
+    pub fn add(
+        &mut self,
+        trigger: Trigger,
+        description: String,
+        events: Vec<node::Event>,
+        tick: u64,
+        time: Scalar,
+    ) -> Result<u32, String> {
+        match trigger {
+            Trigger::Every(0) => {
+                return Err("Scheduled commands must repeat at least 1 tick apart".to_string())
+            }
+            Trigger::AtTick(at_tick) if at_tick < tick => {
+                return Err(format!(
+                    "tick {} has already passed, the simulation is at tick {}",
+                    at_tick, tick
+                ))
+            }
+            Trigger::AtTime(at_time) if at_time < time => {
+                return Err(format!(
+                    "time {} has already passed, the simulation is at time {}",
+                    at_time, time
+                ))
+            }
+            _ => (),
+        }
+        if events
+            .iter()
+            .any(|event| matches!(event, node::Event::Step(_)))
+        {
+            return Err("Step commands cannot be scheduled".to_string());
+        }
+        let next_tick = match trigger {
+            Trigger::Every(ticks) => tick.saturating_add(ticks),
+            Trigger::AtTick(_) | Trigger::AtTime(_) => tick,
+        };
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.entries.push(Entry {
+            handle,
+            trigger,
+            description,
+            events,
+            next_tick,
+        });
+        Ok(handle)
+    }
+
+    /// Removes the entry with the given handle. Returns false if there is no such entry.
+    pub fn cancel(&mut self, handle: u32) -> bool {
+        let entry_count = self.entries.len();
+        self.entries.retain(|entry| entry.handle != handle);
+        self.entries.len() != entry_count
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Events that are due at the given tick and time, in the order they were scheduled. Entries
+    /// that only run once are removed from the schedule.
+    pub fn take_due(&mut self, tick: u64, time: Scalar) -> Vec<node::Event> {
+        let mut events = vec![];
+        for entry in self.entries.iter_mut() {
+            if !entry.is_due(tick, time) {
+                continue;
+            }
+            events.extend(entry.events.iter().cloned());
+            if let Trigger::Every(ticks) = entry.trigger {
+                entry.next_tick = tick.saturating_add(ticks);
+            }
+        }
+        self.entries.retain(|entry| match entry.trigger {
+            Trigger::Every(_) => true,
+            Trigger::AtTick(_) | Trigger::AtTime(_) => !entry.is_due(tick, time),
+        });
+        events
+    }
+}
+
+#[cfg(test)]
+mod a_schedule {
+    use super::*;
+
+    fn tps_event(target_tps: u32) -> node::Event {
+        node::Event::SetTargetTps(node::SetTargetTpsEvent { target_tps })
+    }
+
+    fn target_tps_of(events: Vec<node::Event>) -> Vec<u32> {
+        events
+            .into_iter()
+            .map(|event| match event {
+                node::Event::SetTargetTps(event) => event.target_tps,
+                _ => panic!("Unexpected event"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn runs_events_once_at_their_tick() {
+        let mut schedule = Schedule::new();
+        schedule
+            .add(
+                Trigger::AtTick(2),
+                "".to_string(),
+                vec![tps_event(1)],
+                0,
+                0.0,
+            )
+            .unwrap();
+
+        assert!(schedule.take_due(1, 0.0).is_empty());
+        assert_eq!(vec![1], target_tps_of(schedule.take_due(2, 0.0)));
+        assert!(schedule.take_due(3, 0.0).is_empty());
+        assert!(schedule.is_empty());
+    }
+
+    #[test]
+    fn runs_events_once_at_their_time() {
+        let mut schedule = Schedule::new();
+        schedule
+            .add(
+                Trigger::AtTime(1.5),
+                "".to_string(),
+                vec![tps_event(1)],
+                0,
+                0.0,
+            )
+            .unwrap();
+
+        assert!(schedule.take_due(1, 1.0).is_empty());
+        assert_eq!(vec![1], target_tps_of(schedule.take_due(2, 2.0)));
+        assert!(schedule.is_empty());
+    }
+
+    #[test]
+    fn repeats_events_every_interval() {
+        let mut schedule = Schedule::new();
+        schedule
+            .add(
+                Trigger::Every(2),
+                "".to_string(),
+                vec![tps_event(1)],
+                3,
+                0.0,
+            )
+            .unwrap();
+
+        let runs: Vec<u64> = (3..10)
+            .filter(|tick| !schedule.take_due(*tick, 0.0).is_empty())
+            .collect();
+
+        assert_eq!(vec![5, 7, 9], runs);
+    }
+
+    #[test]
+    fn does_not_overflow_long_intervals() {
+        let mut schedule = Schedule::new();
+        schedule
+            .add(
+                Trigger::Every(u64::MAX),
+                "".to_string(),
+                vec![tps_event(1)],
+                3,
+                0.0,
+            )
+            .unwrap();
+
+        assert!(schedule.take_due(4, 0.0).is_empty());
+        assert!(schedule.take_due(u64::MAX - 1, 0.0).is_empty());
+    }
+
+    #[test]
+    fn cannot_schedule_in_the_past() {
+        let mut schedule = Schedule::new();
+
+        let at_tick = schedule.add(
+            Trigger::AtTick(2),
+            "".to_string(),
+            vec![tps_event(1)],
+            3,
+            0.0,
+        );
+        let at_time = schedule.add(
+            Trigger::AtTime(1.0),
+            "".to_string(),
+            vec![tps_event(1)],
+            3,
+            2.0,
+        );
+
+        assert!(at_tick.is_err());
+        assert!(at_time.is_err());
+        assert!(schedule.is_empty());
+    }
+
+    #[test]
+    fn runs_events_in_the_order_they_were_scheduled() {
+        let mut schedule = Schedule::new();
+        schedule
+            .add(
+                Trigger::Every(1),
+                "".to_string(),
+                vec![tps_event(1)],
+                0,
+                0.0,
+            )
+            .unwrap();
+        schedule
+            .add(
+                Trigger::AtTick(1),
+                "".to_string(),
+                vec![tps_event(2), tps_event(3)],
+                0,
+                0.0,
+            )
+            .unwrap();
+
+        assert_eq!(vec![1, 2, 3], target_tps_of(schedule.take_due(1, 0.0)));
+    }
+
+    #[test]
+    fn can_cancel_entries_by_handle() {
+        let mut schedule = Schedule::new();
+        let first = schedule
+            .add(
+                Trigger::Every(1),
+                "".to_string(),
+                vec![tps_event(1)],
+                0,
+                0.0,
+            )
+            .unwrap();
+        let second = schedule
+            .add(
+                Trigger::Every(1),
+                "".to_string(),
+                vec![tps_event(2)],
+                0,
+                0.0,
+            )
+            .unwrap();
+
+        assert!(schedule.cancel(first));
+        assert!(!schedule.cancel(first));
+
+        assert_eq!(vec![2], target_tps_of(schedule.take_due(1, 0.0)));
+        assert_eq!(
+            vec![second],
+            schedule
+                .iter()
+                .map(|entry| entry.handle)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn cannot_schedule_steps() {
+        let mut schedule = Schedule::new();
+        let step = node::Event::Step(node::event::step::StepEvent { steps: 1 });
+
+        assert!(schedule
+            .add(Trigger::AtTick(1), "".to_string(), vec![step], 0, 0.0)
+            .is_err());
+        assert!(schedule
+            .add(
+                Trigger::Every(0),
+                "".to_string(),
+                vec![tps_event(1)],
+                0,
+                0.0
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,548 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use cgmath::EuclideanSpace;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, FLOAT, INT};
+
+use super::Simulation;
+use crate::node::{self, Scalar};
+
+/// Name of the function a hook defines to be run every tick
+const ON_TICK: &str = "on_tick";
+
+/// A Rhai script loaded into the simulation. The script is run once when it is added, and its
+/// `on_tick(sim)` function, if it has one, is run at the start of every step.
+#[derive(Clone)]
+pub struct Hook {
+    pub id: u32,
+    /// Where the script came from, such as its file
+    pub name: String,
+    /// Time the hook may run for each tick before it is stopped
+    pub budget: Duration,
+    ast: AST,
+    runs_on_tick: bool,
+}
+
+impl fmt::Display for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Hook {}:\n\tscript: {}\n\tbudget: {:?}\n\truns on tick: {}",
+            self.id, self.name, self.budget, self.runs_on_tick
+        )
+    }
+}
+
+/// Runs sandboxed Rhai scripts with access to the simulation. Scripts cannot import modules or
+/// evaluate strings as code, and are stopped if they run over their time budget.
+#[derive(Clone, Default)]
+pub struct ScriptHost {
+    /// Created when the first hook is added, so that simulations without hooks stay cheap to
+    /// create
+    engine: Option<Arc<Engine>>,
+    /// When the running script has to stop by
+    deadline: Arc<Mutex<Option<Instant>>>,
+    hooks: Vec<Hook>,
+}
+
+impl ScriptHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub fn hooks(&self) -> impl Iterator<Item = &Hook> {
+        self.hooks.iter()
+    }
+
+    /// Compiles `source` and runs it once against `simulation`. If it defines `on_tick(sim)`, the
+    /// hook is kept to be run every tick.
+    pub fn add_hook(
+        &mut self,
+        id: u32,
+        name: String,
+        source: &str,
+        budget: Duration,
+        simulation: &mut Simulation,
+    ) -> Result<(), String> {
+        if self.hooks.iter().any(|hook| hook.id == id) {
+            return Err(format!("A hook with id {} already exists", id));
+        }
+        let engine = Arc::clone(
+            self.engine
+                .get_or_insert_with(|| Arc::new(create_engine(Arc::clone(&self.deadline)))),
+        );
+        let ast = engine.compile(source).map_err(|err| err.to_string())?;
+        let runs_on_tick = ast
+            .iter_functions()
+            .any(|function| function.name == ON_TICK && function.params.len() == 1);
+        let hook = Hook {
+            id,
+            name,
+            budget,
+            ast,
+            runs_on_tick,
+        };
+        share(simulation, |handle| {
+            self.run_within_budget(&hook, || {
+                let mut scope = Scope::new();
+                scope.push_constant("sim", handle);
+                engine.run_ast_with_scope(&mut scope, &hook.ast)
+            })
+        })?;
+        if hook.runs_on_tick {
+            self.hooks.push(hook);
+        }
+        Ok(())
+    }
+
+    /// Removes the hook with the given id. Returns false if there is no such hook.
+    pub fn remove_hook(&mut self, id: u32) -> bool {
+        let hook_count = self.hooks.len();
+        self.hooks.retain(|hook| hook.id != id);
+        self.hooks.len() != hook_count
+    }
+
+    /// Runs the `on_tick` function of every hook. Hooks that fail, or run over their budget, are
+    /// removed - changes they made before stopping are kept.
+    pub fn run_tick_hooks(&mut self, simulation: &mut Simulation) {
+        let Some(engine) = self.engine.clone() else {
+            return;
+        };
+        let failed: Vec<u32> = share(simulation, |handle| {
+            self.hooks
+                .iter()
+                .filter_map(|hook| {
+                    let result = self.run_within_budget(hook, || {
+                        engine
+                            .call_fn_with_options::<Dynamic>(
+                                // Only call `on_tick`, not the statements run when the hook was
+                                // added
+                                CallFnOptions::new().eval_ast(false),
+                                &mut Scope::new(),
+                                &hook.ast,
+                                ON_TICK,
+                                (handle.clone(),),
+                            )
+                            .map(|_| ())
+                    });
+                    let err = result.err()?;
+                    println!("Error running hook {}, removing it - {}", hook.id, err);
+                    Some(hook.id)
+                })
+                .collect()
+        });
+        self.hooks.retain(|hook| !failed.contains(&hook.id));
+    }
+
+    /// Runs a script for `hook`, stopping it if it runs over the hook's budget
+    fn run_within_budget<F>(&self, hook: &Hook, run: F) -> Result<(), String>
+    where
+        F: FnOnce() -> Result<(), Box<EvalAltResult>>,
+    {
+        *self.deadline.lock().unwrap() = Some(Instant::now() + hook.budget);
+        let result = run();
+        *self.deadline.lock().unwrap() = None;
+        result.map_err(|err| err.to_string())
+    }
+}
+
+/// Gives scripts shared access to `simulation` for the length of `run`
+fn share<T, F>(simulation: &mut Simulation, run: F) -> T
+where
+    F: FnOnce(SimulationHandle) -> T,
+{
+    let shared = Arc::new(Mutex::new(std::mem::replace(simulation, Simulation::new())));
+    let result = run(SimulationHandle(Arc::clone(&shared)));
+    *simulation = match Arc::try_unwrap(shared) {
+        Ok(shared) => shared.into_inner().unwrap(),
+        // A script kept hold of the simulation
+        Err(shared) => shared.lock().unwrap().clone(),
+    };
+    result
+}
+
+fn create_engine(deadline: Arc<Mutex<Option<Instant>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_call_levels(64);
+    engine.set_max_expr_depths(64, 64);
+    engine.set_max_string_size(1 << 20);
+    engine.set_max_array_size(1 << 20);
+    engine.set_max_map_size(1 << 16);
+    engine.on_progress(move |operations| {
+        // Checking the clock is slow compared to a single operation
+        if operations % 1024 != 0 {
+            return None;
+        }
+        match *deadline.lock().unwrap() {
+            Some(deadline) if Instant::now() > deadline => {
+                Some("Script ran over its time budget".into())
+            }
+            _ => None,
+        }
+    });
+    register_vector(&mut engine);
+    register_node(&mut engine);
+    register_simulation(&mut engine);
+    engine
+}
+
+/// A vector, such as a position or velocity, as seen by scripts
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Vector {
+    pub x: FLOAT,
+    pub y: FLOAT,
+    pub z: FLOAT,
+}
+
+impl From<cgmath::Vector3<Scalar>> for Vector {
+    fn from(value: cgmath::Vector3<Scalar>) -> Self {
+        Vector {
+            x: value.x as FLOAT,
+            y: value.y as FLOAT,
+            z: value.z as FLOAT,
+        }
+    }
+}
+
+impl From<Vector> for cgmath::Vector3<Scalar> {
+    fn from(value: Vector) -> Self {
+        cgmath::Vector3::new(value.x as Scalar, value.y as Scalar, value.z as Scalar)
+    }
+}
+
+impl Vector {
+    fn length(&mut self) -> FLOAT {
+        self.dot(*self).sqrt()
+    }
+
+    fn dot(&mut self, other: Vector) -> FLOAT {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn scale(self, scale: FLOAT) -> Vector {
+        Vector {
+            x: self.x * scale,
+            y: self.y * scale,
+            z: self.z * scale,
+        }
+    }
+}
+
+fn register_vector(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Vector>("Vector")
+        .register_fn("vector", |x: FLOAT, y: FLOAT, z: FLOAT| Vector { x, y, z })
+        .register_get_set("x", |v: &mut Vector| v.x, |v: &mut Vector, x| v.x = x)
+        .register_get_set("y", |v: &mut Vector| v.y, |v: &mut Vector, y| v.y = y)
+        .register_get_set("z", |v: &mut Vector| v.z, |v: &mut Vector, z| v.z = z)
+        .register_fn("length", Vector::length)
+        .register_fn("dot", Vector::dot)
+        .register_fn("+", |a: Vector, b: Vector| Vector {
+            x: a.x + b.x,
+            y: a.y + b.y,
+            z: a.z + b.z,
+        })
+        .register_fn("-", |a: Vector, b: Vector| Vector {
+            x: a.x - b.x,
+            y: a.y - b.y,
+            z: a.z - b.z,
+        })
+        .register_fn("-", |a: Vector| a.scale(-1.0))
+        .register_fn("*", Vector::scale)
+        .register_fn("*", |scale: FLOAT, v: Vector| v.scale(scale))
+        .register_fn("/", |v: Vector, scale: FLOAT| v.scale(1.0 / scale))
+        .register_fn("to_string", |v: &mut Vector| {
+            format!("x: {}, y: {}, z: {}", v.x, v.y, v.z)
+        })
+        .register_fn("to_debug", |v: &mut Vector| format!("{:?}", v));
+}
+
+fn register_node(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<node::Node>("Node")
+        .register_get("id", |node: &mut node::Node| node.id.0 as INT)
+        .register_get_set(
+            "position",
+            |node: &mut node::Node| Vector::from(node.position.0.to_vec()),
+            |node: &mut node::Node, position: Vector| {
+                node.position = node::Position(cgmath::Point3::from_vec(position.into()))
+            },
+        )
+        .register_get_set(
+            "velocity",
+            |node: &mut node::Node| Vector::from(node.velocity.0),
+            |node: &mut node::Node, velocity: Vector| node.velocity = node::Force(velocity.into()),
+        )
+        .register_get_set(
+            "mass",
+            |node: &mut node::Node| node.mass as FLOAT,
+            |node: &mut node::Node, mass: FLOAT| node.mass = mass as Scalar,
+        )
+        .register_get_set(
+            "radius",
+            |node: &mut node::Node| node.radius as FLOAT,
+            |node: &mut node::Node, radius: FLOAT| node.radius = radius.abs() as Scalar,
+        )
+        .register_get_set(
+            "freeze",
+            |node: &mut node::Node| node.freeze,
+            |node: &mut node::Node, freeze: bool| node.freeze = freeze,
+        )
+        .register_fn("to_string", |node: &mut node::Node| {
+            format!("Node {}", node.id.0)
+        })
+        .register_fn("to_debug", |node: &mut node::Node| format!("{:?}", node));
+}
+
+/// The simulation, as seen by scripts while they run
+#[derive(Clone)]
+struct SimulationHandle(Arc<Mutex<Simulation>>);
+
+fn to_id(id: INT) -> Result<node::Id, Box<EvalAltResult>> {
+    match u32::try_from(id) {
+        Ok(id) => Ok(node::Id(id)),
+        Err(_) => Err(format!("Invalid node id {}", id).into()),
+    }
+}
+
+fn register_simulation(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<SimulationHandle>("Simulation")
+        .register_get("tick", |sim: &mut SimulationHandle| {
+            sim.0.lock().unwrap().tick() as INT
+        })
+        .register_get("time", |sim: &mut SimulationHandle| {
+            sim.0.lock().unwrap().time() as FLOAT
+        })
+        .register_get("node_count", |sim: &mut SimulationHandle| {
+            sim.0.lock().unwrap().nodes.len() as INT
+        })
+        .register_fn("node_ids", |sim: &mut SimulationHandle| -> Array {
+            let simulation = sim.0.lock().unwrap();
+            simulation
+                .nodes
+                .ids()
+                .iter()
+                .map(|id| Dynamic::from(id.0 as INT))
+                .collect()
+        })
+        // Gives () if there is no node with the id
+        .register_fn(
+            "node",
+            |sim: &mut SimulationHandle, id: INT| -> Result<Dynamic, Box<EvalAltResult>> {
+                let id = to_id(id)?;
+                Ok(match sim.0.lock().unwrap().nodes.find(id) {
+                    Some(node) => Dynamic::from(node),
+                    None => Dynamic::UNIT,
+                })
+            },
+        )
+        // Replaces the node with the same id. Gives false if there is no such node.
+        .register_fn(
+            "set_node",
+            |sim: &mut SimulationHandle, mut node: node::Node| {
+                let mut simulation = sim.0.lock().unwrap();
+                simulation
+                    .dimensionality()
+                    .apply(&mut node.position, &mut node.velocity);
+                simulation
+                    .nodes
+                    .modify(node.id, |existing| *existing = node)
+            },
+        )
+        .register_fn(
+            "add_node",
+            |sim: &mut SimulationHandle,
+             id: INT,
+             position: Vector|
+             -> Result<node::Node, Box<EvalAltResult>> {
+                let id = to_id(id)?;
+                let mut simulation = sim.0.lock().unwrap();
+                if simulation.nodes.find(id).is_some() {
+                    return Err(format!("A node with id {} already exists", id.0).into());
+                }
+                let node = node::Node::new(
+                    id,
+                    node::Position(cgmath::Point3::from_vec(position.into())),
+                );
+                simulation.add_node(node);
+                Ok(node)
+            },
+        )
+        .register_fn(
+            "remove_node",
+            |sim: &mut SimulationHandle, id: INT| -> Result<(), Box<EvalAltResult>> {
+                sim.0.lock().unwrap().remove_node(to_id(id)?);
+                Ok(())
+            },
+        )
+        .register_fn("stats", |sim: &mut SimulationHandle| -> Map {
+            let stats = sim.0.lock().unwrap().stats();
+            let mut map = Map::new();
+            map.insert("tick".into(), Dynamic::from(stats.tick as INT));
+            map.insert(
+                "kinetic_energy".into(),
+                Dynamic::from(stats.kinetic_energy as FLOAT),
+            );
+            map.insert(
+                "potential_energy".into(),
+                Dynamic::from(stats.potential_energy as FLOAT),
+            );
+            map.insert(
+                "total_energy".into(),
+                Dynamic::from(stats.total_energy() as FLOAT),
+            );
+            map.insert(
+                "momentum".into(),
+                Dynamic::from(Vector::from(stats.momentum)),
+            );
+            map.insert(
+                "angular_momentum".into(),
+                Dynamic::from(Vector::from(stats.angular_momentum)),
+            );
+            map.insert(
+                "centre_of_mass".into(),
+                Dynamic::from(Vector::from(stats.centre_of_mass.0.to_vec())),
+            );
+            map
+        });
+}
+
+#[cfg(test)]
+mod a_script_host {
+    use super::*;
+
+    fn simulation_with_node() -> Simulation {
+        let mut simulation = Simulation::new();
+        simulation.add_node(node::Node::new(
+            node::Id(5),
+            node::Position::from((20.0, 0.0, 0.0)),
+        ));
+        simulation
+    }
+
+    fn add_hook(simulation: &mut Simulation, source: &str) -> Result<(), String> {
+        let mut host = std::mem::take(&mut simulation.scripting);
+        let result = host.add_hook(
+            1,
+            "test".to_string(),
+            source,
+            Duration::from_millis(100),
+            simulation,
+        );
+        simulation.scripting = host;
+        result
+    }
+
+    #[test]
+    fn lets_scripts_read_and_modify_nodes() {
+        let mut simulation = simulation_with_node();
+
+        add_hook(
+            &mut simulation,
+            "let node = sim.node(5); node.mass = node.position.x * 2.0; sim.set_node(node);",
+        )
+        .unwrap();
+
+        assert_eq!(40.0, simulation.nodes.find(node::Id(5)).unwrap().mass);
+        assert!(
+            simulation.scripting.is_empty(),
+            "There is no on_tick function"
+        );
+    }
+
+    #[test]
+    fn runs_on_tick_hooks_every_step() {
+        let mut simulation = simulation_with_node();
+        simulation.gravitational_constant = 0.0;
+        let source = r#"
+            fn on_tick(sim) {
+                let node = sim.node(5);
+                if node.position.length() > 10.0 {
+                    node.position = node.position / 2.0;
+                    sim.set_node(node);
+                }
+            }
+        "#;
+        add_hook(&mut simulation, source).unwrap();
+
+        simulation.step();
+        simulation.step();
+        simulation.step();
+
+        assert_eq!(
+            node::Position::from((10.0, 0.0, 0.0)),
+            simulation.nodes.find(node::Id(5)).unwrap().position
+        );
+        assert_eq!(1, simulation.scripting.hooks().count());
+    }
+
+    #[test]
+    fn only_runs_the_statements_outside_on_tick_once() {
+        let mut simulation = Simulation::new();
+        let source = r#"
+            sim.add_node(sim.node_count, vector(0.0, 0.0, 0.0));
+            fn on_tick(sim) {}
+        "#;
+        add_hook(&mut simulation, source).unwrap();
+
+        simulation.step();
+        simulation.step();
+
+        assert_eq!(vec![node::Id(0)], simulation.nodes.ids().to_vec());
+        assert_eq!(1, simulation.scripting.hooks().count());
+    }
+
+    #[test]
+    fn can_add_and_remove_nodes() {
+        let mut simulation = Simulation::new();
+
+        add_hook(
+            &mut simulation,
+            "sim.add_node(3, vector(1.0, 2.0, 3.0)); sim.add_node(4, vector(0.0, 0.0, 0.0)); sim.remove_node(3);",
+        )
+        .unwrap();
+
+        assert_eq!(vec![node::Id(4)], simulation.nodes.ids().to_vec(),);
+    }
+
+    #[test]
+    fn stops_scripts_that_run_over_their_budget() {
+        let mut simulation = Simulation::new();
+
+        let result = add_hook(&mut simulation, "loop {}");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn removes_hooks_that_fail() {
+        let mut simulation = Simulation::new();
+        add_hook(
+            &mut simulation,
+            "fn on_tick(sim) { if sim.tick == 1 { throw \"failed\"; } }",
+        )
+        .unwrap();
+
+        simulation.step();
+        assert!(!simulation.scripting.is_empty());
+        simulation.step();
+
+        assert!(simulation.scripting.is_empty());
+    }
+
+    #[test]
+    fn cannot_import_modules() {
+        let mut simulation = Simulation::new();
+
+        assert!(add_hook(&mut simulation, "import \"file\" as file;").is_err());
+        assert!(add_hook(&mut simulation, "eval(\"1\");").is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use cgmath::InnerSpace;
+
+use crate::node::{Position, Scalar};
+
+/// A spherical region that absorbs any node that enters it
+#[derive(PartialEq, Debug, Clone)]
+pub struct Sink {
+    pub id: u32,
+    pub position: Position,
+    pub radius: Scalar,
+    absorbed: u64,
+}
+
+impl fmt::Display for Sink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Sink {}:\n\tposition: {}\n\tradius: {}\n\tabsorbed: {}",
+            self.id, self.position, self.radius, self.absorbed
+        )
+    }
+}
+
+impl Sink {
+    pub fn new(id: u32, position: Position, radius: Scalar) -> Result<Self, String> {
+        if radius <= 0.0 {
+            return Err("Sink radius must be greater than 0".to_string());
+        }
+        Ok(Sink {
+            id,
+            position,
+            radius,
+            absorbed: 0,
+        })
+    }
+
+    /// Number of nodes the sink has absorbed
+    pub fn absorbed(&self) -> u64 {
+        self.absorbed
+    }
+
+    /// Absorbs a node at `position` if it is within the sink. Returns whether it was absorbed.
+    pub fn absorb(&mut self, position: &Position) -> bool {
+        let inside = Position::displacement(&self.position, position).magnitude() <= self.radius;
+        if inside {
+            self.absorbed += 1;
+        }
+        inside
+    }
+}
+
+#[cfg(test)]
+mod a_sink {
+    use super::*;
+
+    #[test]
+    fn absorbs_nodes_within_its_radius() {
+        let mut sink = Sink::new(1, Position::from((1.0, 0.0, 0.0)), 0.5).unwrap();
+
+        assert!(sink.absorb(&Position::from((1.0, 0.4, 0.0))));
+        assert!(!sink.absorb(&Position::from((2.0, 0.0, 0.0))));
+        assert_eq!(1, sink.absorbed());
+    }
+
+    #[test]
+    fn cannot_be_created_without_a_radius() {
+        assert!(Sink::new(1, Position::default(), 0.0).is_err());
+    }
+}
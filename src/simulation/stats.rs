@@ -0,0 +1,330 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use cgmath::{EuclideanSpace, Zero};
+
+use super::{
+    units::{Quantity, UnitSystem},
+    Simulation,
+};
+use crate::node;
+
+/// Conserved quantities and extent of a simulation, used to check it is physically healthy
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Stats {
+    pub tick: u64,
+    pub kinetic_energy: node::Scalar,
+    pub potential_energy: node::Scalar,
+    pub momentum: cgmath::Vector3<node::Scalar>,
+    /// Angular momentum about the origin
+    pub angular_momentum: cgmath::Vector3<node::Scalar>,
+    pub centre_of_mass: node::Position,
+    /// Minimum and maximum corners of the box bounding every node, if there are any nodes
+    pub bounds: Option<(node::Position, node::Position)>,
+}
+
+impl Stats {
+    pub fn new(simulation: &Simulation) -> Self {
+        let nodes = &simulation.nodes;
+
+        let kinetic_energy = nodes
+            .iter()
+            .map(|node| 0.5 * node.mass * node.velocity.magnitude().powf(2.0))
+            .sum();
+
+        let mut potential_energy = 0.0;
+        for (i, node) in nodes.iter().enumerate() {
+            for other in nodes.iter().skip(i + 1) {
+                potential_energy += node::Force::calculate_pair_potential_energy(
+                    &node,
+                    &other,
+                    &simulation.gravitational_constant,
+                    &simulation.softening_length,
+                    simulation.force_radius,
+                );
+            }
+        }
+
+        let momentum = nodes.iter().map(|node| node.velocity.0 * node.mass).sum();
+
+        let angular_momentum = nodes
+            .iter()
+            .map(|node| node.position.0.to_vec().cross(node.velocity.0 * node.mass))
+            .sum();
+
+        let total_mass: node::Scalar = nodes.iter().map(|node| node.mass).sum();
+        let centre_of_mass = match total_mass == 0.0 {
+            true => node::Position::default(),
+            false => {
+                let weighted_positions: cgmath::Vector3<node::Scalar> = nodes
+                    .iter()
+                    .map(|node| node.position.0.to_vec() * node.mass)
+                    .sum();
+                node::Position::from(cgmath::Point3::from_vec(weighted_positions / total_mass))
+            }
+        };
+
+        let bounds = nodes.iter().fold(None, |bounds, node| {
+            let position = node.position.0;
+            match bounds {
+                None => Some((position, position)),
+                Some((min, max)) => Some((
+                    cgmath::Point3::new(
+                        node::Scalar::min(min.x, position.x),
+                        node::Scalar::min(min.y, position.y),
+                        node::Scalar::min(min.z, position.z),
+                    ),
+                    cgmath::Point3::new(
+                        node::Scalar::max(max.x, position.x),
+                        node::Scalar::max(max.y, position.y),
+                        node::Scalar::max(max.z, position.z),
+                    ),
+                )),
+            }
+        });
+        let bounds = bounds.map(|(min, max)| (node::Position(min), node::Position(max)));
+
+        Stats {
+            tick: simulation.tick(),
+            kinetic_energy,
+            potential_energy,
+            momentum,
+            angular_momentum,
+            centre_of_mass,
+            bounds,
+        }
+    }
+
+    pub fn total_energy(&self) -> node::Scalar {
+        self.kinetic_energy + self.potential_energy
+    }
+
+    /// Describes the stats, with each value followed by the unit it is measured in
+    pub fn to_display_string(&self, units: &UnitSystem) -> String {
+        let energy = |energy: node::Scalar| format!("{}{}", energy, units.suffix(Quantity::Energy));
+        let vector = |vector: cgmath::Vector3<node::Scalar>, quantity| {
+            format!("{}{}", node::Force(vector), units.suffix(quantity))
+        };
+        let position =
+            |position: node::Position| format!("{}{}", position, units.suffix(Quantity::Length));
+        let bounds = match self.bounds {
+            Some((min, max)) => format!("min {}, max {}", position(min), position(max)),
+            None => "None".to_string(),
+        };
+        [
+            "Stats:".to_string(),
+            format!("tick: {}", self.tick),
+            format!("kinetic energy: {}", energy(self.kinetic_energy)),
+            format!("potential energy: {}", energy(self.potential_energy)),
+            format!("total energy: {}", energy(self.total_energy())),
+            format!("momentum: {}", vector(self.momentum, Quantity::Momentum)),
+            format!(
+                "angular momentum: {}",
+                vector(self.angular_momentum, Quantity::AngularMomentum)
+            ),
+            format!("centre of mass: {}", position(self.centre_of_mass)),
+            format!("bounds: {}", bounds),
+        ]
+        .join("\n\t")
+    }
+
+    fn csv_header() -> &'static str {
+        "tick,kinetic_energy,potential_energy,total_energy,\
+        momentum_x,momentum_y,momentum_z,\
+        angular_momentum_x,angular_momentum_y,angular_momentum_z,\
+        centre_of_mass_x,centre_of_mass_y,centre_of_mass_z,\
+        min_x,min_y,min_z,max_x,max_y,max_z"
+    }
+
+    fn to_csv_row(self) -> String {
+        let (min, max) = match self.bounds {
+            Some((min, max)) => (vector_to_csv(min.0.to_vec()), vector_to_csv(max.0.to_vec())),
+            None => (",,".to_string(), ",,".to_string()),
+        };
+        format!(
+            "{},{},{},{},{},{},{},{},{}",
+            self.tick,
+            self.kinetic_energy,
+            self.potential_energy,
+            self.total_energy(),
+            vector_to_csv(self.momentum),
+            vector_to_csv(self.angular_momentum),
+            vector_to_csv(self.centre_of_mass.0.to_vec()),
+            min,
+            max,
+        )
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats {
+            tick: 0,
+            kinetic_energy: 0.0,
+            potential_energy: 0.0,
+            momentum: cgmath::Vector3::zero(),
+            angular_momentum: cgmath::Vector3::zero(),
+            centre_of_mass: node::Position::default(),
+            bounds: None,
+        }
+    }
+}
+
+fn vector_to_csv(vector: cgmath::Vector3<node::Scalar>) -> String {
+    format!("{},{},{}", vector.x, vector.y, vector.z)
+}
+
+/// Periodically writes the stats of a simulation to a CSV file
+#[derive(PartialEq, Debug, Clone)]
+pub struct StatsLog {
+    pub path: PathBuf,
+    /// Number of ticks between each row written to the log
+    pub interval: u32,
+}
+
+impl StatsLog {
+    /// Creates the log file, overwriting any existing file, and writes the CSV header
+    pub fn create(path: PathBuf, interval: u32) -> io::Result<Self> {
+        if interval == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Stats log interval must be greater than 0",
+            ));
+        }
+        let mut file = File::create(&path)?;
+        writeln!(file, "{}", Stats::csv_header())?;
+        Ok(StatsLog { path, interval })
+    }
+
+    pub fn is_due(&self, tick: u64) -> bool {
+        tick.is_multiple_of(self.interval as u64)
+    }
+
+    pub fn record(&self, stats: &Stats) -> io::Result<()> {
+        let mut file = OpenOptions::new().append(true).open(&self.path)?;
+        writeln!(file, "{}", stats.to_csv_row())
+    }
+}
+
+#[cfg(test)]
+mod a_stats {
+    use std::fs;
+
+    use super::*;
+
+    fn two_node_simulation() -> Simulation {
+        let mut simulation = Simulation::new();
+        let mut node_a = node::Node::new(node::Id(1), node::Position::from((1.0, 0.0, 0.0)));
+        node_a.velocity = node::Force::from((0.0, 0.0, 2.0));
+        node_a.mass = 2.0;
+        let mut node_b = node::Node::new(node::Id(2), node::Position::from((-1.0, 2.0, 0.0)));
+        node_b.velocity = node::Force::from((0.0, 0.0, -1.0));
+        simulation.add_node(node_a);
+        simulation.add_node(node_b);
+        simulation
+    }
+
+    #[test]
+    fn are_default_for_an_empty_simulation() {
+        let simulation = Simulation::new();
+        assert_eq!(Stats::default(), simulation.stats());
+    }
+
+    #[test]
+    fn are_displayed_with_the_units_of_the_simulation() {
+        let units = UnitSystem::Physical(crate::simulation::units::Units::SI);
+        let display_string = two_node_simulation().stats().to_display_string(&units);
+
+        assert!(display_string.contains("\n\tkinetic energy: 4.5 kg m^2/s^2\n"));
+        assert!(display_string.contains("\n\tmomentum: x: 0, y: 0, z: 3 kg m/s\n"));
+        assert!(display_string.ends_with("max x: 1, y: 2, z: 0 m"));
+    }
+
+    #[test]
+    fn include_kinetic_energy() {
+        let stats = two_node_simulation().stats();
+        // 0.5 * 2 * 2^2 + 0.5 * 1 * 1^2
+        assert_eq!(4.5, stats.kinetic_energy);
+    }
+
+    #[test]
+    fn include_potential_energy() {
+        let simulation = two_node_simulation();
+        let nodes = &simulation.nodes;
+        let expected_potential_energy = node::Force::calculate_pair_potential_energy(
+            &nodes.get(0).unwrap(),
+            &nodes.get(1).unwrap(),
+            &simulation.gravitational_constant,
+            &simulation.softening_length,
+            simulation.force_radius,
+        );
+
+        let stats = simulation.stats();
+
+        assert_eq!(expected_potential_energy, stats.potential_energy);
+        assert!(
+            stats.potential_energy > 0.0,
+            "Default constant is repulsive"
+        );
+    }
+
+    #[test]
+    fn include_momentum() {
+        let stats = two_node_simulation().stats();
+        assert_eq!(cgmath::Vector3::new(0.0, 0.0, 3.0), stats.momentum);
+    }
+
+    #[test]
+    fn include_angular_momentum() {
+        let stats = two_node_simulation().stats();
+        // (1, 0, 0) x (0, 0, 4) + (-1, 2, 0) x (0, 0, -1)
+        assert_eq!(
+            cgmath::Vector3::new(-2.0, -5.0, 0.0),
+            stats.angular_momentum
+        );
+    }
+
+    #[test]
+    fn include_centre_of_mass() {
+        let stats = two_node_simulation().stats();
+        assert_eq!(
+            node::Position::from((1.0 / 3.0, 2.0 / 3.0, 0.0)),
+            stats.centre_of_mass
+        );
+    }
+
+    #[test]
+    fn include_bounds() {
+        let stats = two_node_simulation().stats();
+        assert_eq!(
+            Some((
+                node::Position::from((-1.0, 0.0, 0.0)),
+                node::Position::from((1.0, 2.0, 0.0))
+            )),
+            stats.bounds
+        );
+    }
+
+    #[test]
+    fn can_be_logged_to_csv_every_n_ticks() {
+        let path = std::env::temp_dir().join("node_simulator_stats_log_test.csv");
+        let mut simulation = two_node_simulation();
+        simulation.stats_log = Some(StatsLog::create(path.clone(), 2).unwrap());
+
+        for _ in 0..5 {
+            simulation.step();
+        }
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(3, lines.len());
+        assert_eq!(Stats::csv_header(), lines[0]);
+        assert!(lines[1].starts_with("2,"));
+        assert!(lines[2].starts_with("4,"));
+        assert_eq!(19, lines[1].split(',').count());
+    }
+}
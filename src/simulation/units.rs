@@ -0,0 +1,340 @@
+use std::fmt;
+
+use crate::node::{Force, Scalar};
+
+/// Newtons gravitational constant, in cubic metres per kilogram per second squared
+const GRAVITATIONAL_CONSTANT: f64 = 6.674_30e-11;
+
+/// Units of length that can be written after a value, and the metres in each
+const LENGTH_UNITS: [Unit; 3] = [
+    Unit::new("m", 1.0),
+    Unit::new("km", 1.0e3),
+    Unit::new("AU", 1.495_978_707e11),
+];
+
+/// Units of time that can be written after a value, and the seconds in each
+const TIME_UNITS: [Unit; 4] = [
+    Unit::new("s", 1.0),
+    Unit::new("min", 60.0),
+    Unit::new("h", 3600.0),
+    Unit::new("day", 86400.0),
+];
+
+/// Units of mass, and the kilograms in each
+const MASS_UNITS: [Unit; 3] = [
+    Unit::new("kg", 1.0),
+    Unit::new("Mearth", 5.972_2e24),
+    Unit::new("Msun", 1.988_47e30),
+];
+
+/// What a value measures, which decides the units it can be written in
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Quantity {
+    Length,
+    Time,
+    Mass,
+    Velocity,
+    /// Angles are in degrees, unless written in radians with "rad"
+    Angle,
+    Energy,
+    Momentum,
+    AngularMomentum,
+    GravitationalConstant,
+}
+
+/// A real world unit
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Unit {
+    pub name: &'static str,
+    /// Size of the unit in metres, seconds or kilograms. Kept as an `f64` whichever [`Scalar`] is
+    /// used, as units are combined into values too large for an `f32`
+    pub size: f64,
+}
+
+impl Unit {
+    const fn new(name: &'static str, size: f64) -> Self {
+        Self { name, size }
+    }
+
+    /// The unit of length called `name`
+    pub fn length(name: &str) -> Result<Self, String> {
+        find_unit(&LENGTH_UNITS, name, "length")
+    }
+
+    /// The unit of time called `name`
+    pub fn time(name: &str) -> Result<Self, String> {
+        find_unit(&TIME_UNITS, name, "time")
+    }
+
+    /// The unit of mass called `name`
+    pub fn mass(name: &str) -> Result<Self, String> {
+        find_unit(&MASS_UNITS, name, "mass")
+    }
+}
+
+fn find_unit(units: &[Unit], name: &str, quantity: &str) -> Result<Unit, String> {
+    units
+        .iter()
+        .find(|unit| unit.name == name)
+        .copied()
+        .ok_or_else(|| format!("Unknown unit of {} '{}'", quantity, name))
+}
+
+/// The real world units a simulation measures in
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Units {
+    pub length: Unit,
+    pub time: Unit,
+    pub mass: Unit,
+}
+
+impl Units {
+    /// Metres, seconds and kilograms
+    pub const SI: Self = Self {
+        length: LENGTH_UNITS[0],
+        time: TIME_UNITS[0],
+        mass: MASS_UNITS[0],
+    };
+
+    /// Astronomical units, days and solar masses, which keep the solar system to small numbers
+    pub const ASTRONOMICAL: Self = Self {
+        length: LENGTH_UNITS[2],
+        time: TIME_UNITS[3],
+        mass: MASS_UNITS[2],
+    };
+
+    /// Name of the unit `quantity` is measured in
+    pub fn name(&self, quantity: Quantity) -> String {
+        match quantity {
+            Quantity::Length => self.length.name.to_string(),
+            Quantity::Time => self.time.name.to_string(),
+            Quantity::Mass => self.mass.name.to_string(),
+            Quantity::Velocity => format!("{}/{}", self.length.name, self.time.name),
+            Quantity::Angle => "rad".to_string(),
+            Quantity::Energy => format!(
+                "{} {}^2/{}^2",
+                self.mass.name, self.length.name, self.time.name
+            ),
+            Quantity::Momentum => {
+                format!("{} {}/{}", self.mass.name, self.length.name, self.time.name)
+            }
+            Quantity::AngularMomentum => format!(
+                "{} {}^2/{}",
+                self.mass.name, self.length.name, self.time.name
+            ),
+            Quantity::GravitationalConstant => format!(
+                "{}^3/({} {}^2)",
+                self.length.name, self.mass.name, self.time.name
+            ),
+        }
+    }
+
+    /// Real world size of one unit of `quantity`, in metres, seconds, kilograms or radians
+    fn size(&self, quantity: Quantity) -> f64 {
+        match quantity {
+            Quantity::Length => self.length.size,
+            Quantity::Time => self.time.size,
+            Quantity::Mass => self.mass.size,
+            Quantity::Velocity => self.length.size / self.time.size,
+            Quantity::Angle => 1.0,
+            Quantity::Energy => self.mass.size * self.length.size.powi(2) / self.time.size.powi(2),
+            Quantity::Momentum => self.mass.size * self.length.size / self.time.size,
+            Quantity::AngularMomentum => self.mass.size * self.length.size.powi(2) / self.time.size,
+            Quantity::GravitationalConstant => {
+                self.length.size.powi(3) / (self.mass.size * self.time.size.powi(2))
+            }
+        }
+    }
+
+    /// Size of the real world unit called `name`, in the same terms as [`Units::size`]
+    fn size_of(name: &str, quantity: Quantity) -> Result<f64, String> {
+        match quantity {
+            Quantity::Length => Ok(Unit::length(name)?.size),
+            Quantity::Time => Ok(Unit::time(name)?.size),
+            Quantity::Mass => Ok(Unit::mass(name)?.size),
+            Quantity::Velocity => match name.split_once('/') {
+                Some((length, time)) => Ok(Unit::length(length)?.size / Unit::time(time)?.size),
+                None => Err(format!("Unknown unit of velocity '{}'", name)),
+            },
+            Quantity::Angle => match name {
+                "deg" => Ok(1.0_f64.to_radians()),
+                "rad" => Ok(1.0),
+                _ => Err(format!("Unknown unit of angle '{}'", name)),
+            },
+            _ => Err(format!(
+                "Can not use '{}' for this value - give it in the units of the simulation",
+                name
+            )),
+        }
+    }
+
+    /// The gravitational constant in these units
+    pub fn gravitational_constant(&self) -> Scalar {
+        (GRAVITATIONAL_CONSTANT / self.size(Quantity::GravitationalConstant)) as Scalar
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "length: {}, time: {}, mass: {}",
+            self.length.name, self.time.name, self.mass.name
+        )
+    }
+}
+
+/// How values in the simulation are measured
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum UnitSystem {
+    /// Values have no units, and gravity has the strength of -1 it has always had, which pushes
+    /// nodes apart
+    #[default]
+    Dimensionless,
+    /// Values are in real world units, and gravity has its real strength
+    Physical(Units),
+}
+
+impl fmt::Display for UnitSystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnitSystem::Dimensionless => write!(f, "dimensionless"),
+            UnitSystem::Physical(units) => write!(f, "{}", units),
+        }
+    }
+}
+
+impl UnitSystem {
+    /// The gravitational constant simulations use when they are set to these units
+    pub fn gravitational_constant(&self) -> Scalar {
+        match self {
+            UnitSystem::Dimensionless => -1.0,
+            UnitSystem::Physical(units) => units.gravitational_constant(),
+        }
+    }
+
+    /// Distance beyond which nodes do not interact. Dimensionless simulations keep the radius they
+    /// have always had, while gravity reaches any distance in real world units
+    pub fn force_radius(&self) -> Option<Scalar> {
+        match self {
+            UnitSystem::Dimensionless => Some(Force::FORCE_RADIUS),
+            UnitSystem::Physical(_) => None,
+        }
+    }
+
+    /// Converts `value`, written in `unit`, into the units of the simulation. Values without a unit
+    /// are taken to already be in them, apart from angles which are in degrees
+    pub fn convert(&self, value: Scalar, unit: &str, quantity: Quantity) -> Result<Scalar, String> {
+        let unit = match (quantity, unit) {
+            (Quantity::Angle, "") => "deg",
+            (_, "") => return Ok(value),
+            (_, unit) => unit,
+        };
+        let size = Units::size_of(unit, quantity)?;
+        let system_size = match (self, quantity) {
+            (_, Quantity::Angle) => 1.0,
+            (UnitSystem::Physical(units), _) => units.size(quantity),
+            (UnitSystem::Dimensionless, _) => {
+                return Err(format!(
+                    "Can not use '{}' as the simulation is dimensionless - set its units first",
+                    unit
+                ))
+            }
+        };
+        Ok(value * (size / system_size) as Scalar)
+    }
+
+    /// Unit to print after a value of `quantity`, including the space before it. Dimensionless
+    /// values are printed as they are
+    pub fn suffix(&self, quantity: Quantity) -> String {
+        match self {
+            UnitSystem::Dimensionless => String::new(),
+            UnitSystem::Physical(units) => format!(" {}", units.name(quantity)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod a_unit_system {
+    use rstest::rstest;
+
+    use super::*;
+
+    const SI: UnitSystem = UnitSystem::Physical(Units::SI);
+
+    #[rstest]
+    #[case(2.0, "", Quantity::Length, 2.0)]
+    #[case(2.0, "km", Quantity::Length, 2000.0)]
+    #[case(3.0, "km/s", Quantity::Velocity, 3000.0)]
+    #[case(1.0, "m/min", Quantity::Velocity, 1.0 / 60.0)]
+    #[case(2.0, "day", Quantity::Time, 172800.0)]
+    #[case(1.0, "rad", Quantity::Angle, 1.0)]
+    fn converts_values_into_metres_and_seconds_in_si(
+        #[case] value: Scalar,
+        #[case] unit: &str,
+        #[case] quantity: Quantity,
+        #[case] expected_value: Scalar,
+    ) {
+        assert_eq!(Ok(expected_value), SI.convert(value, unit, quantity));
+    }
+
+    #[test]
+    fn takes_angles_in_degrees_unless_given_in_radians() {
+        let radians = UnitSystem::Dimensionless
+            .convert(180.0, "", Quantity::Angle)
+            .unwrap();
+        assert!((radians - crate::node::consts::PI).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn converts_values_into_its_own_units() {
+        let units = UnitSystem::Physical(Units::ASTRONOMICAL);
+
+        let length = units.convert(2.992e8, "km", Quantity::Length);
+        assert!((length.unwrap() - 2.0).abs() < 1.0e-4);
+        let velocity = units.convert(1.0, "AU/day", Quantity::Velocity);
+        assert!((velocity.unwrap() - 1.0).abs() < 1.0e-6);
+        assert_eq!(Ok(5.0), units.convert(5.0, "", Quantity::Velocity));
+    }
+
+    #[rstest]
+    #[case(SI, "ly", Quantity::Length)]
+    #[case(SI, "km", Quantity::Velocity)]
+    #[case(SI, "km/year", Quantity::Velocity)]
+    #[case(SI, "m", Quantity::Angle)]
+    #[case(SI, "J", Quantity::Energy)]
+    #[case(UnitSystem::Dimensionless, "m", Quantity::Length)]
+    fn rejects_units_it_can_not_convert(
+        #[case] units: UnitSystem,
+        #[case] unit: &str,
+        #[case] quantity: Quantity,
+    ) {
+        assert!(units.convert(1.0, unit, quantity).is_err());
+    }
+
+    #[test]
+    fn gives_the_real_gravitational_constant_in_its_units() {
+        let g = Units::ASTRONOMICAL.gravitational_constant();
+        assert!((g - 2.959_122e-4).abs() < 1.0e-8, "g was {g}");
+        assert_eq!(-1.0, UnitSystem::Dimensionless.gravitational_constant());
+    }
+
+    #[test]
+    fn prints_units_after_values_unless_dimensionless() {
+        let units = UnitSystem::Physical(Units::ASTRONOMICAL);
+        assert_eq!(" AU/day", units.suffix(Quantity::Velocity));
+        assert_eq!("", UnitSystem::Dimensionless.suffix(Quantity::Mass));
+    }
+
+    #[rstest]
+    #[case(Quantity::Energy, " kg m^2/s^2")]
+    #[case(Quantity::Momentum, " kg m/s")]
+    #[case(Quantity::AngularMomentum, " kg m^2/s")]
+    #[case(Quantity::GravitationalConstant, " m^3/(kg s^2)")]
+    fn names_quantities_made_of_several_units(
+        #[case] quantity: Quantity,
+        #[case] expected_suffix: &str,
+    ) {
+        assert_eq!(expected_suffix, SI.suffix(quantity));
+    }
+}
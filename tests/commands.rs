@@ -10,6 +10,10 @@ Commands:
   close         
   step          
   script        
+  at            
+  every         
+  assert        
+  expect        
   help          Print this message or the help of the given subcommand(s)
 
 "#;
@@ -19,6 +23,35 @@ Node 1:
 	position: x: 1, y: 2, z: 3
 "#;
 
+const EXPECTED_SCHEDULED_COMMAND_OUTPUT: &str = r#"Running node_simulator...
+Scheduled 0: at tick 1000000 - add node --id 1 --position 1,2,3
+0: at tick 1000000 - add node --id 1 --position 1,2,3
+Nothing scheduled
+"#;
+
+const EXPECTED_SCRIPT_COMMAND_OUTPUT: &str = r#"Running node_simulator...
+Node 2:
+	mass: 1
+Node 3:
+	mass: 6
+"#;
+
+const EXPECTED_EXIT_AFTER_OUTPUT: &str = r#"Node 1:
+	position: x: 1, y: 2, z: 3
+Stats:
+	tick: 2
+"#;
+
+const EXPECTED_ASSERTIONS_OUTPUT: &str = r#"tests/scripts/assertions.txt:4 - Expectation failed - mass was 6, expected 5 (tolerance 0.5)
+tests/scripts/assertions.txt:6 - Assertion failed - mass was 1, expected 2 (tolerance 0.000001)
+2 of 4 checks passed
+"#;
+
+const EXPECTED_UNITS_OUTPUT: &str = r#"Node 1:
+	mass: 2 Mearth
+units: length: km, time: s, mass: Mearth
+"#;
+
 const EXPECTED_REMOVE_NODE_COMMAND_OUTPUT: &str = r#"Running node_simulator...
 Error displaying node information for node with id 1 - no node with that id exists
 "#;
@@ -75,3 +108,115 @@ fn can_execute_remove_node_command() {
 
     common::Binary::kill(process);
 }
+
+#[test]
+fn can_schedule_and_cancel_command() {
+    let mut process = common::Binary::get();
+    let std_in = process.stdin.take().expect("Child had no stdin");
+    let std_out = process.stdout.take().expect("Child had no stdout");
+
+    let commands = [
+        "at 1000000 add node --id 1 --position 1,2,3",
+        "get schedule",
+        "remove schedule --handle 0",
+        "get schedule",
+    ];
+
+    common::Write::write_lines_to_cli(std_in, commands.iter());
+    let output = common::Read::read_from_cli(std_out);
+
+    assert_eq!(EXPECTED_SCHEDULED_COMMAND_OUTPUT, output);
+
+    common::Binary::kill(process);
+}
+
+#[test]
+fn can_execute_script_command() {
+    let mut process = common::Binary::get();
+    let std_in = process.stdin.take().expect("Child had no stdin");
+    let std_out = process.stdout.take().expect("Child had no stdout");
+
+    let commands = [
+        "script tests/scripts/masses.txt",
+        "get node --id 2 --mass",
+        "get node --id 3 --mass",
+    ];
+
+    common::Write::write_lines_to_cli(std_in, commands.iter());
+    let output = common::Read::read_from_cli(std_out);
+
+    assert_eq!(EXPECTED_SCRIPT_COMMAND_OUTPUT, output);
+
+    common::Binary::kill(process);
+}
+
+#[test]
+fn can_run_commands_from_args_and_exit() {
+    let output = common::Binary::run(&[
+        "--exec",
+        "add node --id 1 --position 1,2,3",
+        "--steps",
+        "2",
+        "--exec",
+        "get node --id 1 --position",
+        "--exec",
+        "get stats",
+        "--exit-after",
+    ]);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.starts_with(EXPECTED_EXIT_AFTER_OUTPUT), "{stdout}");
+}
+
+#[test]
+fn exits_with_an_error_if_a_command_fails() {
+    let output = common::Binary::run(&[
+        "--script",
+        "tests/scripts/masses.txt",
+        "--exec",
+        "get node --id 100 --mass",
+        "--exit-after",
+    ]);
+
+    assert_eq!(Some(1), output.status.code());
+}
+
+#[test]
+fn reports_failed_checks_and_stops_at_a_failed_assertion() {
+    let output = common::Binary::run(&["--script", "tests/scripts/assertions.txt", "--exit-after"]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        EXPECTED_ASSERTIONS_OUTPUT,
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn reads_and_shows_values_in_the_units_of_the_simulation() {
+    let output = common::Binary::run(&[
+        "--exec",
+        "set units si --length km --mass Mearth",
+        "--exec",
+        "add node --id 1",
+        "--exec",
+        "set node --id 1 --mass 2Mearth",
+        "--exec",
+        "get node --id 1 --mass",
+        "--exec",
+        "get units",
+        "--exec",
+        "get stats",
+        "--exit-after",
+    ]);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.starts_with(EXPECTED_UNITS_OUTPUT), "{stdout}");
+    assert!(stdout.contains(" km^3/(Mearth s^2)\n"), "{stdout}");
+    assert!(
+        stdout.contains("\tkinetic energy: 0 Mearth km^2/s^2\n"),
+        "{stdout}"
+    );
+}
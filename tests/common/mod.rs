@@ -1,6 +1,6 @@
 use std::{
     io::{BufRead, BufReader},
-    process::{Child, ChildStdin, ChildStdout, Stdio},
+    process::{Child, ChildStdin, ChildStdout, Output, Stdio},
     slice::Iter,
     sync::mpsc,
     thread,
@@ -18,6 +18,15 @@ impl Binary {
             .unwrap()
     }
 
+    /// Runs the binary with `args`, without writing to its stdin, until it exits
+    pub fn run(args: &[&str]) -> Output {
+        test_bin::get_test_bin("node_simulator")
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .unwrap()
+    }
+
     pub fn kill(mut process: Child) {
         process.kill().unwrap();
     }